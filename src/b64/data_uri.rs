@@ -0,0 +1,137 @@
+//! `data:` URIs, RFC 2397, to inline small files into HTML and CSS.
+
+use std::path::{Path, PathBuf};
+
+use crate::codec::sniff::{extension_from_mime, mime_from_extension, sniff};
+use crate::codec::write_decoded;
+use crate::input::{read_all, InputSource};
+
+#[derive(clap::Args, Debug)]
+pub struct DataUriArgs {
+    #[clap(flatten)]
+    options: DataUriOptions,
+
+    #[clap(flatten)]
+    input: InputSource,
+}
+
+#[derive(clap::Args, Debug)]
+struct DataUriOptions {
+    /// Read a `data:` URI and write its payload to a file instead
+    #[clap(long, short)]
+    decode: bool,
+
+    /// MIME type to use instead of the detected one
+    #[clap(long, value_name = "TYPE", conflicts_with = "decode")]
+    mime: Option<String>,
+
+    /// Minify HTML, SVG and JSON before encoding
+    #[clap(long, conflicts_with = "decode")]
+    minify: bool,
+
+    /// File to write the decoded payload to, the extension of the MIME type is
+    /// added when it has none [default: data.<ext>]
+    #[clap(long, short, value_name = "FILE", requires = "decode")]
+    output: Option<String>,
+}
+
+pub fn run(args: DataUriArgs) -> anyhow::Result<()> {
+    if args.options.decode {
+        return decode(&args.options, read_all(args.input)?);
+    }
+
+    // text formats can only be told apart by the extension of the file name
+    let file_name = match args.input.raw {
+        true => None,
+        false => args.input.input.clone(),
+    };
+    encode(&args.options, file_name.as_deref(), read_all(args.input)?)
+}
+
+fn encode(options: &DataUriOptions, file_name: Option<&str>, data: Vec<u8>) -> anyhow::Result<()> {
+    let mime = match &options.mime {
+        Some(mime) => mime.clone(),
+        None => detect_mime(file_name, &data).to_string(),
+    };
+
+    let data = if options.minify {
+        let text =
+            String::from_utf8(data).map_err(|_| anyhow::anyhow!("Can not minify binary data"))?;
+        match mime.as_str() {
+            "text/html" | "image/svg+xml" => minify::html::minify(&text).into_bytes(),
+            "application/json" => minify::json::minify(&text).into_bytes(),
+            _ => anyhow::bail!("Can not minify {}, only HTML, SVG and JSON", mime),
+        }
+    } else {
+        data
+    };
+
+    // text is US-ASCII unless the URI says otherwise
+    let charset = match std::str::from_utf8(&data) {
+        Ok(text) if mime.starts_with("text/") && !text.is_ascii() => ";charset=utf-8",
+        _ => "",
+    };
+    println!("data:{}{};base64,{}", mime, charset, base64::encode(&data));
+    Ok(())
+}
+
+/// The MIME type from the content, then the file extension, falling back to plain text
+/// or arbitrary binary data.
+fn detect_mime(file_name: Option<&str>, data: &[u8]) -> &'static str {
+    if let Some(file_type) = sniff(data) {
+        return file_type.mime;
+    }
+    let by_extension = file_name
+        .and_then(|name| Path::new(name).extension())
+        .and_then(|ext| mime_from_extension(&ext.to_string_lossy()));
+    match by_extension {
+        Some(mime) => mime,
+        None if std::str::from_utf8(data).is_ok() => "text/plain",
+        None => "application/octet-stream",
+    }
+}
+
+fn decode(options: &DataUriOptions, input: Vec<u8>) -> anyhow::Result<()> {
+    let input = String::from_utf8_lossy(&input);
+    let uri = input.trim();
+    let rest = match uri.get(..5) {
+        Some(scheme) if scheme.eq_ignore_ascii_case("data:") => &uri[5..],
+        _ => anyhow::bail!("Not a data: URI"),
+    };
+    let (header, payload) = rest
+        .split_once(',')
+        .ok_or_else(|| anyhow::anyhow!("Missing the `,` before the data"))?;
+
+    let mut params = header.split(';');
+    let mime = params
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    let is_base64 = params.any(|p| p.trim().eq_ignore_ascii_case("base64"));
+
+    let payload = crate::url::percent_decode(payload);
+    let data = if is_base64 {
+        super::decode_lenient(&payload)?
+    } else {
+        payload
+    };
+
+    let extension = extension_from_mime(&mime)
+        .or_else(|| sniff(&data).and_then(|t| extension_from_mime(t.mime)))
+        .unwrap_or(match mime.as_str() {
+            // the default of RFC 2397
+            "" => "txt",
+            _ => "bin",
+        });
+    let path = output_path(options.output.as_deref(), extension);
+    write_decoded(data, Some(&path.to_string_lossy()))
+}
+
+fn output_path(output: Option<&str>, extension: &str) -> PathBuf {
+    let path = PathBuf::from(output.unwrap_or("data"));
+    match path.extension() {
+        Some(_) => path,
+        None => path.with_extension(extension),
+    }
+}
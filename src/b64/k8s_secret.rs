@@ -0,0 +1,844 @@
+//! Decoding and encoding the values of Kubernetes `Secret` manifests, in YAML or JSON.
+//!
+//! YAML is edited line by line rather than parsed and printed again, so comments,
+//! ordering and formatting outside of the converted entries are kept as they are.
+
+use anyhow::Context;
+use serde_json::{Map, Value};
+
+use crate::input::{for_text_input, InputSource};
+
+#[derive(clap::Args, Debug)]
+pub struct K8sSecretArgs {
+    #[clap(flatten)]
+    options: K8sSecretOptions,
+
+    #[clap(flatten)]
+    input: InputSource,
+}
+
+#[derive(clap::Args, Debug)]
+struct K8sSecretOptions {
+    /// Move the `stringData` values into `data`, base64 encoded, instead of decoding
+    /// `data` into `stringData`
+    #[clap(long)]
+    encode: bool,
+
+    /// Only convert these keys, can be repeated or comma separated
+    #[clap(long, value_name = "KEY", use_value_delimiter = true)]
+    keys: Vec<String>,
+
+    /// Print the decoded values as a .env file instead of the manifest
+    #[clap(long, conflicts_with = "encode")]
+    env: bool,
+}
+
+impl K8sSecretOptions {
+    /// The field the values are taken from and the one they are moved to.
+    fn fields(&self) -> (&'static str, &'static str) {
+        if self.encode {
+            ("stringData", "data")
+        } else {
+            ("data", "stringData")
+        }
+    }
+
+    fn selected(&self, key: &str) -> bool {
+        self.keys.is_empty() || self.keys.iter().any(|k| k == key)
+    }
+}
+
+/// Converts the values and keeps track of what was converted across the documents.
+struct Converter<'a> {
+    options: &'a K8sSecretOptions,
+    secrets: usize,
+    seen: Vec<String>,
+    converted: Vec<(String, String)>,
+}
+
+impl<'a> Converter<'a> {
+    fn new(options: &'a K8sSecretOptions) -> Self {
+        Converter {
+            options,
+            secrets: 0,
+            seen: Vec::new(),
+            converted: Vec::new(),
+        }
+    }
+
+    /// The converted value, `None` when a decoded value is binary and can not be
+    /// moved to `stringData`.
+    fn convert(&mut self, key: &str, value: &str) -> anyhow::Result<Option<String>> {
+        self.seen.push(key.to_string());
+        let converted = if self.options.encode {
+            base64::encode(value)
+        } else {
+            let cleaned: String = value.split_whitespace().collect();
+            let bytes = base64::decode(&cleaned)
+                .with_context(|| format!("The value of '{}' is not valid base64", key))?;
+            match String::from_utf8(bytes) {
+                Ok(text) => text,
+                Err(_) => {
+                    eprintln!("Keeping '{}' in data, its value is binary", key);
+                    return Ok(None);
+                }
+            }
+        };
+        self.converted.push((key.to_string(), converted.clone()));
+        Ok(Some(converted))
+    }
+
+    fn finish(&self) -> anyhow::Result<()> {
+        if self.secrets == 0 {
+            anyhow::bail!("No Secret found in the input");
+        }
+        if let Some(missing) = self.options.keys.iter().find(|k| !self.seen.contains(k)) {
+            anyhow::bail!("Key '{}' not found in {}", missing, self.options.fields().0);
+        }
+        Ok(())
+    }
+}
+
+pub fn run(args: K8sSecretArgs) -> anyhow::Result<()> {
+    let options = &args.options;
+    for_text_input(args.input, |text| {
+        let mut converter = Converter::new(options);
+        let output = if text.trim_start().starts_with('{') {
+            convert_json(&text, &mut converter)?
+        } else {
+            convert_yaml(&text, &mut converter)?
+        };
+        converter.finish()?;
+
+        if options.env {
+            for (key, value) in &converter.converted {
+                println!("{}={}", key, env_value(value));
+            }
+        } else {
+            println!("{}", output.trim_end_matches('\n'));
+        }
+        Ok(())
+    })
+}
+
+/// Quotes a value for a .env file when it has anything but safe characters.
+fn env_value(value: &str) -> String {
+    let safe = |c: char| c.is_ascii_alphanumeric() || "_-.,/:@+=%".contains(c);
+    if value.chars().all(safe) {
+        return value.to_string();
+    }
+    let mut quoted = String::from('"');
+    for c in value.chars() {
+        match c {
+            '"' | '\\' | '$' | '`' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            '\n' => quoted.push_str("\\n"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn convert_json(text: &str, converter: &mut Converter) -> anyhow::Result<String> {
+    let mut json: Value = serde_json::from_str(text).context("Invalid JSON")?;
+    let is_kind =
+        |value: &Value, kind: &str| value.get("kind").and_then(Value::as_str) == Some(kind);
+
+    // `kubectl get secrets -o json` prints a List of them
+    if is_kind(&json, "List") {
+        if let Some(items) = json.get_mut("items").and_then(Value::as_array_mut) {
+            for item in items.iter_mut().filter(|i| is_kind(i, "Secret")) {
+                if let Some(secret) = item.as_object_mut() {
+                    convert_json_secret(secret, converter)?;
+                }
+            }
+        }
+    } else if is_kind(&json, "Secret") {
+        if let Some(secret) = json.as_object_mut() {
+            convert_json_secret(secret, converter)?;
+        }
+    }
+
+    Ok(serde_json::to_string_pretty(&json)?)
+}
+
+fn convert_json_secret(
+    secret: &mut Map<String, Value>,
+    converter: &mut Converter,
+) -> anyhow::Result<()> {
+    converter.secrets += 1;
+    let (from, to) = converter.options.fields();
+    let entries = match secret.get(from) {
+        Some(Value::Object(entries)) => entries.clone(),
+        Some(Value::Null) | None => return Ok(()),
+        Some(_) => anyhow::bail!("`{}` is not an object", from),
+    };
+
+    let mut remaining = Map::new();
+    let mut moved = Map::new();
+    for (key, value) in entries {
+        if !converter.options.selected(&key) {
+            remaining.insert(key, value);
+            continue;
+        }
+        let text = value
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("The value of '{}' is not a string", key))?;
+        match converter.convert(&key, text)? {
+            Some(converted) => {
+                moved.insert(key, Value::String(converted));
+            }
+            None => {
+                remaining.insert(key, value);
+            }
+        }
+    }
+    if moved.is_empty() {
+        return Ok(());
+    }
+
+    // rebuilt to keep the position of the fields
+    let has_to = secret.contains_key(to);
+    for (key, value) in std::mem::take(secret) {
+        if key == from {
+            if !remaining.is_empty() {
+                secret.insert(key, Value::Object(std::mem::take(&mut remaining)));
+            }
+            if !has_to {
+                secret.insert(to.to_string(), Value::Object(std::mem::take(&mut moved)));
+            }
+        } else if key == to {
+            let mut merged = match value {
+                Value::Object(existing) => existing,
+                _ => Map::new(),
+            };
+            merged.extend(std::mem::take(&mut moved));
+            secret.insert(key, Value::Object(merged));
+        } else {
+            secret.insert(key, value);
+        }
+    }
+    Ok(())
+}
+
+fn convert_yaml(text: &str, converter: &mut Converter) -> anyhow::Result<String> {
+    let mut output = Vec::new();
+    let mut document = Vec::new();
+    for line in text.lines() {
+        if is_document_marker(line) {
+            output.extend(convert_yaml_document(&document, converter)?);
+            output.push(line.to_string());
+            document.clear();
+        } else {
+            document.push(line.to_string());
+        }
+    }
+    output.extend(convert_yaml_document(&document, converter)?);
+    Ok(output.join("\n"))
+}
+
+fn is_document_marker(line: &str) -> bool {
+    ["---", "..."].iter().any(|marker| {
+        line.strip_prefix(marker)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with([' ', '\t']))
+    })
+}
+
+/// A top level `key: value` and the lines below it.
+struct Section {
+    key: Option<String>,
+    lines: Vec<String>,
+}
+
+fn sections(document: &[String]) -> anyhow::Result<Vec<Section>> {
+    let mut sections = vec![Section {
+        key: None,
+        lines: Vec::new(),
+    }];
+    for line in document {
+        // sequence entries may sit at the indentation of their key, as in `items:`
+        let entry = line == "-" || line.starts_with("- ");
+        let top_level =
+            !line.starts_with([' ', '\t']) && !line.is_empty() && !line.starts_with('#') && !entry;
+        if top_level {
+            let (key, _) = split_key(line)
+                .ok_or_else(|| anyhow::anyhow!("Expected a `key: value` line: {}", line))?;
+            sections.push(Section {
+                key: Some(key),
+                lines: Vec::new(),
+            });
+        }
+        if let Some(section) = sections.last_mut() {
+            section.lines.push(line.clone());
+        }
+    }
+    Ok(sections)
+}
+
+fn convert_yaml_document(
+    document: &[String],
+    converter: &mut Converter,
+) -> anyhow::Result<Vec<String>> {
+    let sections = sections(document)?;
+    let field = |name: &str| sections.iter().position(|s| s.key.as_deref() == Some(name));
+    let is_kind = |kind: &str| {
+        field("kind").is_some_and(|i| {
+            let (_, value) = split_key(&sections[i].lines[0]).unwrap_or_default();
+            parse_scalar(value, &[]).is_ok_and(|k| k == kind)
+        })
+    };
+
+    // `kubectl get secrets -o yaml` prints a List of them
+    if let (true, Some(items)) = (is_kind("List"), field("items")) {
+        let mut output = Vec::new();
+        for (i, section) in sections.iter().enumerate() {
+            match i == items {
+                true => output.extend(convert_yaml_items(&section.lines, converter)?),
+                false => output.extend(section.lines.iter().cloned()),
+            }
+        }
+        return Ok(output);
+    }
+    if !is_kind("Secret") {
+        return Ok(document.to_vec());
+    }
+    converter.secrets += 1;
+
+    let (from, to) = converter.options.fields();
+    let from_index = match field(from) {
+        Some(i) => i,
+        None => return Ok(document.to_vec()),
+    };
+    let to_index = field(to);
+
+    let from_mapping = Mapping::parse(&sections[from_index].lines)?;
+    let mut remaining = Vec::new();
+    let mut moved = Vec::new();
+    for entry in from_mapping.entries {
+        if !converter.options.selected(&entry.key) {
+            remaining.push(entry);
+            continue;
+        }
+        match converter.convert(&entry.key, &entry.value()?)? {
+            Some(converted) => moved.push((entry.leading, entry.key, converted)),
+            None => remaining.push(entry),
+        }
+    }
+    if moved.is_empty() {
+        return Ok(document.to_vec());
+    }
+
+    let mut output = Vec::new();
+    for (i, section) in sections.iter().enumerate() {
+        if i == from_index {
+            if !remaining.is_empty() {
+                output.push(from_mapping.header.clone());
+                for entry in &remaining {
+                    output.extend(entry.leading.iter().chain(&entry.lines).cloned());
+                }
+            }
+            if to_index.is_none() {
+                output.push(format!("{}:", to));
+                let indent = from_mapping.indent.as_deref().unwrap_or("  ");
+                output.extend(render_entries(&moved, indent));
+            }
+            output.extend(from_mapping.trailer.iter().cloned());
+        } else if Some(i) == to_index {
+            let mut to_mapping = Mapping::parse(&section.lines)?;
+            // the converted value wins, as it does when Kubernetes merges the two fields
+            to_mapping
+                .entries
+                .retain(|entry| !moved.iter().any(|(_, key, _)| *key == entry.key));
+            output.push(to_mapping.header.clone());
+            for entry in &to_mapping.entries {
+                output.extend(entry.leading.iter().chain(&entry.lines).cloned());
+            }
+            let indent = to_mapping.indent.as_deref().unwrap_or("  ");
+            output.extend(render_entries(&moved, indent));
+            output.extend(to_mapping.trailer.iter().cloned());
+        } else {
+            output.extend(section.lines.iter().cloned());
+        }
+    }
+    Ok(output)
+}
+
+/// The `items:` section of a List, each item is converted as a document of its own.
+fn convert_yaml_items(lines: &[String], converter: &mut Converter) -> anyhow::Result<Vec<String>> {
+    let is_item = |line: &str| {
+        let content = line.trim_start();
+        content == "-" || content.starts_with("- ")
+    };
+    let mut output = vec![lines[0].clone()];
+    let mut item: Vec<String> = Vec::new();
+    let mut item_indent = None;
+    for line in &lines[1..] {
+        let indent = line.len() - line.trim_start().len();
+        if is_item(line) && item_indent.is_none_or(|i| i == indent) {
+            if !item.is_empty() {
+                output.extend(convert_yaml_item(&item, converter)?);
+            }
+            item = vec![line.clone()];
+            item_indent = Some(indent);
+        } else if item.is_empty() {
+            output.push(line.clone());
+        } else {
+            item.push(line.clone());
+        }
+    }
+    if !item.is_empty() {
+        output.extend(convert_yaml_item(&item, converter)?);
+    }
+    Ok(output)
+}
+
+/// A `- key: value` item and the lines below it, moved to the left to be converted as
+/// a document and indented again.
+fn convert_yaml_item(lines: &[String], converter: &mut Converter) -> anyhow::Result<Vec<String>> {
+    let first = &lines[0];
+    let dash = first.len() - first.trim_start().len();
+    let content = first.len() - first[dash + 1..].trim_start().len();
+    let indent = " ".repeat(content);
+
+    let mut document = vec![first[content..].to_string()];
+    for line in &lines[1..] {
+        let dedented = match line.get(..content) {
+            Some(prefix) if prefix.trim().is_empty() => &line[content..],
+            // blank lines and comments to the left of the item
+            _ => line.trim_start(),
+        };
+        document.push(dedented.to_string());
+    }
+
+    let converted = convert_yaml_document(&document, converter)?;
+    let mut output = Vec::new();
+    for (i, line) in converted.iter().enumerate() {
+        output.push(match (i, line.as_str()) {
+            (0, line) => format!("{}{}", &first[..content], line),
+            (_, "") => String::new(),
+            (_, line) => format!("{}{}", indent, line),
+        });
+    }
+    Ok(output)
+}
+
+/// A block mapping of strings, as in `data` and `stringData`.
+struct Mapping {
+    header: String,
+    indent: Option<String>,
+    entries: Vec<Entry>,
+    /// Blank lines and comments after the last entry.
+    trailer: Vec<String>,
+}
+
+struct Entry {
+    /// Blank lines and comments before the entry.
+    leading: Vec<String>,
+    key: String,
+    lines: Vec<String>,
+}
+
+impl Mapping {
+    fn parse(lines: &[String]) -> anyhow::Result<Mapping> {
+        let (key, value) = split_key(&lines[0]).unwrap_or_default();
+        let header = match strip_comment(value).trim() {
+            "" => lines[0].clone(),
+            // nothing to keep from an empty flow mapping once entries are added
+            "{}" => format!("{}:", key),
+            _ => anyhow::bail!("Only block mappings are supported: {}", lines[0]),
+        };
+
+        let mut mapping = Mapping {
+            header,
+            indent: None,
+            entries: Vec::new(),
+            trailer: Vec::new(),
+        };
+        let mut pending = Vec::new();
+        for line in &lines[1..] {
+            let content = line.trim_start();
+            let indent = &line[..line.len() - content.len()];
+            let deeper = mapping
+                .indent
+                .as_ref()
+                .is_some_and(|i| indent.len() > i.len());
+            if content.is_empty() || (content.starts_with('#') && !deeper) {
+                pending.push(line.clone());
+                continue;
+            }
+
+            match (&mapping.indent, mapping.entries.last_mut()) {
+                // a continuation of the value, blank lines within it included
+                (Some(_), Some(entry)) if deeper => {
+                    entry.lines.append(&mut pending);
+                    entry.lines.push(line.clone());
+                }
+                (Some(i), _) if indent != i => {
+                    anyhow::bail!("Unexpected indentation: {}", line)
+                }
+                _ => {
+                    mapping.indent = Some(indent.to_string());
+                    let (key, _) = split_key(content)
+                        .ok_or_else(|| anyhow::anyhow!("Expected a `key: value` line: {}", line))?;
+                    mapping.entries.push(Entry {
+                        leading: std::mem::take(&mut pending),
+                        key,
+                        lines: vec![line.clone()],
+                    });
+                }
+            }
+        }
+        mapping.trailer = pending;
+        Ok(mapping)
+    }
+}
+
+impl Entry {
+    fn value(&self) -> anyhow::Result<String> {
+        let first = &self.lines[0];
+        let (_, value) = split_key(first).unwrap_or_default();
+        let value = value.trim();
+        let parsed = if value.starts_with('|') || value.starts_with('>') {
+            let indent = first.len() - first.trim_start().len();
+            parse_block_scalar(value, &self.lines[1..], indent)
+        } else {
+            parse_scalar(value, &self.lines[1..])
+        };
+        parsed.with_context(|| format!("Reading the value of '{}'", self.key))
+    }
+}
+
+/// Splits `key: value` into the unquoted key and the rest of the line.
+fn split_key(line: &str) -> Option<(String, &str)> {
+    let line = line.trim_start();
+    for quote in ['"', '\''] {
+        if line.starts_with(quote) {
+            let end = line[1..].find(quote)? + 1;
+            let rest = line[end + 1..].trim_start().strip_prefix(':')?;
+            return Some((parse_scalar(&line[..=end], &[]).ok()?, rest));
+        }
+    }
+
+    let colon = line
+        .match_indices(':')
+        .map(|(i, _)| i)
+        .find(|&i| line[i + 1..].is_empty() || line[i + 1..].starts_with([' ', '\t']))?;
+    Some((line[..colon].trim_end().to_string(), &line[colon + 1..]))
+}
+
+fn strip_comment(value: &str) -> &str {
+    match value.find(" #") {
+        Some(i) => &value[..i],
+        None if value.trim_start().starts_with('#') => "",
+        None => value,
+    }
+}
+
+/// Parses a plain or quoted scalar, `rest` is what follows the key on its line and
+/// `continuation` the more indented lines below it.
+fn parse_scalar(rest: &str, continuation: &[String]) -> anyhow::Result<String> {
+    let value = rest.trim();
+
+    // flow scalars may be folded over several lines, the breaks become spaces
+    let mut joined = value.to_string();
+    for line in continuation {
+        let line = line.trim();
+        if !line.is_empty() {
+            joined.push(' ');
+            joined.push_str(line);
+        }
+    }
+
+    if let Some(quoted) = joined.strip_prefix('\'') {
+        let mut out = String::new();
+        let mut chars = quoted.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '\'' if chars.peek() == Some(&'\'') => {
+                    chars.next();
+                    out.push('\'');
+                }
+                '\'' => return Ok(out),
+                c => out.push(c),
+            }
+        }
+        anyhow::bail!("Unterminated single quoted string");
+    }
+
+    if let Some(quoted) = joined.strip_prefix('"') {
+        let mut out = String::new();
+        let mut chars = quoted.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => return Ok(out),
+                '\\' => match chars.next() {
+                    Some('n') => out.push('\n'),
+                    Some('t') => out.push('\t'),
+                    Some('r') => out.push('\r'),
+                    Some('0') => out.push('\0'),
+                    Some(c @ ('x' | 'u' | 'U')) => {
+                        let len = match c {
+                            'x' => 2,
+                            'u' => 4,
+                            _ => 8,
+                        };
+                        let hex: String = chars.by_ref().take(len).collect();
+                        let c = u32::from_str_radix(&hex, 16)
+                            .ok()
+                            .and_then(char::from_u32)
+                            .ok_or_else(|| anyhow::anyhow!("Invalid escape \\{}{}", c, hex))?;
+                        out.push(c);
+                    }
+                    Some(c) => out.push(c),
+                    None => break,
+                },
+                c => out.push(c),
+            }
+        }
+        anyhow::bail!("Unterminated double quoted string");
+    }
+
+    Ok(match strip_comment(&joined).trim() {
+        "~" | "null" => String::new(),
+        plain => plain.to_string(),
+    })
+}
+
+/// Literal `|` and folded `>` scalars, with their `-` and `+` chomping indicators.
+fn parse_block_scalar(
+    header: &str,
+    lines: &[String],
+    parent_indent: usize,
+) -> anyhow::Result<String> {
+    let header = strip_comment(header).trim();
+    let folded = header.starts_with('>');
+    let mut chomp = None;
+    let mut indent = None;
+    for c in header[1..].chars() {
+        match c {
+            '-' | '+' => chomp = Some(c),
+            '1'..='9' => indent = c.to_digit(10).map(|d| d as usize),
+            _ => anyhow::bail!("Invalid block scalar header '{}'", header),
+        }
+    }
+
+    // an explicit indentation is relative to the entry, it is only needed when the
+    // value starts with spaces
+    let indent = match indent {
+        Some(i) => parent_indent + i,
+        None => lines
+            .iter()
+            .find(|l| !l.trim().is_empty())
+            .map_or(0, |l| l.len() - l.trim_start().len()),
+    };
+    let lines: Vec<&str> = lines
+        .iter()
+        .map(|l| l.get(indent..).unwrap_or_default())
+        .collect();
+
+    let mut text = String::new();
+    for (i, line) in lines.iter().enumerate() {
+        if i > 0 {
+            let previous = lines[i - 1];
+            // folding joins lines of text, but keeps line breaks around blank and
+            // more indented lines
+            let fold = folded
+                && !line.is_empty()
+                && !previous.is_empty()
+                && !line.starts_with([' ', '\t'])
+                && !previous.starts_with([' ', '\t']);
+            text.push(if fold { ' ' } else { '\n' });
+        }
+        text.push_str(line);
+    }
+
+    let content = text.trim_end_matches('\n');
+    Ok(match chomp {
+        Some('-') => content.to_string(),
+        Some('+') if !text.is_empty() => format!("{}\n", text),
+        _ if content.is_empty() => String::new(),
+        _ => format!("{}\n", content),
+    })
+}
+
+fn render_entries(entries: &[(Vec<String>, String, String)], indent: &str) -> Vec<String> {
+    let mut lines = Vec::new();
+    for (leading, key, value) in entries {
+        lines.extend(leading.iter().cloned());
+        let key = match key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-._".contains(c))
+        {
+            true => key.clone(),
+            false => quote(key),
+        };
+        lines.extend(render_value(value, indent, &key));
+    }
+    lines
+}
+
+/// `key: value`, with a literal block scalar for multi-line values.
+fn render_value(value: &str, indent: &str, key: &str) -> Vec<String> {
+    let trailing = value.len() - value.trim_end_matches('\n').len();
+    // trailing blank lines would be taken for the ones between entries
+    let literal = value.contains('\n')
+        && trailing <= 1
+        && !value.trim().is_empty()
+        && !value.starts_with([' ', '\t', '\n'])
+        && !value
+            .chars()
+            .any(|c| c.is_control() && c != '\n' && c != '\t');
+    if !literal {
+        let value = if is_plain_safe(value) {
+            value.to_string()
+        } else {
+            quote(value)
+        };
+        return vec![format!("{}{}: {}", indent, key, value)];
+    }
+
+    let chomp = if trailing == 0 { "-" } else { "" };
+    let body = value.strip_suffix('\n').unwrap_or(value);
+    let mut lines = vec![format!("{}{}: |{}", indent, key, chomp)];
+    for line in body.split('\n') {
+        lines.push(match line {
+            "" => String::new(),
+            line => format!("{}  {}", indent, line),
+        });
+    }
+    lines
+}
+
+/// Whether the value reads back as the same string without quotes.
+fn is_plain_safe(value: &str) -> bool {
+    const KEYWORDS: &[&str] = &[
+        "true", "false", "yes", "no", "on", "off", "y", "n", "null", "~",
+    ];
+    let first = match value.chars().next() {
+        Some(c) => c,
+        None => return false,
+    };
+    !first.is_ascii_digit()
+        && !"-+.?:,[]{}#&*!|>'\"%@`".contains(first)
+        && value.trim() == value
+        && !value.contains(": ")
+        && !value.contains(" #")
+        && !value.ends_with(':')
+        && !value.chars().any(char::is_control)
+        && !KEYWORDS.iter().any(|k| value.eq_ignore_ascii_case(k))
+}
+
+/// A double quoted YAML string.
+fn quote(value: &str) -> String {
+    let mut quoted = String::from('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            '\r' => quoted.push_str("\\r"),
+            c if c.is_control() => quoted.push_str(&format!("\\x{:02x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn convert(text: &str, encode: bool) -> (String, Vec<(String, String)>) {
+        let options = K8sSecretOptions {
+            encode,
+            keys: Vec::new(),
+            env: false,
+        };
+        let mut converter = Converter::new(&options);
+        let output = convert_yaml(text, &mut converter).unwrap();
+        converter.finish().unwrap();
+        (output + "\n", converter.converted)
+    }
+
+    #[test]
+    fn yaml_list() {
+        // as printed by `kubectl get secrets -o yaml`
+        let list = "\
+apiVersion: v1
+items:
+- apiVersion: v1
+  data:
+    password: cGFzcw==
+  kind: Secret
+  metadata:
+    name: a
+- apiVersion: v1
+  data:
+    x: eQ==
+  kind: ConfigMap
+- apiVersion: v1
+  data:
+    token: bXVsdGkKbGluZQo=
+  kind: Secret
+kind: List
+metadata:
+  resourceVersion: \"\"
+";
+        let (decoded, values) = convert(list, false);
+        assert_eq!(
+            decoded,
+            "\
+apiVersion: v1
+items:
+- apiVersion: v1
+  stringData:
+    password: pass
+  kind: Secret
+  metadata:
+    name: a
+- apiVersion: v1
+  data:
+    x: eQ==
+  kind: ConfigMap
+- apiVersion: v1
+  stringData:
+    token: |
+      multi
+      line
+  kind: Secret
+kind: List
+metadata:
+  resourceVersion: \"\"
+"
+        );
+        assert_eq!(
+            values,
+            [("password", "pass"), ("token", "multi\nline\n")]
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+        );
+        assert_eq!(convert(&decoded, true).0, list);
+
+        let indented = "\
+kind: List
+items:
+  - kind: Secret
+    data:
+      password: cGFzcw==
+";
+        assert_eq!(
+            convert(indented, false).0,
+            "\
+kind: List
+items:
+  - kind: Secret
+    stringData:
+      password: pass
+"
+        );
+    }
+}
@@ -0,0 +1,134 @@
+//! Base64 encoding and decoding, with the standard and URL-safe alphabets.
+
+use anyhow::Context;
+
+use crate::codec::{print_wrapped, write_decoded};
+use crate::input::{for_input, InputSource};
+
+mod data_uri;
+mod k8s_secret;
+
+#[derive(clap::Args, Debug)]
+pub struct Base64Arg {
+    #[clap(subcommand)]
+    action: Base64Action,
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum Base64Action {
+    Encode(EncodeArgs),
+    Decode(DecodeArgs),
+
+    /// Encode a file as a `data:` URI for HTML and CSS, or write out the payload of one
+    DataUri(data_uri::DataUriArgs),
+
+    /// Decode the `data` of Kubernetes Secrets into `stringData`, or encode it back
+    K8sSecret(k8s_secret::K8sSecretArgs),
+}
+
+#[derive(clap::Args, Debug)]
+struct EncodeArgs {
+    /// Use the URL and filename safe alphabet (`-` and `_`) as in JWTs
+    #[clap(long)]
+    url_safe: bool,
+
+    /// Omit the `=` padding
+    #[clap(long)]
+    no_pad: bool,
+
+    /// Wrap lines after this many characters, 76 for MIME and 64 for PEM (0 disables wrapping)
+    #[clap(long, value_name = "COLS")]
+    wrap: Option<usize>,
+
+    #[clap(flatten)]
+    input: InputSource,
+}
+
+#[derive(clap::Args, Debug)]
+struct DecodeArgs {
+    /// Use the URL and filename safe alphabet (`-` and `_`) as in JWTs
+    #[clap(long)]
+    url_safe: bool,
+
+    /// Accept either alphabet, missing padding, whitespace anywhere and PEM
+    /// `-----BEGIN ...-----` lines
+    #[clap(long, conflicts_with = "url-safe")]
+    lenient: bool,
+
+    /// Write the decoded bytes to this file instead of stdout
+    #[clap(long, short, value_name = "FILE")]
+    output: Option<String>,
+
+    #[clap(flatten)]
+    input: InputSource,
+}
+
+pub fn run(arg: Base64Arg) -> anyhow::Result<()> {
+    match arg.action {
+        Base64Action::Encode(args) => encode(args).context("Base64 Encoding"),
+        Base64Action::Decode(args) => decode(args).context("Base64 Decoding"),
+        Base64Action::DataUri(args) => data_uri::run(args).context("Data URI"),
+        Base64Action::K8sSecret(args) => k8s_secret::run(args).context("Kubernetes Secret"),
+    }
+}
+
+fn encode(args: EncodeArgs) -> anyhow::Result<()> {
+    let charset = if args.url_safe {
+        base64::CharacterSet::UrlSafe
+    } else {
+        base64::CharacterSet::Standard
+    };
+    let config = base64::Config::new(charset, !args.no_pad);
+
+    for_input(args.input, |input| {
+        print_wrapped(&base64::encode_config(input, config), args.wrap);
+        Ok(())
+    })
+}
+
+fn decode(args: DecodeArgs) -> anyhow::Result<()> {
+    for_input(args.input, |input| {
+        let decoded = if args.lenient {
+            decode_lenient(&input)?
+        } else {
+            let config = if args.url_safe {
+                base64::URL_SAFE
+            } else {
+                base64::STANDARD
+            };
+            // wrapped lines as printed by `encode --wrap`
+            let input: Vec<u8> = input
+                .trim_ascii()
+                .iter()
+                .copied()
+                .filter(|b| !matches!(b, b'\r' | b'\n'))
+                .collect();
+            base64::decode_config(&input, config).context(
+                "Invalid base64, --lenient accepts either alphabet, missing padding and whitespace",
+            )?
+        };
+        write_decoded(decoded, args.output.as_deref())
+    })
+}
+
+/// Decodes base64 in whichever alphabet, ignoring whitespace, padding and PEM armor.
+fn decode_lenient(input: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let text = String::from_utf8_lossy(input);
+    let mut cleaned: Vec<u8> = text
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("-----"))
+        .flat_map(|line| line.bytes())
+        .filter(|b| !b.is_ascii_whitespace())
+        .map(|b| match b {
+            b'-' => b'+',
+            b'_' => b'/',
+            b => b,
+        })
+        .collect();
+    while cleaned.last() == Some(&b'=') {
+        cleaned.pop();
+    }
+
+    let config = base64::STANDARD_NO_PAD.decode_allow_trailing_bits(true);
+    base64::decode_config(&cleaned, config).context("Invalid base64")
+}
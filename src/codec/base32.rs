@@ -0,0 +1,106 @@
+//! Base32 and base32hex from RFC 4648.
+
+pub const STANDARD: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+/// The "Extended Hex" alphabet, which preserves the sort order of the data.
+pub const HEX: &[u8; 32] = b"0123456789ABCDEFGHIJKLMNOPQRSTUV";
+
+/// Encodes with the standard alphabet and `=` padding.
+pub fn encode(data: &[u8]) -> String {
+    encode_with(data, STANDARD, true)
+}
+
+pub fn encode_with(data: &[u8], alphabet: &[u8; 32], pad: bool) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(5) * 8);
+    for block in data.chunks(5) {
+        let mut buf = [0u8; 5];
+        buf[..block.len()].copy_from_slice(block);
+        let bits = buf.iter().fold(0u64, |acc, &b| (acc << 8) | b as u64);
+
+        let symbols = (block.len() * 8).div_ceil(5);
+        for i in 0..8 {
+            if i < symbols {
+                let index = (bits >> (35 - i * 5)) & 0x1f;
+                out.push(alphabet[index as usize] as char);
+            } else if pad {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Decodes case-insensitively, whitespace and padding are optional as they are
+/// often left out of TOTP secrets.
+pub fn decode(text: &str, alphabet: &[u8; 32]) -> anyhow::Result<Vec<u8>> {
+    let symbols: Vec<u8> = text.bytes().filter(|b| !b.is_ascii_whitespace()).collect();
+    let symbols = match symbols.iter().position(|&b| b == b'=') {
+        Some(end) if symbols[end..].iter().all(|&b| b == b'=') => &symbols[..end],
+        Some(end) => anyhow::bail!("Unexpected data after the padding at offset {}", end),
+        None => &symbols[..],
+    };
+    if matches!(symbols.len() % 8, 1 | 3 | 6) {
+        anyhow::bail!("Invalid length, {} symbols", symbols.len());
+    }
+
+    let mut out = Vec::with_capacity(symbols.len() * 5 / 8);
+    let (mut bits, mut count) = (0u64, 0);
+    for (i, &symbol) in symbols.iter().enumerate() {
+        let value = alphabet
+            .iter()
+            .position(|&a| a == symbol.to_ascii_uppercase())
+            .ok_or_else(|| {
+                anyhow::anyhow!("Invalid character '{}' at offset {}", symbol as char, i)
+            })?;
+        bits = (bits << 5) | value as u64;
+        count += 5;
+        if count >= 8 {
+            count -= 8;
+            out.push((bits >> count) as u8);
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, encode_with, HEX, STANDARD};
+
+    /// The test vectors of RFC 4648 section 10.
+    const VECTORS: [(&str, &str, &str); 7] = [
+        ("", "", ""),
+        ("f", "MY======", "CO======"),
+        ("fo", "MZXQ====", "CPNG===="),
+        ("foo", "MZXW6===", "CPNMU==="),
+        ("foob", "MZXW6YQ=", "CPNMUOG="),
+        ("fooba", "MZXW6YTB", "CPNMUOJ1"),
+        ("foobar", "MZXW6YTBOI======", "CPNMUOJ1E8======"),
+    ];
+
+    #[test]
+    fn rfc_vectors() {
+        for (data, base32, base32hex) in VECTORS {
+            assert_eq!(encode_with(data.as_bytes(), STANDARD, true), base32);
+            assert_eq!(encode_with(data.as_bytes(), HEX, true), base32hex);
+            assert_eq!(decode(base32, STANDARD).unwrap(), data.as_bytes());
+            assert_eq!(decode(base32hex, HEX).unwrap(), data.as_bytes());
+
+            let unpadded = base32.trim_end_matches('=');
+            assert_eq!(encode_with(data.as_bytes(), STANDARD, false), unpadded);
+            assert_eq!(decode(unpadded, STANDARD).unwrap(), data.as_bytes());
+        }
+    }
+
+    #[test]
+    fn lenient_decoding() {
+        assert_eq!(decode("mzxw 6ytb\noi", STANDARD).unwrap(), b"foobar");
+        assert_eq!(decode("MZXW6YQ", STANDARD).unwrap(), b"foob");
+
+        assert!(decode("MY=A====", STANDARD).is_err());
+        assert!(decode("M", STANDARD).is_err());
+        assert!(decode("MZX", STANDARD).is_err());
+        assert!(decode("MZXW6Y", STANDARD).is_err());
+        assert!(decode("MZXW1===", STANDARD).is_err());
+        // the hex alphabet has no W
+        assert!(decode("MZXW6===", HEX).is_err());
+    }
+}
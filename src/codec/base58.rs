@@ -0,0 +1,124 @@
+//! Base58 and Base58Check, see https://datatracker.ietf.org/doc/html/draft-msporny-base58
+//! and https://en.bitcoin.it/wiki/Base58Check_encoding
+
+use anyhow::Context;
+
+pub const BITCOIN: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+pub const FLICKR: &[u8; 58] = b"123456789abcdefghijkmnopqrstuvwxyzABCDEFGHJKLMNPQRSTUVWXYZ";
+pub const RIPPLE: &[u8; 58] = b"rpshnaf39wBUDNEGHJKLM4PQRST7VWXYZ2bcdeCg65jkm8oFqi1tuvAxyz";
+
+const CHECKSUM_LEN: usize = 4;
+
+pub fn encode(data: &[u8], alphabet: &[u8; 58]) -> String {
+    // every leading zero byte is written as a leading zero digit
+    let zeros = data.iter().take_while(|&&b| b == 0).count();
+
+    // little endian base 58 digits of the rest, as a big number
+    let mut digits: Vec<u8> = Vec::with_capacity(data.len() * 138 / 100 + 1);
+    for &byte in &data[zeros..] {
+        let mut carry = byte as u32;
+        for digit in digits.iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+
+    std::iter::repeat_n(alphabet[0], zeros)
+        .chain(digits.iter().rev().map(|&d| alphabet[d as usize]))
+        .map(char::from)
+        .collect()
+}
+
+pub fn decode(text: &str, alphabet: &[u8; 58]) -> anyhow::Result<Vec<u8>> {
+    let text = text.trim();
+    let zeros = text.bytes().take_while(|&b| b == alphabet[0]).count();
+
+    let mut bytes: Vec<u8> = Vec::with_capacity(text.len());
+    for (i, symbol) in text.bytes().enumerate().skip(zeros) {
+        let mut carry = alphabet.iter().position(|&a| a == symbol).ok_or_else(|| {
+            anyhow::anyhow!("Invalid character '{}' at offset {}", symbol as char, i)
+        })? as u32;
+        for byte in bytes.iter_mut() {
+            carry += *byte as u32 * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+
+    let mut out = vec![0; zeros];
+    out.extend(bytes.iter().rev());
+    Ok(out)
+}
+
+/// Base58 of the data followed by the first 4 bytes of its double SHA-256.
+pub fn encode_check(data: &[u8], alphabet: &[u8; 58]) -> anyhow::Result<String> {
+    let mut payload = data.to_vec();
+    payload.extend_from_slice(&checksum(data)?);
+    Ok(encode(&payload, alphabet))
+}
+
+/// Decodes and verifies Base58Check, returns the data without the checksum.
+pub fn decode_check(text: &str, alphabet: &[u8; 58]) -> anyhow::Result<Vec<u8>> {
+    let mut data = decode(text, alphabet)?;
+    if data.len() < CHECKSUM_LEN {
+        anyhow::bail!("Too short for a Base58Check checksum");
+    }
+    let expected = data.split_off(data.len() - CHECKSUM_LEN);
+    if checksum(&data)? != expected[..] {
+        anyhow::bail!("Invalid Base58Check checksum");
+    }
+    Ok(data)
+}
+
+fn checksum(data: &[u8]) -> anyhow::Result<[u8; CHECKSUM_LEN]> {
+    let once = openssl::sha::sha256(data);
+    let twice = openssl::sha::sha256(&once);
+    twice[..CHECKSUM_LEN]
+        .try_into()
+        .context("Computing the checksum")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, decode_check, encode, encode_check, BITCOIN, FLICKR};
+
+    #[test]
+    fn encoding() {
+        let vectors: [(&[u8], &str); 4] = [
+            (b"Hello World!", "2NEpo7TZRRrLZSi2U"),
+            (
+                b"The quick brown fox jumps over the lazy dog.",
+                "USm3fpXnKG5EUBx2ndxBDMPVciP5hGey2Jh4NDv6gmeo1LkMeiKrLJUUBk6Z",
+            ),
+            (&[0x00, 0x00, 0x28, 0x7f, 0xb4, 0xcd], "11233QC4"),
+            (b"", ""),
+        ];
+        for (data, text) in vectors {
+            assert_eq!(encode(data, BITCOIN), text);
+            assert_eq!(decode(text, BITCOIN).unwrap(), data);
+        }
+        assert_eq!(encode(b"Hello World!", FLICKR), "2nePN7syqqRkyrH2t");
+        assert!(decode("0OIl", BITCOIN).is_err());
+    }
+
+    #[test]
+    fn check() {
+        // version 0 and an all zero hash, the best known Bitcoin address
+        let address = "1111111111111111111114oLvT2";
+        assert_eq!(encode_check(&[0; 21], BITCOIN).unwrap(), address);
+        assert_eq!(decode_check(address, BITCOIN).unwrap(), [0; 21]);
+
+        let error = decode_check("1111111111111111111114oLvT3", BITCOIN).unwrap_err();
+        assert_eq!(error.to_string(), "Invalid Base58Check checksum");
+        assert!(decode_check("2", BITCOIN).is_err());
+    }
+}
@@ -0,0 +1,176 @@
+//! Ascii85 as used by PostScript and PDF, and Z85 from https://rfc.zeromq.org/spec/32/
+
+/// Ascii85 uses the characters `!` to `u`.
+const ASCII85_OFFSET: u8 = b'!';
+pub const Z85: &[u8; 85] =
+    b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ.-:+=^!/*?&<>()[]{}@%$#";
+
+/// Encodes 4 bytes as 5 digits, with `z` for all zero groups. A final partial group is
+/// padded with zeros and only the digits it needs are kept.
+pub fn encode_ascii85(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(4) * 5);
+    for group in data.chunks(4) {
+        if group == [0; 4] {
+            out.push('z');
+            continue;
+        }
+        let digits = encode_group(group);
+        out.extend(
+            digits[..group.len() + 1]
+                .iter()
+                .map(|&d| (d + ASCII85_OFFSET) as char),
+        );
+    }
+    out
+}
+
+/// Decodes Ascii85 with or without the `<~` and `~>` delimiters of Adobe.
+pub fn decode_ascii85(text: &str) -> anyhow::Result<Vec<u8>> {
+    let text = text.trim();
+    let text = text.strip_prefix("<~").unwrap_or(text);
+    let text = text.strip_suffix("~>").unwrap_or(text);
+
+    let mut out = Vec::with_capacity(text.len() * 4 / 5);
+    let mut group = Vec::with_capacity(5);
+    for (i, c) in text.bytes().enumerate() {
+        match c {
+            c if c.is_ascii_whitespace() => continue,
+            b'z' if group.is_empty() => out.extend_from_slice(&[0; 4]),
+            b'!'..=b'u' => group.push(c - ASCII85_OFFSET),
+            _ => anyhow::bail!("Invalid character '{}' at offset {}", c as char, i),
+        }
+        if group.len() == 5 {
+            out.extend_from_slice(&decode_group(&group)?);
+            group.clear();
+        }
+    }
+
+    match group.len() {
+        0 => {}
+        1 => anyhow::bail!("Invalid length, a final group needs at least 2 characters"),
+        n => {
+            // padded with the highest digit so the truncated bytes round up correctly
+            group.resize(5, 84);
+            out.extend_from_slice(&decode_group(&group)?[..n - 1]);
+        }
+    }
+    Ok(out)
+}
+
+/// Encodes Z85, the length of the data must be a multiple of 4.
+pub fn encode_z85(data: &[u8]) -> anyhow::Result<String> {
+    if data.len() % 4 != 0 {
+        anyhow::bail!(
+            "Z85 can only encode a multiple of 4 bytes, got {}",
+            data.len()
+        );
+    }
+    Ok(data
+        .chunks(4)
+        .flat_map(encode_group)
+        .map(|d| Z85[d as usize] as char)
+        .collect())
+}
+
+pub fn decode_z85(text: &str) -> anyhow::Result<Vec<u8>> {
+    let digits = text
+        .bytes()
+        .filter(|b| !b.is_ascii_whitespace())
+        .enumerate()
+        .map(|(i, c)| match Z85.iter().position(|&z| z == c) {
+            Some(d) => Ok(d as u8),
+            None => Err(anyhow::anyhow!(
+                "Invalid character '{}' at offset {}",
+                c as char,
+                i
+            )),
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    if digits.len() % 5 != 0 {
+        anyhow::bail!(
+            "Z85 text must be a multiple of 5 characters, got {}",
+            digits.len()
+        );
+    }
+
+    let mut out = Vec::with_capacity(digits.len() * 4 / 5);
+    for group in digits.chunks(5) {
+        out.extend_from_slice(&decode_group(group)?);
+    }
+    Ok(out)
+}
+
+/// Base 85 digits of up to 4 big endian bytes, padded with zeros.
+fn encode_group(group: &[u8]) -> [u8; 5] {
+    let mut bytes = [0; 4];
+    bytes[..group.len()].copy_from_slice(group);
+    let mut value = u32::from_be_bytes(bytes);
+
+    let mut digits = [0; 5];
+    for digit in digits.iter_mut().rev() {
+        *digit = (value % 85) as u8;
+        value /= 85;
+    }
+    digits
+}
+
+fn decode_group(digits: &[u8]) -> anyhow::Result<[u8; 4]> {
+    let value = digits
+        .iter()
+        .try_fold(0u32, |acc, &d| acc.checked_mul(85)?.checked_add(d as u32))
+        .ok_or_else(|| anyhow::anyhow!("Invalid group, its value does not fit in 4 bytes"))?;
+    Ok(value.to_be_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_ascii85, decode_z85, encode_ascii85, encode_z85};
+
+    /// The sample of the Ascii85 article of Wikipedia, from Leviathan by Thomas Hobbes.
+    const LEVIATHAN: &str = "Man is distinguished, not only by his reason, but by this singular passion from other animals, which is a lust of the mind, that by a perseverance of delight in the continued and indefatigable generation of knowledge, exceeds the short vehemence of any carnal pleasure.";
+    const LEVIATHAN_ASCII85: &str = r#"9jqo^BlbD-BleB1DJ+*+F(f,q/0JhKF<GL>Cj@.4Gp$d7F!,L7@<6@)/0JDEF<G%<+EV:2F!,O<DJ+*.@<*K0@<6L(Df-\0Ec5e;DffZ(EZee.Bl.9pF"AGXBPCsi+DGm>@3BB/F*&OCAfu2/AKYi(DIb:@FD,*)+C]U=@3BN#EcYf8ATD3s@q?d$AftVqCh[NqF<G:8+EV:.+Cf>-FD5W8ARlolDIal(DId<j@<?3r@:F%a+D58'ATD4$Bl@l3De:,-DJs`8ARoFb/0JMK@qB4^F!,R<AKZ&-DfTqBG%G>uD.RTpAKYo'+CT/5+Cei#DII?(E,9)oF*2M7/c"#;
+
+    #[test]
+    fn ascii85() {
+        assert_eq!(encode_ascii85(LEVIATHAN.as_bytes()), LEVIATHAN_ASCII85);
+        assert_eq!(
+            decode_ascii85(LEVIATHAN_ASCII85).unwrap(),
+            LEVIATHAN.as_bytes()
+        );
+
+        // a final partial group keeps one digit more than it has bytes
+        assert_eq!(encode_ascii85(b"sure."), "F*2M7/c");
+        assert_eq!(decode_ascii85("F*2M7/c").unwrap(), b"sure.");
+        assert_eq!(decode_ascii85("<~F*2M7\n/c~>").unwrap(), b"sure.");
+
+        assert_eq!(encode_ascii85(&[0; 7]), "z!!!!");
+        assert_eq!(decode_ascii85("z!!!!").unwrap(), [0; 7]);
+        assert_eq!(decode_ascii85("zz").unwrap(), [0; 8]);
+    }
+
+    #[test]
+    fn ascii85_errors() {
+        // a single digit in the final group
+        assert!(decode_ascii85("F*2M7/").is_err());
+        assert!(decode_ascii85("9jqo^v").is_err());
+        // `z` inside a group
+        assert!(decode_ascii85("9jzo^").is_err());
+        // beyond 2^32 - 1
+        assert!(decode_ascii85("uuuuu").is_err());
+    }
+
+    #[test]
+    fn z85() {
+        // the test vector of the Z85 spec
+        let data = [0x86, 0x4F, 0xD2, 0x6F, 0xB5, 0x59, 0xF7, 0x5B];
+        assert_eq!(encode_z85(&data).unwrap(), "HelloWorld");
+        assert_eq!(decode_z85("HelloWorld").unwrap(), data);
+        assert_eq!(decode_z85("Hello World").unwrap(), data);
+
+        assert!(encode_z85(b"abc").is_err());
+        assert!(decode_z85("Hello").is_ok());
+        assert!(decode_z85("Hell").is_err());
+        assert!(decode_z85("Hello~orld").is_err());
+        assert!(decode_z85("%%%%%").is_err());
+    }
+}
@@ -0,0 +1,29 @@
+//! Canonical hex and ASCII display, as `hexdump -C` prints it.
+
+/// One line per 16 bytes followed by a line with the total length.
+pub fn hexdump(data: &[u8]) -> String {
+    let mut out = String::new();
+    for (n, line) in data.chunks(16).enumerate() {
+        out.push_str(&format!("{:08x} ", n * 16));
+        for i in 0..16 {
+            if i % 8 == 0 {
+                out.push(' ');
+            }
+            match line.get(i) {
+                Some(b) => out.push_str(&format!("{:02x} ", b)),
+                None => out.push_str("   "),
+            }
+        }
+        out.push_str(" |");
+        out.extend(line.iter().map(|&b| {
+            if b.is_ascii_graphic() || b == b' ' {
+                b as char
+            } else {
+                '.'
+            }
+        }));
+        out.push_str("|\n");
+    }
+    out.push_str(&format!("{:08x}\n", data.len()));
+    out
+}
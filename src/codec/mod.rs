@@ -0,0 +1,289 @@
+//! Binary-to-text encodings.
+
+use std::io::Write;
+
+use anyhow::Context;
+
+use crate::input::{for_input, InputSource};
+
+pub mod base32;
+pub mod base58;
+pub mod base85;
+pub mod hexdump;
+pub mod sniff;
+
+/// The encodings that share the `encode`/`decode` subcommands, base64 has its own.
+#[derive(Clone, Copy, Debug)]
+pub enum Codec {
+    Base32,
+    Base58,
+    Base85,
+    Hex,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct CodecArg {
+    #[clap(subcommand)]
+    action: CodecAction,
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum CodecAction {
+    Encode(EncodeArgs),
+    Decode(DecodeArgs),
+}
+
+/// Alphabets of the encodings, the first one of each is the default.
+#[derive(clap::ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Variant {
+    /// b32: RFC 4648 standard alphabet
+    Rfc4648,
+    /// b32: RFC 4648 "Extended Hex" alphabet
+    Base32hex,
+    /// b58: Bitcoin, also used by IPFS
+    Bitcoin,
+    /// b58: Flickr short URLs
+    Flickr,
+    /// b58: Ripple addresses
+    Ripple,
+    /// b85: Ascii85
+    Ascii85,
+    /// b85: Ascii85 between Adobe's `<~` and `~>` delimiters
+    Adobe,
+    /// b85: ZeroMQ Z85
+    Z85,
+    /// hex: lowercase digits
+    Lower,
+    /// hex: uppercase digits
+    Upper,
+}
+
+#[derive(clap::Args, Debug)]
+struct EncodeArgs {
+    #[clap(flatten)]
+    options: EncodeOptions,
+
+    #[clap(flatten)]
+    input: InputSource,
+}
+
+#[derive(clap::Args, Debug)]
+struct EncodeOptions {
+    /// Alphabet variant of the encoding
+    #[clap(long, arg_enum)]
+    variant: Option<Variant>,
+
+    /// Omit the `=` padding (base32)
+    #[clap(long)]
+    no_pad: bool,
+
+    /// Wrap lines after this many characters (0 disables wrapping)
+    #[clap(long, value_name = "COLS")]
+    wrap: Option<usize>,
+
+    /// Append a checksum, Base58Check (base58)
+    #[clap(long)]
+    check: bool,
+}
+
+#[derive(clap::Args, Debug)]
+struct DecodeArgs {
+    #[clap(flatten)]
+    options: DecodeOptions,
+
+    #[clap(flatten)]
+    input: InputSource,
+}
+
+#[derive(clap::Args, Debug)]
+struct DecodeOptions {
+    /// Alphabet variant of the encoding
+    #[clap(long, arg_enum)]
+    variant: Option<Variant>,
+
+    /// Verify and strip the checksum, Base58Check (base58)
+    #[clap(long)]
+    check: bool,
+
+    /// Write the decoded bytes to this file instead of stdout
+    #[clap(long, short, value_name = "FILE")]
+    output: Option<String>,
+}
+
+impl Codec {
+    fn name(self) -> &'static str {
+        match self {
+            Codec::Base32 => "Base32",
+            Codec::Base58 => "Base58",
+            Codec::Base85 => "Base85",
+            Codec::Hex => "Hex",
+        }
+    }
+
+    fn variants(self) -> &'static [Variant] {
+        match self {
+            Codec::Base32 => &[Variant::Rfc4648, Variant::Base32hex],
+            Codec::Base58 => &[Variant::Bitcoin, Variant::Flickr, Variant::Ripple],
+            Codec::Base85 => &[Variant::Ascii85, Variant::Adobe, Variant::Z85],
+            Codec::Hex => &[Variant::Lower, Variant::Upper],
+        }
+    }
+
+    /// The requested variant if this encoding has it, otherwise its default one.
+    fn variant(self, requested: Option<Variant>) -> anyhow::Result<Variant> {
+        let variants = self.variants();
+        match requested {
+            None => Ok(variants[0]),
+            Some(v) if variants.contains(&v) => Ok(v),
+            Some(v) => anyhow::bail!("{} has no {:?} variant", self.name(), v),
+        }
+    }
+
+    fn encode(self, args: &EncodeOptions, data: &[u8]) -> anyhow::Result<String> {
+        if args.no_pad && !matches!(self, Codec::Base32) {
+            anyhow::bail!("--no-pad is only supported by base32");
+        }
+        if args.check && !matches!(self, Codec::Base58) {
+            anyhow::bail!("--check is only supported by base58");
+        }
+        let pad = !args.no_pad;
+
+        Ok(match self.variant(args.variant)? {
+            Variant::Rfc4648 => base32::encode_with(data, base32::STANDARD, pad),
+            Variant::Base32hex => base32::encode_with(data, base32::HEX, pad),
+            v @ (Variant::Bitcoin | Variant::Flickr | Variant::Ripple) => {
+                let alphabet = base58_alphabet(v);
+                if args.check {
+                    base58::encode_check(data, alphabet)?
+                } else {
+                    base58::encode(data, alphabet)
+                }
+            }
+            Variant::Ascii85 => base85::encode_ascii85(data),
+            Variant::Adobe => format!("<~{}~>", base85::encode_ascii85(data)),
+            Variant::Z85 => base85::encode_z85(data)?,
+            Variant::Lower => hex::encode(data),
+            Variant::Upper => hex::encode_upper(data),
+        })
+    }
+
+    fn decode(self, args: &DecodeOptions, text: &str) -> anyhow::Result<Vec<u8>> {
+        if args.check && !matches!(self, Codec::Base58) {
+            anyhow::bail!("--check is only supported by base58");
+        }
+
+        match self.variant(args.variant)? {
+            Variant::Rfc4648 => base32::decode(text, base32::STANDARD),
+            Variant::Base32hex => base32::decode(text, base32::HEX),
+            v @ (Variant::Bitcoin | Variant::Flickr | Variant::Ripple) if args.check => {
+                base58::decode_check(text, base58_alphabet(v))
+            }
+            v @ (Variant::Bitcoin | Variant::Flickr | Variant::Ripple) => {
+                base58::decode(text, base58_alphabet(v))
+            }
+            // the delimiters are optional when decoding
+            Variant::Ascii85 | Variant::Adobe => base85::decode_ascii85(text),
+            Variant::Z85 => base85::decode_z85(text),
+            Variant::Lower | Variant::Upper => decode_hex(text),
+        }
+    }
+}
+
+fn base58_alphabet(variant: Variant) -> &'static [u8; 58] {
+    match variant {
+        Variant::Flickr => base58::FLICKR,
+        Variant::Ripple => base58::RIPPLE,
+        _ => base58::BITCOIN,
+    }
+}
+
+/// Decodes hex in either case, ignoring whitespace, a `0x` prefix and `:` separators.
+fn decode_hex(text: &str) -> anyhow::Result<Vec<u8>> {
+    let text = text.trim();
+    let text = text
+        .strip_prefix("0x")
+        .or_else(|| text.strip_prefix("0X"))
+        .unwrap_or(text);
+    let digits: String = text
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace() && *c != ':')
+        .collect();
+    hex::decode(&digits).map_err(|e| anyhow::anyhow!("Invalid hex: {}", e))
+}
+
+pub fn run(codec: Codec, arg: CodecArg) -> anyhow::Result<()> {
+    match arg.action {
+        CodecAction::Encode(args) => for_input(args.input, |data| {
+            print_wrapped(&codec.encode(&args.options, &data)?, args.options.wrap);
+            Ok(())
+        })
+        .context(format!("{} Encoding", codec.name())),
+        CodecAction::Decode(args) => for_input(args.input, |data| {
+            let decoded = codec.decode(&args.options, &String::from_utf8_lossy(&data))?;
+            write_decoded(decoded, args.options.output.as_deref())
+        })
+        .context(format!("{} Decoding", codec.name())),
+    }
+}
+
+/// Prints encoded text, split into lines of `wrap` characters unless it is 0.
+pub fn print_wrapped(encoded: &str, wrap: Option<usize>) {
+    match wrap {
+        Some(cols) if cols > 0 => {
+            // the alphabets are ASCII, so every chunk is valid UTF-8
+            for line in encoded.as_bytes().chunks(cols) {
+                println!("{}", String::from_utf8_lossy(line));
+            }
+        }
+        _ => println!("{}", encoded),
+    }
+}
+
+/// Prints text as is, binary data raw when stdout is redirected and as a hexdump
+/// when it is a terminal.
+pub fn write_decoded(decoded: Vec<u8>, output: Option<&str>) -> anyhow::Result<()> {
+    let file_type = sniff::sniff(&decoded);
+    let description = match file_type {
+        Some(t) => format!("{} bytes of {} ({})", decoded.len(), t.name, t.mime),
+        None => format!("{} bytes", decoded.len()),
+    };
+
+    if let Some(path) = output {
+        std::fs::write(path, &decoded).with_context(|| format!("Writing '{}'", path))?;
+        eprintln!("Wrote {} to '{}'", description, path);
+        return Ok(());
+    }
+
+    let decoded = match (file_type, String::from_utf8(decoded)) {
+        // control characters such as NUL are a sign of binary data
+        (None, Ok(text)) if !text.chars().any(|c| c.is_control() && !c.is_whitespace()) => {
+            // byte for byte when redirected, the newline is only for the prompt
+            let mut stdout = std::io::stdout();
+            stdout.write_all(text.as_bytes())?;
+            if atty::is(atty::Stream::Stdout) {
+                stdout.write_all(b"\n")?;
+            }
+            stdout.flush()?;
+            return Ok(());
+        }
+        (_, Ok(text)) => text.into_bytes(),
+        (_, Err(e)) => e.into_bytes(),
+    };
+
+    if atty::is(atty::Stream::Stdout) {
+        eprintln!(
+            "Decoded {}, showing a hexdump (use --output to save the bytes)",
+            description
+        );
+        print!("{}", hexdump::hexdump(&decoded));
+        Ok(())
+    } else {
+        if file_type.is_some() {
+            eprintln!("Decoded {}", description);
+        }
+        let mut stdout = std::io::stdout();
+        stdout.write_all(&decoded)?;
+        stdout.flush()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,213 @@
+//! Detects the type of binary data from the magic numbers at its start.
+
+pub struct FileType {
+    pub name: &'static str,
+    pub mime: &'static str,
+}
+
+/// Byte strings that must all be present at their offsets, and a check of the rest of
+/// the header for the short ones that text can start with.
+struct Signature {
+    parts: &'static [(usize, &'static [u8])],
+    header: fn(&[u8]) -> bool,
+    file_type: FileType,
+}
+
+macro_rules! signature {
+    ($name:literal, $mime:literal, $(($offset:literal, $magic:literal)),+) => {
+        signature!($name, $mime, $(($offset, $magic)),+; |_| true)
+    };
+    ($name:literal, $mime:literal, $(($offset:literal, $magic:literal)),+; $header:expr) => {
+        Signature {
+            parts: &[$(($offset, $magic)),+],
+            header: $header,
+            file_type: FileType {
+                name: $name,
+                mime: $mime,
+            },
+        }
+    };
+}
+
+const SIGNATURES: &[Signature] = &[
+    signature!("PNG image", "image/png", (0, b"\x89PNG\r\n\x1a\n")),
+    signature!("JPEG image", "image/jpeg", (0, b"\xff\xd8\xff")),
+    signature!("GIF image", "image/gif", (0, b"GIF87a")),
+    signature!("GIF image", "image/gif", (0, b"GIF89a")),
+    signature!("WebP image", "image/webp", (0, b"RIFF"), (8, b"WEBP")),
+    signature!("TIFF image", "image/tiff", (0, b"II*\x00")),
+    signature!("TIFF image", "image/tiff", (0, b"MM\x00*")),
+    signature!("ICO image", "image/x-icon", (0, b"\x00\x00\x01\x00")),
+    signature!("PDF document", "application/pdf", (0, b"%PDF-")),
+    signature!("ZIP archive", "application/zip", (0, b"PK\x03\x04")),
+    signature!("ZIP archive", "application/zip", (0, b"PK\x05\x06")),
+    signature!("gzip data", "application/gzip", (0, b"\x1f\x8b")),
+    signature!("bzip2 data", "application/x-bzip2", (0, b"BZh"); bzip2_header),
+    signature!("xz data", "application/x-xz", (0, b"\xfd7zXZ\x00")),
+    signature!("zstd data", "application/zstd", (0, b"\x28\xb5\x2f\xfd")),
+    signature!(
+        "7-Zip archive",
+        "application/x-7z-compressed",
+        (0, b"7z\xbc\xaf\x27\x1c")
+    ),
+    signature!("tar archive", "application/x-tar", (257, b"ustar")),
+    signature!("ELF executable", "application/x-elf", (0, b"\x7fELF")),
+    signature!("WebAssembly module", "application/wasm", (0, b"\x00asm")),
+    signature!(
+        "Java class file",
+        "application/java-vm",
+        (0, b"\xca\xfe\xba\xbe")
+    ),
+    signature!(
+        "SQLite database",
+        "application/vnd.sqlite3",
+        (0, b"SQLite format 3\x00")
+    ),
+    signature!("MP3 audio", "audio/mpeg", (0, b"ID3"); id3_header),
+    signature!("Ogg media", "audio/ogg", (0, b"OggS")),
+    signature!("FLAC audio", "audio/flac", (0, b"fLaC")),
+    signature!("WAV audio", "audio/wav", (0, b"RIFF"), (8, b"WAVE")),
+    signature!("MP4 video", "video/mp4", (4, b"ftyp")),
+    signature!("WOFF font", "font/woff", (0, b"wOFF")),
+    signature!("WOFF2 font", "font/woff2", (0, b"wOF2")),
+    // the weakest signature is checked last
+    signature!(
+        "Windows executable",
+        "application/vnd.microsoft.portable-executable",
+        (0, b"MZ");
+        pe_header
+    ),
+];
+
+/// A block size from 1 to 9, then the magic of a block or of the end of the stream.
+fn bzip2_header(data: &[u8]) -> bool {
+    let level = data.get(3).is_some_and(|d| (b'1'..=b'9').contains(d));
+    let block = data.get(4..10);
+    level
+        && (block == Some(b"\x31\x41\x59\x26\x53\x59")
+            || block == Some(b"\x17\x72\x45\x38\x50\x90"))
+}
+
+/// ID3v2.2 to 2.4, with a revision that is not 0xff and a syncsafe size.
+fn id3_header(data: &[u8]) -> bool {
+    match data.get(3..10) {
+        Some([major, revision, _flags, size @ ..]) => {
+            (2..=4).contains(major) && *revision != 0xff && size.iter().all(|b| b & 0x80 == 0)
+        }
+        _ => false,
+    }
+}
+
+/// The DOS header points at the `PE\0\0` of the executable at 0x3c.
+fn pe_header(data: &[u8]) -> bool {
+    let offset = match data.get(0x3c..0x40) {
+        Some(offset) => u32::from_le_bytes([offset[0], offset[1], offset[2], offset[3]]) as usize,
+        None => return false,
+    };
+    data.get(offset..offset + 4) == Some(b"PE\0\0")
+}
+
+pub fn sniff(data: &[u8]) -> Option<&'static FileType> {
+    SIGNATURES
+        .iter()
+        .find(|s| {
+            s.parts
+                .iter()
+                .all(|(offset, magic)| data.get(*offset..offset + magic.len()) == Some(*magic))
+                && (s.header)(data)
+        })
+        .map(|s| &s.file_type)
+}
+
+/// File extensions and their MIME types, the first extension of a type is its usual one.
+const EXTENSIONS: &[(&str, &str)] = &[
+    ("png", "image/png"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("gif", "image/gif"),
+    ("webp", "image/webp"),
+    ("avif", "image/avif"),
+    ("svg", "image/svg+xml"),
+    ("ico", "image/x-icon"),
+    ("bmp", "image/bmp"),
+    ("tiff", "image/tiff"),
+    ("tif", "image/tiff"),
+    ("css", "text/css"),
+    ("js", "text/javascript"),
+    ("mjs", "text/javascript"),
+    ("json", "application/json"),
+    ("html", "text/html"),
+    ("htm", "text/html"),
+    ("txt", "text/plain"),
+    ("csv", "text/csv"),
+    ("md", "text/markdown"),
+    ("xml", "application/xml"),
+    ("pdf", "application/pdf"),
+    ("zip", "application/zip"),
+    ("gz", "application/gzip"),
+    ("bz2", "application/x-bzip2"),
+    ("xz", "application/x-xz"),
+    ("zst", "application/zstd"),
+    ("7z", "application/x-7z-compressed"),
+    ("tar", "application/x-tar"),
+    ("wasm", "application/wasm"),
+    ("class", "application/java-vm"),
+    ("sqlite", "application/vnd.sqlite3"),
+    ("exe", "application/vnd.microsoft.portable-executable"),
+    ("woff", "font/woff"),
+    ("woff2", "font/woff2"),
+    ("ttf", "font/ttf"),
+    ("otf", "font/otf"),
+    ("mp3", "audio/mpeg"),
+    ("ogg", "audio/ogg"),
+    ("flac", "audio/flac"),
+    ("wav", "audio/wav"),
+    ("mp4", "video/mp4"),
+    ("webm", "video/webm"),
+];
+
+pub fn mime_from_extension(extension: &str) -> Option<&'static str> {
+    EXTENSIONS
+        .iter()
+        .find(|(ext, _)| ext.eq_ignore_ascii_case(extension))
+        .map(|(_, mime)| *mime)
+}
+
+pub fn extension_from_mime(mime: &str) -> Option<&'static str> {
+    EXTENSIONS
+        .iter()
+        .find(|(_, m)| m.eq_ignore_ascii_case(mime))
+        .map(|(ext, _)| *ext)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::sniff;
+
+    fn name(data: &[u8]) -> Option<&'static str> {
+        sniff(data).map(|t| t.name)
+    }
+
+    #[test]
+    fn text_starting_like_a_short_signature_is_text() {
+        assert_eq!(name(b"MZ is the start of this sentence"), None);
+        assert_eq!(name(b"BZh, said nobody, ever"), None);
+        assert_eq!(name(b"ID3 tags are for MP3 files"), None);
+    }
+
+    #[test]
+    fn full_headers() {
+        let mut exe = vec![0; 0x84];
+        exe[..2].copy_from_slice(b"MZ");
+        exe[0x3c] = 0x80;
+        exe[0x80..].copy_from_slice(b"PE\0\0");
+        assert_eq!(name(&exe), Some("Windows executable"));
+
+        assert_eq!(name(b"BZh91AY&SY\x00"), Some("bzip2 data"));
+        assert_eq!(name(b"BZh9\x17\x72\x45\x38\x50\x90"), Some("bzip2 data"));
+        assert_eq!(name(b"BZh0\x31\x41\x59\x26\x53\x59"), None);
+
+        assert_eq!(name(b"ID3\x04\x00\x00\x00\x00\x01\x7f"), Some("MP3 audio"));
+        assert_eq!(name(b"ID3\x04\x00\x00\x00\x00\x01\x80"), None);
+    }
+}
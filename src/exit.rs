@@ -0,0 +1,21 @@
+//! Exit statuses for the failures that scripts need to tell apart.
+
+use std::fmt;
+
+/// Attached as context to an error to choose the exit status of the process, the
+/// default for errors without one is 1.
+#[derive(Clone, Copy, Debug)]
+pub struct ExitStatus(pub i32, pub &'static str);
+
+impl fmt::Display for ExitStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.1)
+    }
+}
+
+/// The exit status of an error, from the [`ExitStatus`] context it carries.
+pub fn code(error: &anyhow::Error) -> i32 {
+    error
+        .downcast_ref::<ExitStatus>()
+        .map_or(1, |status| status.0)
+}
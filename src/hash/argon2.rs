@@ -0,0 +1,616 @@
+//! Argon2d, Argon2i and Argon2id following RFC 9106, including the BLAKE2b
+//! with variable output length it is built on (RFC 7693).
+
+const BLAKE2B_IV: [u64; 8] = [
+    0x6A09_E667_F3BC_C908,
+    0xBB67_AE85_84CA_A73B,
+    0x3C6E_F372_FE94_F82B,
+    0xA54F_F53A_5F1D_36F1,
+    0x510E_527F_ADE6_82D1,
+    0x9B05_688C_2B3E_6C1F,
+    0x1F83_D9AB_FB41_BD6B,
+    0x5BE0_CD19_137E_2179,
+];
+const SIGMA: [[usize; 16]; 12] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+];
+const BLAKE2B_BLOCK_LEN: usize = 128;
+const BLAKE2B_OUT_LEN: usize = 64;
+
+struct Blake2b {
+    h: [u64; 8],
+    counter: u128,
+    buffer: [u8; BLAKE2B_BLOCK_LEN],
+    buffer_len: usize,
+    out_len: usize,
+}
+
+impl Blake2b {
+    fn new(out_len: usize) -> Self {
+        let mut h = BLAKE2B_IV;
+        h[0] ^= 0x0101_0000 ^ out_len as u64;
+        Blake2b {
+            h,
+            counter: 0,
+            buffer: [0; BLAKE2B_BLOCK_LEN],
+            buffer_len: 0,
+            out_len,
+        }
+    }
+
+    fn compress(&mut self, last: bool) {
+        let m: Vec<u64> = self
+            .buffer
+            .chunks_exact(8)
+            .map(|w| u64::from_le_bytes(w.try_into().unwrap()))
+            .collect();
+        let mut v = [0; 16];
+        v[..8].copy_from_slice(&self.h);
+        v[8..].copy_from_slice(&BLAKE2B_IV);
+        v[12] ^= self.counter as u64;
+        v[13] ^= (self.counter >> 64) as u64;
+        if last {
+            v[14] = !v[14];
+        }
+
+        let mut g = |a: usize, b: usize, c: usize, d: usize, x: u64, y: u64| {
+            v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+            v[d] = (v[d] ^ v[a]).rotate_right(32);
+            v[c] = v[c].wrapping_add(v[d]);
+            v[b] = (v[b] ^ v[c]).rotate_right(24);
+            v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+            v[d] = (v[d] ^ v[a]).rotate_right(16);
+            v[c] = v[c].wrapping_add(v[d]);
+            v[b] = (v[b] ^ v[c]).rotate_right(63);
+        };
+        for s in SIGMA {
+            g(0, 4, 8, 12, m[s[0]], m[s[1]]);
+            g(1, 5, 9, 13, m[s[2]], m[s[3]]);
+            g(2, 6, 10, 14, m[s[4]], m[s[5]]);
+            g(3, 7, 11, 15, m[s[6]], m[s[7]]);
+            g(0, 5, 10, 15, m[s[8]], m[s[9]]);
+            g(1, 6, 11, 12, m[s[10]], m[s[11]]);
+            g(2, 7, 8, 13, m[s[12]], m[s[13]]);
+            g(3, 4, 9, 14, m[s[14]], m[s[15]]);
+        }
+
+        for i in 0..8 {
+            self.h[i] ^= v[i] ^ v[i + 8];
+        }
+    }
+
+    fn update(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            // the last block is compressed differently, so a full buffer is
+            // only compressed once more data follows
+            if self.buffer_len == BLAKE2B_BLOCK_LEN {
+                self.counter += BLAKE2B_BLOCK_LEN as u128;
+                self.compress(false);
+                self.buffer_len = 0;
+            }
+            let take = (BLAKE2B_BLOCK_LEN - self.buffer_len).min(data.len());
+            self.buffer[self.buffer_len..self.buffer_len + take].copy_from_slice(&data[..take]);
+            self.buffer_len += take;
+            data = &data[take..];
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        self.counter += self.buffer_len as u128;
+        self.buffer[self.buffer_len..].fill(0);
+        self.compress(true);
+        self.h
+            .iter()
+            .flat_map(|w| w.to_le_bytes())
+            .take(self.out_len)
+            .collect()
+    }
+}
+
+/// The variable length hash function H' of the specification.
+fn blake2b_long(out_len: usize, inputs: &[&[u8]]) -> Vec<u8> {
+    let hash = |out_len: usize, inputs: &[&[u8]]| {
+        let mut hasher = Blake2b::new(out_len);
+        for input in inputs {
+            hasher.update(input);
+        }
+        hasher.finish()
+    };
+
+    let len = (out_len as u32).to_le_bytes();
+    let mut all = vec![&len[..]];
+    all.extend_from_slice(inputs);
+    if out_len <= BLAKE2B_OUT_LEN {
+        return hash(out_len, &all);
+    }
+
+    // the first half of each 64 byte hash of the previous one, then a last hash
+    // of the remaining length
+    let mut output = Vec::with_capacity(out_len);
+    let mut v = hash(BLAKE2B_OUT_LEN, &all);
+    while out_len - output.len() > BLAKE2B_OUT_LEN {
+        output.extend_from_slice(&v[..32]);
+        let remaining = out_len - output.len();
+        v = hash(remaining.min(BLAKE2B_OUT_LEN), &[&v]);
+    }
+    output.extend_from_slice(&v);
+    output
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Variant {
+    D = 0,
+    I = 1,
+    Id = 2,
+}
+
+impl Variant {
+    pub fn name(self) -> &'static str {
+        match self {
+            Variant::D => "argon2d",
+            Variant::I => "argon2i",
+            Variant::Id => "argon2id",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [Variant::D, Variant::I, Variant::Id]
+            .into_iter()
+            .find(|v| v.name() == name)
+    }
+}
+
+/// The current version, 1.3.
+pub const VERSION: u32 = 0x13;
+/// The version of hashes without a `v=` parameter.
+pub const VERSION_10: u32 = 0x10;
+
+pub struct Params {
+    /// Memory size in KiB.
+    pub memory: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+const BLOCK_WORDS: usize = 128;
+const SYNC_POINTS: usize = 4;
+
+type Block = [u64; BLOCK_WORDS];
+
+fn block_from_bytes(bytes: &[u8]) -> Block {
+    let mut block = [0; BLOCK_WORDS];
+    for (word, chunk) in block.iter_mut().zip(bytes.chunks_exact(8)) {
+        *word = u64::from_le_bytes(chunk.try_into().unwrap());
+    }
+    block
+}
+
+/// The compression function G.
+fn compress(x: &Block, y: &Block) -> Block {
+    let mut r = [0; BLOCK_WORDS];
+    for i in 0..BLOCK_WORDS {
+        r[i] = x[i] ^ y[i];
+    }
+
+    let mut q = r;
+    for row in 0..8 {
+        let indexes: [usize; 16] = std::array::from_fn(|i| 16 * row + i);
+        permute(&mut q, indexes);
+    }
+    for column in 0..8 {
+        let indexes: [usize; 16] = std::array::from_fn(|i| 2 * column + (i / 2) * 16 + i % 2);
+        permute(&mut q, indexes);
+    }
+
+    for i in 0..BLOCK_WORDS {
+        q[i] ^= r[i];
+    }
+    q
+}
+
+/// The BLAKE2b round function with the multiplications of BlaMka, on 16 words of a block.
+fn permute(block: &mut Block, v: [usize; 16]) {
+    let mut gb = |a: usize, b: usize, c: usize, d: usize| {
+        let blamka = |x: u64, y: u64| {
+            x.wrapping_add(y).wrapping_add(
+                2u64.wrapping_mul(x & 0xFFFF_FFFF)
+                    .wrapping_mul(y & 0xFFFF_FFFF),
+            )
+        };
+        block[a] = blamka(block[a], block[b]);
+        block[d] = (block[d] ^ block[a]).rotate_right(32);
+        block[c] = blamka(block[c], block[d]);
+        block[b] = (block[b] ^ block[c]).rotate_right(24);
+        block[a] = blamka(block[a], block[b]);
+        block[d] = (block[d] ^ block[a]).rotate_right(16);
+        block[c] = blamka(block[c], block[d]);
+        block[b] = (block[b] ^ block[c]).rotate_right(63);
+    };
+    gb(v[0], v[4], v[8], v[12]);
+    gb(v[1], v[5], v[9], v[13]);
+    gb(v[2], v[6], v[10], v[14]);
+    gb(v[3], v[7], v[11], v[15]);
+    gb(v[0], v[5], v[10], v[15]);
+    gb(v[1], v[6], v[11], v[12]);
+    gb(v[2], v[7], v[8], v[13]);
+    gb(v[3], v[4], v[9], v[14]);
+}
+
+struct Memory {
+    variant: Variant,
+    version: u32,
+    iterations: u32,
+    lanes: usize,
+    lane_length: usize,
+    segment_length: usize,
+    blocks: Vec<Block>,
+}
+
+pub fn hash(
+    variant: Variant,
+    version: u32,
+    params: &Params,
+    password: &[u8],
+    salt: &[u8],
+    out_len: usize,
+) -> anyhow::Result<Vec<u8>> {
+    if params.parallelism == 0 || params.parallelism > 0xFF_FFFF {
+        anyhow::bail!("Parallelism must be between 1 and 16777215");
+    }
+    if params.memory < 8 * params.parallelism {
+        anyhow::bail!("Memory must be at least 8 KiB per lane");
+    }
+    if params.iterations == 0 {
+        anyhow::bail!("Iterations must be at least 1");
+    }
+    if salt.len() < 8 {
+        anyhow::bail!("Salt must be at least 8 bytes");
+    }
+    if out_len < 4 {
+        anyhow::bail!("Output must be at least 4 bytes");
+    }
+
+    let lanes = params.parallelism as usize;
+    let segment_length = params.memory as usize / (lanes * SYNC_POINTS);
+    let lane_length = segment_length * SYNC_POINTS;
+
+    let u32_le = |n: usize| (n as u32).to_le_bytes();
+    let mut hasher = Blake2b::new(BLAKE2B_OUT_LEN);
+    for input in [
+        &u32_le(lanes),
+        &u32_le(out_len),
+        &params.memory.to_le_bytes(),
+        &params.iterations.to_le_bytes(),
+        &version.to_le_bytes(),
+        &(variant as u32).to_le_bytes(),
+        &u32_le(password.len()),
+        password,
+        &u32_le(salt.len()),
+        salt,
+        // no secret and no associated data
+        &u32_le(0),
+        &u32_le(0),
+    ] {
+        hasher.update(input);
+    }
+    let h0 = hasher.finish();
+
+    let mut memory = Memory {
+        variant,
+        version,
+        iterations: params.iterations,
+        lanes,
+        lane_length,
+        segment_length,
+        blocks: vec![[0; BLOCK_WORDS]; lanes * lane_length],
+    };
+    for lane in 0..lanes {
+        for i in 0..2 {
+            let bytes = blake2b_long(1024, &[&h0, &u32_le(i), &u32_le(lane)]);
+            memory.blocks[lane * lane_length + i] = block_from_bytes(&bytes);
+        }
+    }
+
+    for pass in 0..params.iterations as usize {
+        for slice in 0..SYNC_POINTS {
+            for lane in 0..lanes {
+                memory.fill_segment(pass, slice, lane);
+            }
+        }
+    }
+
+    let mut last = memory.blocks[lane_length - 1];
+    for lane in 1..lanes {
+        for (word, other) in last
+            .iter_mut()
+            .zip(&memory.blocks[lane * lane_length + lane_length - 1])
+        {
+            *word ^= other;
+        }
+    }
+    let last: Vec<u8> = last.iter().flat_map(|w| w.to_le_bytes()).collect();
+    Ok(blake2b_long(out_len, &[&last]))
+}
+
+/// Pseudo-random values of the data independent addressing, for the next 128 blocks.
+fn next_addresses(input: &mut Block, addresses: &mut Block) {
+    input[6] += 1;
+    *addresses = compress(&[0; BLOCK_WORDS], &compress(&[0; BLOCK_WORDS], input));
+}
+
+impl Memory {
+    fn fill_segment(&mut self, pass: usize, slice: usize, lane: usize) {
+        let data_independent = self.variant == Variant::I
+            || (self.variant == Variant::Id && pass == 0 && slice < SYNC_POINTS / 2);
+
+        let mut input = [0; BLOCK_WORDS];
+        let mut addresses = [0; BLOCK_WORDS];
+        if data_independent {
+            input[..6].copy_from_slice(&[
+                pass as u64,
+                lane as u64,
+                slice as u64,
+                self.blocks.len() as u64,
+                self.iterations as u64,
+                self.variant as u64,
+            ]);
+        }
+
+        // the first two blocks of each lane come from H0
+        let start = if pass == 0 && slice == 0 { 2 } else { 0 };
+        if data_independent && start != 0 {
+            next_addresses(&mut input, &mut addresses);
+        }
+
+        for index in start..self.segment_length {
+            let offset = slice * self.segment_length + index;
+            let current = lane * self.lane_length + offset;
+            let previous = if offset == 0 {
+                current + self.lane_length - 1
+            } else {
+                current - 1
+            };
+
+            let random = if data_independent {
+                if index % BLOCK_WORDS == 0 {
+                    next_addresses(&mut input, &mut addresses);
+                }
+                addresses[index % BLOCK_WORDS]
+            } else {
+                self.blocks[previous][0]
+            };
+
+            let reference_lane = if pass == 0 && slice == 0 {
+                lane
+            } else {
+                (random >> 32) as usize % self.lanes
+            };
+            let reference =
+                self.reference_index(pass, slice, index, reference_lane == lane, random);
+
+            let block = compress(
+                &self.blocks[previous],
+                &self.blocks[reference_lane * self.lane_length + reference],
+            );
+            if pass == 0 || self.version == VERSION_10 {
+                self.blocks[current] = block;
+            } else {
+                for (word, new) in self.blocks[current].iter_mut().zip(block) {
+                    *word ^= new;
+                }
+            }
+        }
+    }
+
+    /// Position in the reference lane of the block to mix in, out of the blocks
+    /// that are already computed and not in the segment being computed by another lane.
+    fn reference_index(
+        &self,
+        pass: usize,
+        slice: usize,
+        index: usize,
+        same_lane: bool,
+        random: u64,
+    ) -> usize {
+        let finished = if pass == 0 {
+            slice * self.segment_length
+        } else {
+            self.lane_length - self.segment_length
+        };
+        let area = if same_lane {
+            finished + index - 1
+        } else if index == 0 {
+            finished - 1
+        } else {
+            finished
+        } as u64;
+
+        let j1 = random & 0xFFFF_FFFF;
+        let x = (j1 * j1) >> 32;
+        let relative = area - 1 - ((area * x) >> 32);
+        let start = if pass == 0 || slice == SYNC_POINTS - 1 {
+            0
+        } else {
+            (slice + 1) * self.segment_length
+        };
+        (start + relative as usize) % self.lane_length
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hash, Params, Variant, VERSION, VERSION_10};
+
+    /// The parameters of the tests of the reference implementation, phc-winner-argon2,
+    /// with 256 KiB of memory.
+    fn reference(
+        variant: Variant,
+        version: u32,
+        iterations: u32,
+        lanes: u32,
+        password: &str,
+        salt: &str,
+    ) -> String {
+        let params = Params {
+            memory: 256,
+            iterations,
+            parallelism: lanes,
+        };
+        let digest = hash(
+            variant,
+            version,
+            &params,
+            password.as_bytes(),
+            salt.as_bytes(),
+            32,
+        );
+        hex::encode(digest.unwrap())
+    }
+
+    #[test]
+    fn argon2i() {
+        let cases = [
+            (
+                VERSION,
+                2,
+                1,
+                "password",
+                "somesalt",
+                "89e9029f4637b295beb027056a7336c414fadd43f6b208645281cb214a56452f",
+            ),
+            (
+                VERSION,
+                2,
+                2,
+                "password",
+                "somesalt",
+                "4ff5ce2769a1d7f4c8a491df09d41a9fbe90e5eb02155a13e4c01e20cd4eab61",
+            ),
+            (
+                VERSION,
+                1,
+                1,
+                "password",
+                "somesalt",
+                "6c47fd5d0fa92a185b59798a573f6648148d361f93b535780fc6146519aa0409",
+            ),
+            (
+                VERSION,
+                2,
+                1,
+                "differentpassword",
+                "somesalt",
+                "d8de454a00127853de217639ca7e0e2207add1adac064a271d4c25021515c427",
+            ),
+            (
+                VERSION,
+                2,
+                1,
+                "password",
+                "diffsalt",
+                "23dde0828fc59908d07b5c9f3016810c00bbf19fd9a0bfe9982d3c3cdb5d7513",
+            ),
+            (
+                VERSION_10,
+                2,
+                1,
+                "password",
+                "somesalt",
+                "fd4dd83d762c49bdeaf57c47bdcd0c2f1babf863fdeb490df63ede9975fccf06",
+            ),
+        ];
+        for (version, t, p, password, salt, expected) in cases {
+            assert_eq!(
+                reference(Variant::I, version, t, p, password, salt),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn argon2d() {
+        let cases = [
+            (
+                VERSION,
+                2,
+                1,
+                "25c4ee8ba448054b49efc804e478b9d823be1f9bd2e99f51d6ec4007a3a1501f",
+            ),
+            (
+                VERSION,
+                2,
+                2,
+                "7b69c92d7c3889aad1281dbc8baefc12cc37c80f1c75e33ef2c2d40c28ebc573",
+            ),
+            (
+                VERSION_10,
+                2,
+                1,
+                "bd404868ff00c52e7543c8332e6a772a5724892d7e328d5cf253bbc8e726b371",
+            ),
+        ];
+        for (version, t, p, expected) in cases {
+            assert_eq!(
+                reference(Variant::D, version, t, p, "password", "somesalt"),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn argon2id() {
+        let cases = [
+            (
+                VERSION,
+                2,
+                1,
+                "9dfeb910e80bad0311fee20f9c0e2b12c17987b4cac90c2ef54d5b3021c68bfe",
+            ),
+            (
+                VERSION,
+                2,
+                2,
+                "6d093c501fd5999645e0ea3bf620d7b8be7fd2db59c20d9fff9539da2bf57037",
+            ),
+            (
+                VERSION,
+                1,
+                1,
+                "024ec0c1ac65d08d95f1e46fcc33801dc5dcee045470e74765b7f7381b50ecd5",
+            ),
+            (
+                VERSION_10,
+                2,
+                1,
+                "da070e576e50f2f38a3c897cbddc6c7fb4028e870971ff9eae7b4e1879295e6e",
+            ),
+        ];
+        for (version, t, p, expected) in cases {
+            assert_eq!(
+                reference(Variant::Id, version, t, p, "password", "somesalt"),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_parameters() {
+        let params = Params {
+            memory: 7,
+            iterations: 1,
+            parallelism: 1,
+        };
+        assert!(hash(Variant::Id, VERSION, &params, b"password", b"somesalt", 32).is_err());
+    }
+}
@@ -0,0 +1,163 @@
+//! Non-cryptographic checksums, they are fast and good at catching corruption
+//! but must not be relied on against tampering.
+
+use super::Digest;
+
+/// Table for a reflected (least significant bit first) CRC-32.
+const fn crc32_table(poly: u32) -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ poly
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+/// Table for a reflected (least significant bit first) CRC-64.
+const fn crc64_table(poly: u64) -> [u64; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u64;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ poly
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+static CRC32_IEEE: [u32; 256] = crc32_table(0xEDB8_8320);
+static CRC32_CASTAGNOLI: [u32; 256] = crc32_table(0x82F6_3B78);
+static CRC64_XZ: [u64; 256] = crc64_table(0xC96C_5795_D787_0F42);
+
+/// CRC-32 as used by zip, gzip and png (`table` = IEEE) or iSCSI and ext4 (`table` = Castagnoli).
+pub struct Crc32 {
+    table: &'static [u32; 256],
+    crc: u32,
+}
+
+impl Crc32 {
+    pub fn ieee() -> Self {
+        Self {
+            table: &CRC32_IEEE,
+            crc: !0,
+        }
+    }
+
+    pub fn castagnoli() -> Self {
+        Self {
+            table: &CRC32_CASTAGNOLI,
+            crc: !0,
+        }
+    }
+}
+
+impl Digest for Crc32 {
+    fn update(&mut self, data: &[u8]) {
+        for &b in data {
+            self.crc = self.table[((self.crc ^ b as u32) & 0xff) as usize] ^ (self.crc >> 8);
+        }
+    }
+
+    fn finish(&self) -> Vec<u8> {
+        (!self.crc).to_be_bytes().to_vec()
+    }
+}
+
+/// CRC-64/XZ, also known as CRC-64/GO-ECMA.
+pub struct Crc64 {
+    crc: u64,
+}
+
+impl Crc64 {
+    pub fn new() -> Self {
+        Self { crc: !0 }
+    }
+}
+
+impl Digest for Crc64 {
+    fn update(&mut self, data: &[u8]) {
+        for &b in data {
+            self.crc = CRC64_XZ[((self.crc ^ b as u64) & 0xff) as usize] ^ (self.crc >> 8);
+        }
+    }
+
+    fn finish(&self) -> Vec<u8> {
+        (!self.crc).to_be_bytes().to_vec()
+    }
+}
+
+/// Adler-32 as used by zlib.
+pub struct Adler32 {
+    a: u32,
+    b: u32,
+}
+
+impl Adler32 {
+    const MOD: u32 = 65521;
+    /// Largest number of bytes that can be summed before `b` may overflow.
+    const NMAX: usize = 5552;
+
+    pub fn new() -> Self {
+        Self { a: 1, b: 0 }
+    }
+}
+
+impl Digest for Adler32 {
+    fn update(&mut self, data: &[u8]) {
+        for chunk in data.chunks(Self::NMAX) {
+            for &byte in chunk {
+                self.a += byte as u32;
+                self.b += self.a;
+            }
+            self.a %= Self::MOD;
+            self.b %= Self::MOD;
+        }
+    }
+
+    fn finish(&self) -> Vec<u8> {
+        ((self.b << 16) | self.a).to_be_bytes().to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::hex_digest;
+    use super::{Adler32, Crc32, Crc64};
+
+    /// The check values of the CRC catalogue are the checksums of "123456789".
+    const CHECK: &[u8] = b"123456789";
+
+    #[test]
+    fn check_values() {
+        assert_eq!(hex_digest(Crc32::ieee, CHECK), "cbf43926");
+        assert_eq!(hex_digest(Crc32::castagnoli, CHECK), "e3069283");
+        assert_eq!(hex_digest(Crc64::new, CHECK), "995dc9bbdf1939fa");
+        assert_eq!(hex_digest(Adler32::new, CHECK), "091e01de");
+    }
+
+    #[test]
+    fn empty_input() {
+        assert_eq!(hex_digest(Crc32::ieee, b""), "00000000");
+        assert_eq!(hex_digest(Adler32::new, b""), "00000001");
+    }
+}
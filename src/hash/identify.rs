@@ -0,0 +1,273 @@
+//! Guesses the algorithm of a digest from its length, alphabet and prefix.
+
+use openssl::hash::MessageDigest;
+
+use super::mac::read_key;
+use super::{digest_bytes, password, Algo};
+
+#[derive(clap::Args, Debug)]
+pub struct IdentifyArgs {
+    /// Digest or password hash to identify, as hex, base64 or a `$id$...` string
+    digest: String,
+
+    /// Hash this plaintext with every candidate and report which ones match: the
+    /// plaintext itself, `@<file>` or `env:<VAR>`
+    #[clap(long)]
+    plaintext: Option<String>,
+}
+
+type Check = Box<dyn Fn(&[u8]) -> anyhow::Result<bool>>;
+
+struct Candidate {
+    name: String,
+    /// `None` for the algorithms that are recognised but not implemented.
+    check: Option<Check>,
+}
+
+impl Candidate {
+    fn new(
+        name: impl Into<String>,
+        check: impl Fn(&[u8]) -> anyhow::Result<bool> + 'static,
+    ) -> Self {
+        Candidate {
+            name: name.into(),
+            check: Some(Box::new(check)),
+        }
+    }
+
+    fn unchecked(name: impl Into<String>) -> Self {
+        Candidate {
+            name: name.into(),
+            check: None,
+        }
+    }
+}
+
+pub fn run(args: IdentifyArgs) -> anyhow::Result<()> {
+    let (format, candidates) = identify(args.digest.trim());
+    if candidates.is_empty() {
+        anyhow::bail!("No candidate algorithm: {}", format);
+    }
+    eprintln!("{}", format);
+
+    let plaintext = match args.plaintext {
+        Some(spec) => read_key(&spec)?,
+        None => {
+            for candidate in &candidates {
+                println!("{}", candidate.name);
+            }
+            return Ok(());
+        }
+    };
+
+    let mut matched = false;
+    for candidate in &candidates {
+        match &candidate.check {
+            Some(check) if check(&plaintext)? => {
+                matched = true;
+                println!("{}: OK", candidate.name);
+            }
+            Some(_) => println!("{}: no match", candidate.name),
+            None => println!("{}: can not be checked", candidate.name),
+        }
+    }
+    if !matched {
+        anyhow::bail!("The plaintext does not match any candidate");
+    }
+    Ok(())
+}
+
+/// A description of the format and the candidate algorithms, the most common first.
+fn identify(digest: &str) -> (String, Vec<Candidate>) {
+    if let Some(result) = identify_prefixed(digest) {
+        return result;
+    }
+
+    let hex = digest.strip_prefix("0x").unwrap_or(digest);
+    if let Ok(bytes) = hex::decode(hex) {
+        let format = format!("{} hex characters ({} bytes)", hex.len(), bytes.len());
+        return (format, by_length(bytes));
+    }
+
+    // MySQL 4.1+ PASSWORD(), `*` followed by the uppercase hex SHA-1 of the SHA-1
+    if let Some(bytes) = digest.strip_prefix('*').and_then(|h| hex::decode(h).ok()) {
+        let candidates = if bytes.len() == 20 {
+            vec![Candidate::new("MySQL 4.1+ PASSWORD()", move |p| {
+                let once = digest_bytes(Algo::Sha1, p)?;
+                Ok(digest_bytes(Algo::Sha1, &once)? == bytes)
+            })]
+        } else {
+            Vec::new()
+        };
+        return ("MySQL password hash".to_string(), candidates);
+    }
+
+    let decoded = base64::decode(digest)
+        .or_else(|_| base64::decode_config(digest, base64::URL_SAFE))
+        .or_else(|_| base64::decode_config(digest, base64::STANDARD_NO_PAD))
+        .or_else(|_| base64::decode_config(digest, base64::URL_SAFE_NO_PAD));
+    if let Ok(bytes) = decoded {
+        let format = format!("{} base64 characters ({} bytes)", digest.len(), bytes.len());
+        return (format, by_length(bytes));
+    }
+
+    ("not hex, base64 or a known prefix".to_string(), Vec::new())
+}
+
+/// Candidates for a raw digest, which can only be told apart by their length.
+fn by_length(expected: Vec<u8>) -> Vec<Candidate> {
+    let (algos, others): (&[Algo], &[&str]) = match expected.len() {
+        4 => (
+            &[Algo::Crc32, Algo::Crc32c, Algo::Adler32, Algo::Murmur3],
+            &[],
+        ),
+        8 => (&[Algo::Xxh64, Algo::Xxh3, Algo::Crc64], &[]),
+        16 => (&[Algo::Md5, Algo::Murmur3_128], &["MD4", "NTLM"]),
+        20 => (&[Algo::Sha1], &["RIPEMD-160"]),
+        28 => (&[Algo::Sha224, Algo::Sha3_224], &[]),
+        32 => (
+            &[
+                Algo::Sha256,
+                Algo::Sha3_256,
+                Algo::Blake3,
+                Algo::Keccak256,
+                Algo::Blake2s,
+                Algo::Sha512_256,
+            ],
+            &[],
+        ),
+        48 => (&[Algo::Sha384, Algo::Sha3_384], &[]),
+        64 => (
+            &[Algo::Sha512, Algo::Sha3_512, Algo::Blake2b],
+            &["Whirlpool"],
+        ),
+        _ => (&[], &[]),
+    };
+
+    let mut candidates: Vec<_> = algos
+        .iter()
+        .map(|&algo| {
+            let expected = expected.clone();
+            Candidate::new(algo.name(), move |p| Ok(digest_bytes(algo, p)? == expected))
+        })
+        .collect();
+    candidates.extend(others.iter().map(|&name| Candidate::unchecked(name)));
+    candidates
+}
+
+/// Password hashes and the other formats that name their algorithm.
+fn identify_prefixed(digest: &str) -> Option<(String, Vec<Candidate>)> {
+    let phc = |name: &str| {
+        let hash = digest.to_string();
+        let candidate = Candidate::new(name, move |p| password::matches(&hash, p));
+        Some(("password hash".to_string(), vec![candidate]))
+    };
+    let unchecked = |name: &str| {
+        Some((
+            "password hash".to_string(),
+            vec![Candidate::unchecked(name)],
+        ))
+    };
+
+    let id = digest
+        .strip_prefix('$')
+        .and_then(|d| d.split_once('$'))
+        .map(|(id, _)| id)
+        .unwrap_or_default();
+    match id {
+        "2a" | "2b" | "2y" => phc("bcrypt"),
+        "2x" => unchecked("bcrypt (PHP crypt_blowfish bug compatible)"),
+        "argon2id" | "argon2i" | "argon2d" => phc(id),
+        "scrypt" => phc("scrypt"),
+        "pbkdf2-sha1" | "pbkdf2-sha256" | "pbkdf2-sha512" => phc(id),
+        "1" => unchecked("MD5-crypt"),
+        "apr1" => unchecked("Apache MD5 (apr1)"),
+        "5" => unchecked("SHA-256-crypt"),
+        "6" => unchecked("SHA-512-crypt"),
+        "y" => unchecked("yescrypt"),
+        "gy" => unchecked("gost-yescrypt"),
+        "7" => unchecked("scrypt (crypt)"),
+        "sha1" => unchecked("SHA-1-crypt (NetBSD)"),
+        "P" | "H" => unchecked("phpass"),
+        _ => None,
+    }
+    .or_else(|| identify_labelled(digest))
+}
+
+/// Formats with a non `$` prefix: LDAP `{SCHEME}`, Django and Subresource Integrity.
+fn identify_labelled(digest: &str) -> Option<(String, Vec<Candidate>)> {
+    if let Some(rest) = digest.strip_prefix("{CRYPT}") {
+        return identify_prefixed(rest);
+    }
+    if let Some((scheme, value)) = digest.strip_prefix('{').and_then(|d| d.split_once('}')) {
+        let (md, salted) = match scheme.to_ascii_uppercase().as_str() {
+            "MD5" => (MessageDigest::md5(), false),
+            "SMD5" => (MessageDigest::md5(), true),
+            "SHA" => (MessageDigest::sha1(), false),
+            "SSHA" => (MessageDigest::sha1(), true),
+            "SHA256" => (MessageDigest::sha256(), false),
+            "SSHA256" => (MessageDigest::sha256(), true),
+            "SHA512" => (MessageDigest::sha512(), false),
+            "SSHA512" => (MessageDigest::sha512(), true),
+            _ => return None,
+        };
+        let name = format!("LDAP {{{}}}", scheme.to_ascii_uppercase());
+        let value = base64::decode(value).ok()?;
+        if value.len() < md.size() || (!salted && value.len() != md.size()) {
+            return None;
+        }
+        // the digest of the password followed by the salt, then the salt
+        let candidate = Candidate::new(name, move |p| {
+            let (expected, salt) = value.split_at(md.size());
+            let computed = openssl::hash::hash(md, &[p, salt].concat())?;
+            Ok(&computed[..] == expected)
+        });
+        return Some(("LDAP password hash".to_string(), vec![candidate]));
+    }
+
+    if let Some((algo, iterations, salt, hash)) = django_pbkdf2(digest) {
+        let name = format!("Django PBKDF2-{}", algo.name());
+        let md = algo.message_digest()?;
+        let salt = salt.to_string();
+        let hash = base64::decode(hash).ok()?;
+        let candidate = Candidate::new(name, move |p| {
+            let mut computed = vec![0; hash.len()];
+            openssl::pkcs5::pbkdf2_hmac(p, salt.as_bytes(), iterations, md, &mut computed)?;
+            Ok(computed == hash)
+        });
+        return Some(("Django password hash".to_string(), vec![candidate]));
+    }
+    if let Some(hash) = digest.strip_prefix("argon2$") {
+        // Django stores argon2 hashes without the leading `$`
+        let hash = format!("${}", hash);
+        let candidate = Candidate::new("Django argon2", move |p| password::matches(&hash, p));
+        return Some(("Django password hash".to_string(), vec![candidate]));
+    }
+
+    let (prefix, value) = digest.split_once('-')?;
+    let algo = match prefix {
+        "sha256" => Algo::Sha256,
+        "sha384" => Algo::Sha384,
+        "sha512" => Algo::Sha512,
+        _ => return None,
+    };
+    let expected = base64::decode(value).ok()?;
+    let candidate = Candidate::new(format!("Subresource Integrity {}", algo.name()), move |p| {
+        Ok(digest_bytes(algo, p)? == expected)
+    });
+    Some(("Subresource Integrity string".to_string(), vec![candidate]))
+}
+
+/// `pbkdf2_<algo>$<iterations>$<salt>$<base64 hash>` as stored by Django.
+fn django_pbkdf2(digest: &str) -> Option<(Algo, usize, &str, &str)> {
+    let mut fields = digest.split('$');
+    let algo = match fields.next()? {
+        "pbkdf2_sha256" => Algo::Sha256,
+        "pbkdf2_sha1" => Algo::Sha1,
+        _ => return None,
+    };
+    let iterations = fields.next()?.parse().ok().filter(|i| *i > 0)?;
+    let salt = fields.next()?;
+    let hash = fields.next()?;
+    Some((algo, iterations, salt, hash))
+}
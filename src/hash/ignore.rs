@@ -0,0 +1,165 @@
+//! `.gitignore` style patterns, see https://git-scm.com/docs/gitignore
+
+use std::path::Path;
+
+struct Pattern {
+    glob: String,
+    negated: bool,
+    dir_only: bool,
+}
+
+impl Pattern {
+    fn parse(line: &str) -> Option<Self> {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        // trailing spaces are ignored unless escaped
+        let mut line = line.trim_end_matches(' ').to_string();
+        if line.ends_with('\\') {
+            line.push(' ');
+        }
+
+        let (negated, line) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest.to_string()),
+            None => (false, line),
+        };
+        let (dir_only, line) = match line.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, line.as_str()),
+        };
+        if line.is_empty() {
+            return None;
+        }
+
+        // without a slash the pattern matches a name at any depth, otherwise
+        // it is relative to the directory of the .gitignore file
+        let glob = if line.contains('/') {
+            line.trim_start_matches('/').to_string()
+        } else {
+            format!("**/{}", line)
+        };
+        Some(Pattern {
+            glob,
+            negated,
+            dir_only,
+        })
+    }
+}
+
+/// Patterns from one source, relative to the directory `base`.
+pub struct Rules {
+    base: String,
+    patterns: Vec<Pattern>,
+}
+
+impl Rules {
+    pub fn parse(base: &str, content: &str) -> Self {
+        Rules {
+            base: base.to_string(),
+            patterns: content.lines().filter_map(Pattern::parse).collect(),
+        }
+    }
+
+    /// Rules of the `.gitignore` file in `dir`, if there is one.
+    pub fn load(dir: &Path, base: &str) -> Option<Self> {
+        let content = std::fs::read_to_string(dir.join(".gitignore")).ok()?;
+        Some(Self::parse(base, &content))
+    }
+
+    /// `Some(true)` if the last matching pattern ignores the path, `Some(false)` if
+    /// it re-includes it and `None` if no pattern matches.
+    fn decide(&self, path: &str, is_dir: bool) -> Option<bool> {
+        let path = if self.base.is_empty() {
+            path
+        } else {
+            path.strip_prefix(&self.base)?.strip_prefix('/')?
+        };
+
+        self.patterns
+            .iter()
+            .rev()
+            .find(|p| (is_dir || !p.dir_only) && glob_match(p.glob.as_bytes(), path.as_bytes()))
+            .map(|p| !p.negated)
+    }
+}
+
+/// Whether `path` (relative to the root, `/` separated) is ignored, later rules win.
+pub fn is_ignored<'a>(
+    rules: impl DoubleEndedIterator<Item = &'a Rules>,
+    path: &str,
+    is_dir: bool,
+) -> bool {
+    rules
+        .rev()
+        .find_map(|r| r.decide(path, is_dir))
+        .unwrap_or(false)
+}
+
+/// Matches `*`, `?`, `[...]` and `**` where only `**` matches across `/`.
+pub fn glob_match(pattern: &[u8], path: &[u8]) -> bool {
+    match pattern {
+        [] => path.is_empty(),
+        [b'*', b'*', b'/', rest @ ..] => {
+            glob_match(rest, path)
+                || path
+                    .iter()
+                    .enumerate()
+                    .any(|(i, &c)| c == b'/' && glob_match(rest, &path[i + 1..]))
+        }
+        [b'*', b'*'] => true,
+        [b'*', rest @ ..] => {
+            for i in 0..=path.len() {
+                if glob_match(rest, &path[i..]) {
+                    return true;
+                }
+                if path.get(i) == Some(&b'/') {
+                    break;
+                }
+            }
+            false
+        }
+        [b'?', rest @ ..] => match path {
+            [c, path @ ..] if *c != b'/' => glob_match(rest, path),
+            _ => false,
+        },
+        [b'[', rest @ ..] => match (path, class_match(rest, path.first().copied())) {
+            ([_, path @ ..], Some((true, rest))) => glob_match(rest, path),
+            // an unterminated `[` is a literal
+            ([b'[', path @ ..], None) => glob_match(rest, path),
+            _ => false,
+        },
+        [b'\\', c, rest @ ..] => path.first() == Some(c) && glob_match(rest, &path[1..]),
+        [c, rest @ ..] => path.first() == Some(c) && glob_match(rest, &path[1..]),
+    }
+}
+
+/// Matches `c` against the class after a `[`, returns the result and the
+/// pattern after the closing `]`, or `None` when the class is not terminated.
+fn class_match(class: &[u8], c: Option<u8>) -> Option<(bool, &[u8])> {
+    let (negated, mut class) = match class {
+        [b'!' | b'^', rest @ ..] => (true, rest),
+        _ => (false, class),
+    };
+
+    let mut matched = false;
+    let mut first = true;
+    loop {
+        match class {
+            [] => return None,
+            [b']', rest @ ..] if !first => {
+                let matched = matched != negated && c.is_some() && c != Some(b'/');
+                return Some((matched, rest));
+            }
+            [lo, b'-', hi, rest @ ..] if *hi != b']' => {
+                matched |= c.is_some_and(|c| (*lo..=*hi).contains(&c));
+                class = rest;
+            }
+            [x, rest @ ..] => {
+                matched |= c == Some(*x);
+                class = rest;
+            }
+        }
+        first = false;
+    }
+}
@@ -0,0 +1,150 @@
+//! Keccak-256 as used by Ethereum. It is the original Keccak submission, which
+//! only differs from the standardised SHA3-256 in its padding.
+
+use super::Digest;
+
+const ROUND_CONSTANTS: [u64; 24] = [
+    0x0000_0000_0000_0001,
+    0x0000_0000_0000_8082,
+    0x8000_0000_0000_808A,
+    0x8000_0000_8000_8000,
+    0x0000_0000_0000_808B,
+    0x0000_0000_8000_0001,
+    0x8000_0000_8000_8081,
+    0x8000_0000_0000_8009,
+    0x0000_0000_0000_008A,
+    0x0000_0000_0000_0088,
+    0x0000_0000_8000_8009,
+    0x0000_0000_8000_000A,
+    0x0000_0000_8000_808B,
+    0x8000_0000_0000_008B,
+    0x8000_0000_0000_8089,
+    0x8000_0000_0000_8003,
+    0x8000_0000_0000_8002,
+    0x8000_0000_0000_0080,
+    0x0000_0000_0000_800A,
+    0x8000_0000_8000_000A,
+    0x8000_0000_8000_8081,
+    0x8000_0000_0000_8080,
+    0x0000_0000_8000_0001,
+    0x8000_0000_8000_8008,
+];
+const ROTATIONS: [u32; 24] = [
+    1, 3, 6, 10, 15, 21, 28, 36, 45, 55, 2, 14, 27, 41, 56, 8, 25, 43, 62, 18, 39, 61, 20, 44,
+];
+const LANES: [usize; 24] = [
+    10, 7, 11, 17, 18, 3, 5, 16, 8, 21, 24, 4, 15, 23, 19, 13, 12, 2, 20, 14, 22, 9, 6, 1,
+];
+
+fn keccak_f(a: &mut [u64; 25]) {
+    for rc in ROUND_CONSTANTS {
+        // θ
+        let mut c = [0; 5];
+        for (x, c) in c.iter_mut().enumerate() {
+            *c = a[x] ^ a[x + 5] ^ a[x + 10] ^ a[x + 15] ^ a[x + 20];
+        }
+        for x in 0..5 {
+            let d = c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate_left(1);
+            for y in 0..5 {
+                a[5 * y + x] ^= d;
+            }
+        }
+
+        // ρ and π
+        let mut last = a[1];
+        for (&lane, &rotation) in LANES.iter().zip(ROTATIONS.iter()) {
+            let next = a[lane];
+            a[lane] = last.rotate_left(rotation);
+            last = next;
+        }
+
+        // χ
+        for y in 0..5 {
+            let row: [u64; 5] = a[5 * y..5 * y + 5].try_into().unwrap();
+            for x in 0..5 {
+                a[5 * y + x] = row[x] ^ (!row[(x + 1) % 5] & row[(x + 2) % 5]);
+            }
+        }
+
+        // ι
+        a[0] ^= rc;
+    }
+}
+
+pub struct Keccak256 {
+    state: [u64; 25],
+    buffer: Vec<u8>,
+}
+
+impl Keccak256 {
+    const RATE: usize = 136;
+    const OUTPUT_LEN: usize = 32;
+
+    pub fn new() -> Self {
+        Self {
+            state: [0; 25],
+            buffer: Vec::with_capacity(Self::RATE),
+        }
+    }
+
+    fn absorb(state: &mut [u64; 25], block: &[u8]) {
+        for (lane, word) in state.iter_mut().zip(block.chunks_exact(8)) {
+            *lane ^= u64::from_le_bytes(word.try_into().unwrap());
+        }
+        keccak_f(state);
+    }
+}
+
+impl Digest for Keccak256 {
+    fn update(&mut self, mut data: &[u8]) {
+        if !self.buffer.is_empty() {
+            let take = (Self::RATE - self.buffer.len()).min(data.len());
+            self.buffer.extend_from_slice(&data[..take]);
+            data = &data[take..];
+            if self.buffer.len() < Self::RATE {
+                return;
+            }
+            Self::absorb(&mut self.state, &self.buffer);
+            self.buffer.clear();
+        }
+
+        let mut blocks = data.chunks_exact(Self::RATE);
+        for block in &mut blocks {
+            Self::absorb(&mut self.state, block);
+        }
+        self.buffer.extend_from_slice(blocks.remainder());
+    }
+
+    fn finish(&self) -> Vec<u8> {
+        let mut state = self.state;
+        let mut block = self.buffer.clone();
+        block.resize(Self::RATE, 0);
+        block[self.buffer.len()] ^= 0x01;
+        block[Self::RATE - 1] ^= 0x80;
+        Self::absorb(&mut state, &block);
+
+        state
+            .iter()
+            .flat_map(|lane| lane.to_le_bytes())
+            .take(Self::OUTPUT_LEN)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::hex_digest;
+    use super::Keccak256;
+
+    #[test]
+    fn known_answers() {
+        assert_eq!(
+            hex_digest(Keccak256::new, b""),
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+        assert_eq!(
+            hex_digest(Keccak256::new, b"abc"),
+            "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45"
+        );
+    }
+}
@@ -0,0 +1,177 @@
+//! Message authentication codes: HMAC, blake3 keyed hashing and blake3 key derivation.
+
+use anyhow::Context;
+use openssl::pkey::PKey;
+use openssl::sign::Signer;
+
+use super::{blake3_output, blake3_stream, parse_length, Algo, Encoding};
+use crate::input::InputSource;
+use crate::progress::{Progress, ProgressReader};
+
+#[derive(clap::Args, Debug)]
+pub struct HmacArgs {
+    /// Hash function to use, blake3 uses its native keyed mode
+    #[clap(long, arg_enum, default_value = "sha256")]
+    algo: Algo,
+
+    /// Secret key: the key itself, `@<file>`, `env:<VAR>` or `hex:<hex bytes>`
+    #[clap(long)]
+    key: String,
+
+    /// Expected signature (hex or base64, an `sha256=` style prefix is ignored),
+    /// compared in constant time
+    #[clap(long)]
+    verify: Option<String>,
+
+    #[clap(long, arg_enum, default_value = "hex")]
+    encoding: Encoding,
+
+    #[clap(flatten)]
+    input: InputSource,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct DeriveKeyArgs {
+    /// Blake3 key derivation context, should be hardcoded, globally unique and application specific
+    #[clap(long)]
+    context: String,
+
+    #[clap(long, arg_enum, default_value = "hex")]
+    encoding: Encoding,
+
+    /// Length of the derived key in bytes
+    #[clap(long, value_name = "N", parse(try_from_str = parse_length))]
+    length: Option<usize>,
+
+    /// Key material to derive the key from
+    #[clap(flatten)]
+    input: InputSource,
+}
+
+pub fn hmac(args: HmacArgs, progress: bool) -> anyhow::Result<()> {
+    let key = read_key(&args.key)?;
+    let mut reader = args.input.open()?;
+    let progress = Progress::new(progress, reader.len());
+
+    let mac = match args.algo.message_digest() {
+        Some(md) => {
+            let key = PKey::hmac(&key)?;
+            let mut signer = Signer::new(md, &key)?;
+            std::io::copy(
+                &mut ProgressReader::new(&mut reader, &progress),
+                &mut signer,
+            )
+            .context("Reading the input")?;
+            signer.sign_to_vec()?
+        }
+        None if args.algo == Algo::Blake3 => {
+            let key: [u8; blake3::KEY_LEN] = key.as_slice().try_into().map_err(|_| {
+                anyhow::anyhow!(
+                    "blake3 keys must be exactly {} bytes, got {}",
+                    blake3::KEY_LEN,
+                    key.len()
+                )
+            })?;
+            let hasher = blake3::Hasher::new_keyed(&key);
+            blake3_stream(hasher, &mut reader, &progress)?
+                .finalize()
+                .as_bytes()
+                .to_vec()
+        }
+        None => anyhow::bail!("{} can not be used for HMAC", args.algo.name()),
+    };
+    progress.finish();
+
+    match args.verify {
+        Some(expected) => verify(&expected, &mac, args.encoding),
+        None => args.encoding.print(&mac),
+    }
+}
+
+pub fn derive_key(args: DeriveKeyArgs, progress: bool) -> anyhow::Result<()> {
+    let mut reader = args.input.open()?;
+    let progress = Progress::new(progress, reader.len());
+    let hasher = blake3::Hasher::new_derive_key(&args.context);
+    let hasher = blake3_stream(hasher, &mut reader, &progress)?;
+    progress.finish();
+
+    args.encoding.print(&blake3_output(&hasher, args.length))
+}
+
+fn verify(expected: &str, mac: &[u8], encoding: Encoding) -> anyhow::Result<()> {
+    let expected = expected.trim();
+    // GitHub sends `sha256=<hex>`, Slack `v0=<hex>`
+    let expected = match expected.split_once('=') {
+        Some((scheme, rest))
+            if !rest.is_empty()
+                && !rest.starts_with('=')
+                && scheme
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || b == b'-') =>
+        {
+            rest
+        }
+        _ => expected,
+    };
+
+    let decoded = hex::decode(expected)
+        .ok()
+        .or_else(|| base64::decode(expected).ok())
+        .or_else(|| base64::decode_config(expected, base64::URL_SAFE_NO_PAD).ok())
+        .context("Expected signature is neither hex nor base64")?;
+
+    if decoded.len() == mac.len() && openssl::memcmp::eq(&decoded, mac) {
+        println!("OK");
+        Ok(())
+    } else {
+        let computed = match encoding {
+            Encoding::Raw => hex::encode(mac),
+            _ => encoding.encode(mac)?,
+        };
+        anyhow::bail!("Signature does NOT match, computed {}", computed)
+    }
+}
+
+/// Reads a key given as `@<file>`, `env:<VAR>`, `hex:<hex bytes>` or the key itself.
+pub fn read_key(spec: &str) -> anyhow::Result<Vec<u8>> {
+    if let Some(file) = spec.strip_prefix('@') {
+        return std::fs::read(file).context(format!("Reading key from file '{}'", file));
+    }
+    if let Some(var) = spec.strip_prefix("env:") {
+        return std::env::var(var)
+            .map(String::into_bytes)
+            .context(format!("Reading key from environment variable '{}'", var));
+    }
+    if let Some(hex) = spec.strip_prefix("hex:") {
+        return hex::decode(hex).map_err(|e| anyhow::anyhow!("Invalid hex key: {}", e));
+    }
+    Ok(spec.as_bytes().to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::verify;
+    use crate::hash::Encoding;
+
+    #[test]
+    fn signatures() {
+        let mac = [0xab; 32];
+        let hex = "ab".repeat(32);
+        assert!(verify(&hex, &mac, Encoding::Hex).is_ok());
+        assert!(verify(&format!("sha256={}", hex), &mac, Encoding::Hex).is_ok());
+        assert!(verify(&format!("v0={}", hex.to_uppercase()), &mac, Encoding::Hex).is_ok());
+        assert!(verify(&base64::encode(mac), &mac, Encoding::Hex).is_ok());
+
+        // hex of the wrong length is a mismatch, not an unreadable signature
+        let error = verify("sha256=00", &mac, Encoding::Hex).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!("Signature does NOT match, computed {}", hex)
+        );
+        let error = verify("not a signature!", &mac, Encoding::Hex).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Expected signature is neither hex nor base64"
+        );
+    }
+}
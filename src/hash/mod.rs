@@ -0,0 +1,497 @@
+use std::io::{Read, Write};
+
+use anyhow::Context;
+use openssl::hash::{Hasher, MessageDigest};
+
+use crate::input::{InputReader, InputSource};
+use crate::progress::{Progress, ProgressReader};
+
+mod argon2;
+mod bcrypt;
+mod checksum;
+mod identify;
+mod ignore;
+mod keccak;
+mod mac;
+mod murmur;
+mod parallel;
+mod password;
+pub mod sri;
+mod sums;
+mod tree;
+mod xxhash;
+
+/// Read buffer size, large enough for blake3 to use its SIMD implementations.
+const BUFFER_SIZE: usize = 1024 * 1024;
+
+#[derive(clap::Args, Debug)]
+pub struct HashArg {
+    /// Show a progress indicator on stderr (only when it is a terminal)
+    #[clap(long, global = true)]
+    progress: bool,
+
+    #[clap(subcommand)]
+    action: HashAction,
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum HashAction {
+    Md5(HashInput),
+    Sha1(HashInput),
+    Sha224(HashInput),
+    Sha256(HashInput),
+    Sha384(HashInput),
+    Sha512(HashInput),
+    /// SHA-512 truncated to 256 bits
+    #[clap(name = "sha512-256")]
+    Sha512_256(HashInput),
+    #[clap(name = "sha3-224")]
+    Sha3_224(HashInput),
+    #[clap(name = "sha3-256")]
+    Sha3_256(HashInput),
+    #[clap(name = "sha3-384")]
+    Sha3_384(HashInput),
+    #[clap(name = "sha3-512")]
+    Sha3_512(HashInput),
+    /// Keccak-256 as used by Ethereum (differs from SHA3-256)
+    Keccak256(HashInput),
+    /// BLAKE2b-512
+    Blake2b(HashInput),
+    /// BLAKE2s-256
+    Blake2s(HashInput),
+    Blake3(HashInput),
+    /// CRC-32 (IEEE) as used by zip, gzip and png
+    Crc32(HashInput),
+    /// CRC-32C (Castagnoli) as used by iSCSI, ext4 and btrfs
+    Crc32c(HashInput),
+    /// CRC-64/XZ
+    Crc64(HashInput),
+    Adler32(HashInput),
+    /// xxHash64
+    Xxh64(HashInput),
+    /// XXH3 64 bits
+    Xxh3(HashInput),
+    /// MurmurHash3 x86 32 bits
+    Murmur3(HashInput),
+    /// MurmurHash3 x64 128 bits
+    #[clap(name = "murmur3-128")]
+    Murmur3_128(HashInput),
+
+    /// Keyed-hash message authentication code, verifies webhook signatures
+    Hmac(mac::HmacArgs),
+
+    /// Derive a key from key material with blake3 in key derivation mode
+    DeriveKey(mac::DeriveKeyArgs),
+
+    /// Single digest of a whole directory, or a manifest of its files
+    Tree(tree::TreeArgs),
+
+    /// Subresource Integrity strings (`sha384-<base64>`) for <script> and <link> tags
+    Sri(sri::SriArgs),
+
+    /// List the algorithms that may have produced a digest, optionally checking a plaintext
+    Identify(identify::IdentifyArgs),
+
+    /// Hash a password with argon2id, bcrypt, scrypt or PBKDF2
+    Password(password::PasswordArgs),
+
+    /// Check a password against an argon2, bcrypt, scrypt or PBKDF2 hash
+    PasswordVerify(password::PasswordVerifyArgs),
+}
+
+#[derive(clap::Args, Debug)]
+struct HashInput {
+    /// Filenames to read from or raw input (must specify --raw)
+    inputs: Vec<String>,
+
+    /// must be provided if raw input is provided
+    #[clap(long)]
+    raw: bool,
+
+    /// Print `<digest>  <name>` lines like sha256sum, even for a single input
+    #[clap(long, short = 'H')]
+    with_filename: bool,
+
+    /// Print BSD style `<ALGO> (<name>) = <digest>` lines
+    #[clap(long)]
+    tag: bool,
+
+    /// Read checksums from the file and verify them
+    #[clap(long, short, value_name = "FILE", conflicts_with_all = &["inputs", "raw", "tag", "with-filename"])]
+    check: Option<String>,
+
+    #[clap(long, arg_enum, default_value = "hex")]
+    encoding: Encoding,
+
+    /// Output length in bytes, blake3 can produce digests of any length
+    #[clap(long, value_name = "N", parse(try_from_str = parse_length))]
+    length: Option<usize>,
+}
+
+#[derive(clap::ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Algo {
+    Md5,
+    Sha1,
+    Sha224,
+    Sha256,
+    Sha384,
+    Sha512,
+    #[clap(name = "sha512-256")]
+    Sha512_256,
+    #[clap(name = "sha3-224")]
+    Sha3_224,
+    #[clap(name = "sha3-256")]
+    Sha3_256,
+    #[clap(name = "sha3-384")]
+    Sha3_384,
+    #[clap(name = "sha3-512")]
+    Sha3_512,
+    Keccak256,
+    Blake2b,
+    Blake2s,
+    Blake3,
+    Crc32,
+    Crc32c,
+    Crc64,
+    Adler32,
+    Xxh64,
+    Xxh3,
+    Murmur3,
+    #[clap(name = "murmur3-128")]
+    Murmur3_128,
+}
+
+impl Algo {
+    /// Name used in BSD style checksum lines.
+    fn name(self) -> &'static str {
+        match self {
+            Algo::Md5 => "MD5",
+            Algo::Sha1 => "SHA1",
+            Algo::Sha224 => "SHA224",
+            Algo::Sha256 => "SHA256",
+            Algo::Sha384 => "SHA384",
+            Algo::Sha512 => "SHA512",
+            Algo::Sha512_256 => "SHA512-256",
+            Algo::Sha3_224 => "SHA3-224",
+            Algo::Sha3_256 => "SHA3-256",
+            Algo::Sha3_384 => "SHA3-384",
+            Algo::Sha3_512 => "SHA3-512",
+            Algo::Keccak256 => "KECCAK-256",
+            Algo::Blake2b => "BLAKE2b",
+            Algo::Blake2s => "BLAKE2s",
+            Algo::Blake3 => "BLAKE3",
+            Algo::Crc32 => "CRC32",
+            Algo::Crc32c => "CRC32C",
+            Algo::Crc64 => "CRC64",
+            Algo::Adler32 => "ADLER32",
+            Algo::Xxh64 => "XXH64",
+            Algo::Xxh3 => "XXH3",
+            Algo::Murmur3 => "MURMUR3",
+            Algo::Murmur3_128 => "MURMUR3-128",
+        }
+    }
+
+    /// The OpenSSL implementation, for the algorithms it provides.
+    fn message_digest(self) -> Option<MessageDigest> {
+        match self {
+            Algo::Md5 => Some(MessageDigest::md5()),
+            Algo::Sha1 => Some(MessageDigest::sha1()),
+            Algo::Sha224 => Some(MessageDigest::sha224()),
+            Algo::Sha256 => Some(MessageDigest::sha256()),
+            Algo::Sha384 => Some(MessageDigest::sha384()),
+            Algo::Sha512 => Some(MessageDigest::sha512()),
+            Algo::Sha512_256 => MessageDigest::from_name("SHA512-256"),
+            Algo::Sha3_224 => Some(MessageDigest::sha3_224()),
+            Algo::Sha3_256 => Some(MessageDigest::sha3_256()),
+            Algo::Sha3_384 => Some(MessageDigest::sha3_384()),
+            Algo::Sha3_512 => Some(MessageDigest::sha3_512()),
+            Algo::Blake2b => MessageDigest::from_name("BLAKE2b512"),
+            Algo::Blake2s => MessageDigest::from_name("BLAKE2s256"),
+            _ => None,
+        }
+    }
+
+    /// The implementation from this crate, for the algorithms OpenSSL does not provide.
+    fn native(self) -> Option<Box<dyn Digest>> {
+        match self {
+            Algo::Keccak256 => Some(Box::new(keccak::Keccak256::new())),
+            Algo::Crc32 => Some(Box::new(checksum::Crc32::ieee())),
+            Algo::Crc32c => Some(Box::new(checksum::Crc32::castagnoli())),
+            Algo::Crc64 => Some(Box::new(checksum::Crc64::new())),
+            Algo::Adler32 => Some(Box::new(checksum::Adler32::new())),
+            Algo::Xxh64 => Some(Box::new(xxhash::Xxh64::new())),
+            Algo::Xxh3 => Some(Box::new(xxhash::Xxh3::new())),
+            Algo::Murmur3 => Some(Box::new(murmur::Murmur3_32::new())),
+            Algo::Murmur3_128 => Some(Box::new(murmur::Murmur3_128::new())),
+            _ => None,
+        }
+    }
+
+    /// `length` is only used by blake3, for extendable output.
+    fn digest(
+        self,
+        reader: InputReader,
+        length: Option<usize>,
+        progress: &Progress,
+    ) -> anyhow::Result<Vec<u8>> {
+        if self == Algo::Blake3 {
+            return blake3_digest(reader, length, progress);
+        }
+        let mut reader = ProgressReader::new(reader, progress);
+
+        if let Some(md) = self.message_digest() {
+            let mut hasher = Hasher::new(md)?;
+            std::io::copy(&mut reader, &mut hasher).context("Reading the input")?;
+            return Ok(hasher.finish()?.to_vec());
+        }
+
+        let native = self
+            .native()
+            .with_context(|| format!("{} is not supported by this build", self.name()))?;
+        let mut writer = DigestWriter(native);
+        std::io::copy(&mut reader, &mut writer).context("Reading the input")?;
+        Ok(writer.0.finish())
+    }
+}
+
+/// A hash function implemented in this crate, computed incrementally over the input.
+trait Digest {
+    fn update(&mut self, data: &[u8]);
+
+    fn finish(&self) -> Vec<u8>;
+}
+
+/// Adapter so a [`Digest`] can be the target of [`std::io::copy`].
+struct DigestWriter(Box<dyn Digest>);
+
+impl std::io::Write for DigestWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// The hex digest of `data`, the same whether it is fed at once or in small pieces
+/// that go through the buffering of partial blocks.
+#[cfg(test)]
+fn hex_digest<D: Digest>(new: impl Fn() -> D, data: &[u8]) -> String {
+    let whole = {
+        let mut digest = new();
+        digest.update(data);
+        hex::encode(digest.finish())
+    };
+    for size in [1, 7, 64] {
+        let mut digest = new();
+        for part in data.chunks(size) {
+            digest.update(part);
+        }
+        assert_eq!(
+            hex::encode(digest.finish()),
+            whole,
+            "fed {} bytes at a time",
+            size
+        );
+    }
+    whole
+}
+
+pub fn run(arg: HashArg) -> anyhow::Result<()> {
+    let (algo, input) = match arg.action {
+        HashAction::Md5(i) => (Algo::Md5, i),
+        HashAction::Sha1(i) => (Algo::Sha1, i),
+        HashAction::Sha224(i) => (Algo::Sha224, i),
+        HashAction::Sha256(i) => (Algo::Sha256, i),
+        HashAction::Sha384(i) => (Algo::Sha384, i),
+        HashAction::Sha512(i) => (Algo::Sha512, i),
+        HashAction::Sha512_256(i) => (Algo::Sha512_256, i),
+        HashAction::Sha3_224(i) => (Algo::Sha3_224, i),
+        HashAction::Sha3_256(i) => (Algo::Sha3_256, i),
+        HashAction::Sha3_384(i) => (Algo::Sha3_384, i),
+        HashAction::Sha3_512(i) => (Algo::Sha3_512, i),
+        HashAction::Keccak256(i) => (Algo::Keccak256, i),
+        HashAction::Blake2b(i) => (Algo::Blake2b, i),
+        HashAction::Blake2s(i) => (Algo::Blake2s, i),
+        HashAction::Blake3(i) => (Algo::Blake3, i),
+        HashAction::Crc32(i) => (Algo::Crc32, i),
+        HashAction::Crc32c(i) => (Algo::Crc32c, i),
+        HashAction::Crc64(i) => (Algo::Crc64, i),
+        HashAction::Adler32(i) => (Algo::Adler32, i),
+        HashAction::Xxh64(i) => (Algo::Xxh64, i),
+        HashAction::Xxh3(i) => (Algo::Xxh3, i),
+        HashAction::Murmur3(i) => (Algo::Murmur3, i),
+        HashAction::Murmur3_128(i) => (Algo::Murmur3_128, i),
+        HashAction::Hmac(args) => return mac::hmac(args, arg.progress).context("HMAC"),
+        HashAction::DeriveKey(args) => {
+            return mac::derive_key(args, arg.progress).context("Blake3 Derive Key")
+        }
+        HashAction::Tree(args) => return tree::run(args, arg.progress).context("Tree Hash"),
+        HashAction::Sri(args) => return sri::run(args).context("Subresource Integrity"),
+        HashAction::Identify(args) => return identify::run(args).context("Identify Hash"),
+        HashAction::Password(args) => return password::hash(args).context("Password Hash"),
+        HashAction::PasswordVerify(args) => {
+            return password::verify(args).context("Password Verify")
+        }
+    };
+
+    if input.length.is_some() && algo != Algo::Blake3 {
+        anyhow::bail!("--length is only supported by blake3");
+    }
+    if let Some(check) = input.check {
+        return sums::check(algo, &check, input.length, arg.progress);
+    }
+    hash(algo, input, arg.progress).context(format!("{} Hash", algo.name()))
+}
+
+fn hash(algo: Algo, input: HashInput, progress: bool) -> anyhow::Result<()> {
+    let sources = if input.inputs.is_empty() {
+        vec![None]
+    } else {
+        input.inputs.into_iter().map(Some).collect()
+    };
+    let with_filename = input.with_filename || input.tag || sources.len() > 1;
+    if with_filename && input.encoding == Encoding::Raw {
+        anyhow::bail!("Raw digests can not be printed as checksum lines");
+    }
+
+    for source in sources {
+        let name = source.clone().unwrap_or_else(|| "-".to_string());
+        let digest = digest(
+            algo,
+            InputSource {
+                input: source,
+                raw: input.raw,
+            },
+            input.length,
+            progress,
+        )?;
+
+        if input.tag {
+            let digest = input.encoding.encode(&digest)?;
+            println!("{}", sums::format_bsd(algo, &name, &digest));
+        } else if with_filename {
+            let digest = input.encoding.encode(&digest)?;
+            println!("{}", sums::format_gnu(&name, &digest));
+        } else {
+            input.encoding.print(&digest)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn digest(
+    algo: Algo,
+    is: InputSource,
+    length: Option<usize>,
+    progress: bool,
+) -> anyhow::Result<Vec<u8>> {
+    let reader = is.open()?;
+    let progress = Progress::new(progress, reader.len());
+    let digest = algo.digest(reader, length, &progress);
+    progress.finish();
+
+    digest
+}
+
+/// Digest of input that is already in memory.
+pub fn digest_bytes(algo: Algo, bytes: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let reader = InputReader::Raw(std::io::Cursor::new(bytes.to_vec()));
+    algo.digest(reader, None, &Progress::new(false, None))
+}
+
+fn blake3_digest(
+    mut reader: InputReader,
+    length: Option<usize>,
+    progress: &Progress,
+) -> anyhow::Result<Vec<u8>> {
+    let len = reader.len().unwrap_or(0);
+    match (&reader, length) {
+        // the parallel implementation only produces the default length
+        (InputReader::File(file), None | Some(blake3::OUT_LEN)) if parallel::worth_it(len) => {
+            let hash = parallel::hash_file(file, progress).context("Reading the input")?;
+            Ok(hash.as_bytes().to_vec())
+        }
+        _ => {
+            let hasher = blake3_stream(blake3::Hasher::new(), &mut reader, progress)?;
+            Ok(blake3_output(&hasher, length))
+        }
+    }
+}
+
+fn parse_length(s: &str) -> Result<usize, String> {
+    match s.parse() {
+        Ok(0) | Err(_) => Err("must be a positive number".to_string()),
+        Ok(n) => Ok(n),
+    }
+}
+
+/// Extendable output of `length` bytes, the standard 32 bytes by default.
+fn blake3_output(hasher: &blake3::Hasher, length: Option<usize>) -> Vec<u8> {
+    let mut output = vec![0; length.unwrap_or(blake3::OUT_LEN)];
+    hasher.finalize_xof().fill(&mut output);
+    output
+}
+
+fn blake3_stream(
+    mut hasher: blake3::Hasher,
+    reader: impl Read,
+    progress: &Progress,
+) -> anyhow::Result<blake3::Hasher> {
+    let mut reader = ProgressReader::new(reader, progress);
+    let mut buf = vec![0; BUFFER_SIZE];
+    loop {
+        match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => hasher.update(&buf[..n]),
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e).context("Reading the input"),
+        };
+    }
+
+    Ok(hasher)
+}
+
+/// How digests are printed.
+#[derive(clap::ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Encoding {
+    /// Lowercase hex
+    Hex,
+    /// Uppercase hex
+    #[clap(name = "HEX")]
+    HexUpper,
+    Base64,
+    /// URL-safe base64 without padding
+    Base64url,
+    Base32,
+    /// The digest bytes as they are
+    Raw,
+}
+
+impl Encoding {
+    fn encode(self, digest: &[u8]) -> anyhow::Result<String> {
+        Ok(match self {
+            Encoding::Hex => hex::encode(digest),
+            Encoding::HexUpper => hex::encode_upper(digest),
+            Encoding::Base64 => base64::encode(digest),
+            Encoding::Base64url => base64::encode_config(digest, base64::URL_SAFE_NO_PAD),
+            Encoding::Base32 => crate::codec::base32::encode(digest),
+            Encoding::Raw => anyhow::bail!("Raw digests can only be printed on their own"),
+        })
+    }
+
+    /// Prints the digest on its own line, or its bytes without a newline for raw output.
+    fn print(self, digest: &[u8]) -> anyhow::Result<()> {
+        if self == Encoding::Raw {
+            let mut stdout = std::io::stdout();
+            stdout.write_all(digest)?;
+            stdout.flush()?;
+            return Ok(());
+        }
+        println!("{}", self.encode(digest)?);
+        Ok(())
+    }
+}
@@ -0,0 +1,225 @@
+//! MurmurHash3 with seed 0, the 32 bit x86 and 128 bit x64 variants.
+
+use super::Digest;
+
+pub struct Murmur3_32 {
+    h: u32,
+    tail: Vec<u8>,
+    total_len: u64,
+}
+
+impl Murmur3_32 {
+    const C1: u32 = 0xcc9e_2d51;
+    const C2: u32 = 0x1b87_3593;
+
+    pub fn new() -> Self {
+        Self {
+            h: 0,
+            tail: Vec::with_capacity(4),
+            total_len: 0,
+        }
+    }
+
+    fn mix_k(k: u32) -> u32 {
+        k.wrapping_mul(Self::C1)
+            .rotate_left(15)
+            .wrapping_mul(Self::C2)
+    }
+
+    fn block(&mut self, block: &[u8]) {
+        let k = u32::from_le_bytes(block.try_into().unwrap());
+        self.h ^= Self::mix_k(k);
+        self.h = self
+            .h
+            .rotate_left(13)
+            .wrapping_mul(5)
+            .wrapping_add(0xe654_6b64);
+    }
+}
+
+impl Digest for Murmur3_32 {
+    fn update(&mut self, mut data: &[u8]) {
+        self.total_len += data.len() as u64;
+
+        if !self.tail.is_empty() {
+            let take = (4 - self.tail.len()).min(data.len());
+            self.tail.extend_from_slice(&data[..take]);
+            data = &data[take..];
+            if self.tail.len() < 4 {
+                return;
+            }
+            let block = std::mem::take(&mut self.tail);
+            self.block(&block);
+        }
+
+        let mut blocks = data.chunks_exact(4);
+        for block in &mut blocks {
+            self.block(block);
+        }
+        self.tail.extend_from_slice(blocks.remainder());
+    }
+
+    fn finish(&self) -> Vec<u8> {
+        let mut h = self.h;
+        if !self.tail.is_empty() {
+            let k = self
+                .tail
+                .iter()
+                .rev()
+                .fold(0u32, |k, &b| (k << 8) | b as u32);
+            h ^= Self::mix_k(k);
+        }
+
+        // the length is mixed in modulo 2^32 by the reference implementation
+        h ^= self.total_len as u32;
+        h ^= h >> 16;
+        h = h.wrapping_mul(0x85eb_ca6b);
+        h ^= h >> 13;
+        h = h.wrapping_mul(0xc2b2_ae35);
+        h ^= h >> 16;
+
+        h.to_be_bytes().to_vec()
+    }
+}
+
+pub struct Murmur3_128 {
+    h1: u64,
+    h2: u64,
+    tail: Vec<u8>,
+    total_len: u64,
+}
+
+impl Murmur3_128 {
+    const C1: u64 = 0x87c3_7b91_1142_53d5;
+    const C2: u64 = 0x4cf5_ad43_2745_937f;
+
+    pub fn new() -> Self {
+        Self {
+            h1: 0,
+            h2: 0,
+            tail: Vec::with_capacity(16),
+            total_len: 0,
+        }
+    }
+
+    fn mix_k1(k: u64) -> u64 {
+        k.wrapping_mul(Self::C1)
+            .rotate_left(31)
+            .wrapping_mul(Self::C2)
+    }
+
+    fn mix_k2(k: u64) -> u64 {
+        k.wrapping_mul(Self::C2)
+            .rotate_left(33)
+            .wrapping_mul(Self::C1)
+    }
+
+    fn block(&mut self, block: &[u8]) {
+        let k1 = u64::from_le_bytes(block[..8].try_into().unwrap());
+        let k2 = u64::from_le_bytes(block[8..].try_into().unwrap());
+
+        self.h1 ^= Self::mix_k1(k1);
+        self.h1 = self
+            .h1
+            .rotate_left(27)
+            .wrapping_add(self.h2)
+            .wrapping_mul(5)
+            .wrapping_add(0x52dc_e729);
+
+        self.h2 ^= Self::mix_k2(k2);
+        self.h2 = self
+            .h2
+            .rotate_left(31)
+            .wrapping_add(self.h1)
+            .wrapping_mul(5)
+            .wrapping_add(0x3849_5ab5);
+    }
+
+    fn fmix(mut k: u64) -> u64 {
+        k ^= k >> 33;
+        k = k.wrapping_mul(0xff51_afd7_ed55_8ccd);
+        k ^= k >> 33;
+        k = k.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+        k ^ (k >> 33)
+    }
+}
+
+impl Digest for Murmur3_128 {
+    fn update(&mut self, mut data: &[u8]) {
+        self.total_len += data.len() as u64;
+
+        if !self.tail.is_empty() {
+            let take = (16 - self.tail.len()).min(data.len());
+            self.tail.extend_from_slice(&data[..take]);
+            data = &data[take..];
+            if self.tail.len() < 16 {
+                return;
+            }
+            let block = std::mem::take(&mut self.tail);
+            self.block(&block);
+        }
+
+        let mut blocks = data.chunks_exact(16);
+        for block in &mut blocks {
+            self.block(block);
+        }
+        self.tail.extend_from_slice(blocks.remainder());
+    }
+
+    fn finish(&self) -> Vec<u8> {
+        let (mut h1, mut h2) = (self.h1, self.h2);
+        let word = |bytes: &[u8]| bytes.iter().rev().fold(0u64, |k, &b| (k << 8) | b as u64);
+        if self.tail.len() > 8 {
+            h2 ^= Self::mix_k2(word(&self.tail[8..]));
+        }
+        if !self.tail.is_empty() {
+            h1 ^= Self::mix_k1(word(&self.tail[..self.tail.len().min(8)]));
+        }
+
+        h1 ^= self.total_len;
+        h2 ^= self.total_len;
+        h1 = h1.wrapping_add(h2);
+        h2 = h2.wrapping_add(h1);
+        h1 = Self::fmix(h1);
+        h2 = Self::fmix(h2);
+        h1 = h1.wrapping_add(h2);
+        h2 = h2.wrapping_add(h1);
+
+        // byte order of the reference implementation's output buffer
+        [h1.to_le_bytes(), h2.to_le_bytes()].concat()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::hex_digest;
+    use super::{Murmur3_128, Murmur3_32};
+
+    const FOX: &[u8] = b"The quick brown fox jumps over the lazy dog";
+
+    #[test]
+    fn murmur3_32() {
+        assert_eq!(hex_digest(Murmur3_32::new, b""), "00000000");
+        assert_eq!(hex_digest(Murmur3_32::new, b"foo"), "f6a5c420");
+        assert_eq!(hex_digest(Murmur3_32::new, b"hello"), "248bfa47");
+        assert_eq!(hex_digest(Murmur3_32::new, FOX), "2e4ff723");
+    }
+
+    /// In the byte order of the reference implementation, as `mmh3.hash_bytes` and
+    /// Guava print them.
+    #[test]
+    fn murmur3_128() {
+        assert_eq!(
+            hex_digest(Murmur3_128::new, b""),
+            "00000000000000000000000000000000"
+        );
+        assert_eq!(
+            hex_digest(Murmur3_128::new, b"foo"),
+            "6145f501578671e2877dba2be487af7e"
+        );
+        assert_eq!(
+            hex_digest(Murmur3_128::new, FOX),
+            "6c1b07bc7bbc4be347939ac4a93c437a"
+        );
+    }
+}
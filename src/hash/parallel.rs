@@ -0,0 +1,183 @@
+//! Multithreaded blake3 of large files. Each thread reads and hashes its own
+//! subtree of the blake3 merkle tree, which are then joined by parent nodes.
+
+use std::fs::File;
+use std::io;
+
+use blake3::guts::{parent_cv, ChunkState, CHUNK_LEN};
+use blake3::Hash;
+
+use crate::progress::Progress;
+
+/// Smaller files are not worth the overhead of spawning threads.
+const MIN_LEN: u64 = 16 * 1024 * 1024;
+const BUFFER_SIZE: usize = 256 * 1024;
+
+pub fn worth_it(len: u64) -> bool {
+    cfg!(any(unix, windows)) && len >= MIN_LEN && threads() > 1
+}
+
+pub fn hash_file(file: &File, progress: &Progress) -> io::Result<Hash> {
+    // enough levels of splitting to give every thread a subtree
+    let depth = usize::BITS - (threads() - 1).leading_zeros();
+    hash_file_split(file, progress, depth)
+}
+
+/// The file split in `2^depth` subtrees, each hashed on its own thread.
+fn hash_file_split(file: &File, progress: &Progress, depth: u32) -> io::Result<Hash> {
+    let len = file.metadata()?.len();
+    subtree(file, progress, 0, len, depth, true)
+}
+
+fn threads() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+}
+
+fn subtree(
+    file: &File,
+    progress: &Progress,
+    offset: u64,
+    len: u64,
+    depth: u32,
+    is_root: bool,
+) -> io::Result<Hash> {
+    if depth == 0 || len <= CHUNK_LEN as u64 {
+        return serial(file, progress, offset, len, is_root);
+    }
+
+    let left_len = left_len(len);
+    let (left, right) = std::thread::scope(|s| {
+        let left = s.spawn(|| subtree(file, progress, offset, left_len, depth - 1, false));
+        let right = subtree(
+            file,
+            progress,
+            offset + left_len,
+            len - left_len,
+            depth - 1,
+            false,
+        );
+        (left.join().expect("blake3 worker thread panicked"), right)
+    });
+
+    Ok(parent_cv(&left?, &right?, is_root))
+}
+
+/// Hashes one subtree on the current thread, `offset` must be at a subtree boundary.
+fn serial(
+    file: &File,
+    progress: &Progress,
+    offset: u64,
+    len: u64,
+    is_root: bool,
+) -> io::Result<Hash> {
+    let first_chunk = offset / CHUNK_LEN as u64;
+    let mut chunk = ChunkState::new(first_chunk);
+    let mut chunks_done = 0;
+    let mut stack: Vec<Hash> = Vec::new();
+
+    let mut buf = vec![0; BUFFER_SIZE];
+    let mut pos = 0;
+    while pos < len {
+        let want = (len - pos).min(buf.len() as u64) as usize;
+        let n = read_at(file, &mut buf[..want], offset + pos)?;
+        if n == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        progress.advance(n as u64);
+        pos += n as u64;
+
+        let mut data = &buf[..n];
+        while !data.is_empty() {
+            // a full chunk is only finalized once more data follows, the last one may be the root
+            if chunk.len() == CHUNK_LEN {
+                let mut cv = chunk.finalize(false);
+                chunks_done += 1;
+                let mut total = chunks_done;
+                while total & 1 == 0 {
+                    cv = parent_cv(&stack.pop().unwrap(), &cv, false);
+                    total >>= 1;
+                }
+                stack.push(cv);
+                chunk = ChunkState::new(first_chunk + chunks_done);
+            }
+            let take = (CHUNK_LEN - chunk.len()).min(data.len());
+            chunk.update(&data[..take]);
+            data = &data[take..];
+        }
+    }
+
+    let mut cv = chunk.finalize(is_root && stack.is_empty());
+    while let Some(left) = stack.pop() {
+        cv = parent_cv(&left, &cv, is_root && stack.is_empty());
+    }
+    Ok(cv)
+}
+
+/// Size of the left subtree: the largest power of two number of chunks that
+/// leaves at least one byte for the right subtree.
+fn left_len(len: u64) -> u64 {
+    let full_chunks = (len - 1) / CHUNK_LEN as u64;
+    (1 << (63 - full_chunks.leading_zeros())) * CHUNK_LEN as u64
+}
+
+#[cfg(unix)]
+fn read_at(file: &File, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+    std::os::unix::fs::FileExt::read_at(file, buf, offset)
+}
+
+#[cfg(windows)]
+fn read_at(file: &File, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+    std::os::windows::fs::FileExt::seek_read(file, buf, offset)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn read_at(_: &File, _: &mut [u8], _: u64) -> io::Result<usize> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use blake3::guts::CHUNK_LEN;
+
+    use super::{hash_file, hash_file_split};
+    use crate::progress::Progress;
+
+    /// `blake3::guts` is not a stable API, this catches a blake3 update changing it.
+    #[test]
+    fn same_as_blake3() {
+        let path = std::env::temp_dir().join(format!("devstuff-parallel-{}", std::process::id()));
+        let progress = Progress::new(false, None);
+        let lens = [
+            0,
+            1,
+            CHUNK_LEN,
+            CHUNK_LEN + 1,
+            8 * CHUNK_LEN - 1,
+            37 * CHUNK_LEN + 123,
+        ];
+        for len in lens {
+            let data: Vec<u8> = (0..len).map(|i| (i % 251) as u8).collect();
+            std::fs::File::create(&path)
+                .unwrap()
+                .write_all(&data)
+                .unwrap();
+            let file = std::fs::File::open(&path).unwrap();
+            let expected = blake3::hash(&data);
+            assert_eq!(
+                hash_file(&file, &progress).unwrap(),
+                expected,
+                "{} bytes",
+                len
+            );
+            for depth in 1..=4 {
+                let hash = hash_file_split(&file, &progress, depth).unwrap();
+                assert_eq!(hash, expected, "{} bytes in {} subtrees", len, 1 << depth);
+            }
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,583 @@
+//! Password hashing with slow, salted hash functions. Hashes are printed in the
+//! PHC string format (https://github.com/P-H-C/phc-string-format/blob/master/phc-sf-spec.md),
+//! except bcrypt which has its own `$2b$<cost>$<salt><hash>` format.
+
+use std::io::BufRead;
+
+use anyhow::Context;
+use openssl::hash::MessageDigest;
+
+use super::argon2::{self, Variant};
+use super::bcrypt;
+use super::mac::read_key;
+
+const SALT_LEN: usize = 16;
+const HASH_LEN: usize = 32;
+
+#[derive(clap::ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PasswordAlgo {
+    Argon2id,
+    Bcrypt,
+    Scrypt,
+    #[clap(name = "pbkdf2-sha256")]
+    Pbkdf2Sha256,
+    #[clap(name = "pbkdf2-sha512")]
+    Pbkdf2Sha512,
+}
+
+impl PasswordAlgo {
+    fn name(self) -> &'static str {
+        match self {
+            PasswordAlgo::Argon2id => "argon2id",
+            PasswordAlgo::Bcrypt => "bcrypt",
+            PasswordAlgo::Scrypt => "scrypt",
+            PasswordAlgo::Pbkdf2Sha256 => "pbkdf2-sha256",
+            PasswordAlgo::Pbkdf2Sha512 => "pbkdf2-sha512",
+        }
+    }
+}
+
+#[derive(clap::Args, Debug)]
+pub struct PasswordArgs {
+    /// Defaults to the parameters recommended by OWASP for each algorithm
+    #[clap(long, arg_enum, default_value = "argon2id")]
+    algo: PasswordAlgo,
+
+    /// argon2id memory in KiB [default: 19456]
+    #[clap(long, value_name = "KiB")]
+    memory: Option<u32>,
+
+    /// argon2id passes or PBKDF2 iterations [default: 2 for argon2id, 600000 for
+    /// pbkdf2-sha256, 210000 for pbkdf2-sha512]
+    #[clap(long, value_name = "N")]
+    iterations: Option<u32>,
+
+    /// argon2id lanes or scrypt parallelization [default: 1]
+    #[clap(long, value_name = "N")]
+    parallelism: Option<u32>,
+
+    /// bcrypt cost or scrypt log2(N) [default: 12 for bcrypt, 17 for scrypt]
+    #[clap(long, value_name = "N")]
+    cost: Option<u32>,
+
+    /// scrypt block size [default: 8]
+    #[clap(long, value_name = "N")]
+    block_size: Option<u32>,
+
+    /// The password: the password itself, `@<file>` or `env:<VAR>`. It is prompted
+    /// for without echo on a terminal, otherwise read from the first line of stdin
+    #[clap(long)]
+    password: Option<String>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct PasswordVerifyArgs {
+    /// Hash in the PHC string format (argon2, scrypt, pbkdf2) or bcrypt's `$2b$...`
+    hash: String,
+
+    /// The candidate password, same as for `hash password`
+    #[clap(long)]
+    password: Option<String>,
+}
+
+pub fn hash(args: PasswordArgs) -> anyhow::Result<()> {
+    let unused = |options: &[(&str, bool)]| match options.iter().find(|(_, set)| *set) {
+        Some((name, _)) => Err(anyhow::anyhow!(
+            "--{} is not used by {}",
+            name,
+            args.algo.name()
+        )),
+        None => Ok(()),
+    };
+    let memory = ("memory", args.memory.is_some());
+    let iterations = ("iterations", args.iterations.is_some());
+    let parallelism = ("parallelism", args.parallelism.is_some());
+    let cost = ("cost", args.cost.is_some());
+    let block_size = ("block-size", args.block_size.is_some());
+    match args.algo {
+        PasswordAlgo::Argon2id => unused(&[cost, block_size])?,
+        PasswordAlgo::Bcrypt => unused(&[memory, iterations, parallelism, block_size])?,
+        PasswordAlgo::Scrypt => unused(&[memory, iterations])?,
+        PasswordAlgo::Pbkdf2Sha256 | PasswordAlgo::Pbkdf2Sha512 => {
+            unused(&[memory, parallelism, cost, block_size])?
+        }
+    }
+
+    let password = read_password(args.password, true)?;
+    let mut salt = [0; SALT_LEN];
+    openssl::rand::rand_bytes(&mut salt)?;
+
+    let phc = match args.algo {
+        PasswordAlgo::Argon2id => {
+            let params = argon2::Params {
+                memory: args.memory.unwrap_or(19456),
+                iterations: args.iterations.unwrap_or(2),
+                parallelism: args.parallelism.unwrap_or(1),
+            };
+            let hash = argon2::hash(
+                Variant::Id,
+                argon2::VERSION,
+                &params,
+                &password,
+                &salt,
+                HASH_LEN,
+            )?;
+            format!(
+                "${}$v={}$m={},t={},p={}${}${}",
+                Variant::Id.name(),
+                argon2::VERSION,
+                params.memory,
+                params.iterations,
+                params.parallelism,
+                b64(&salt),
+                b64(&hash)
+            )
+        }
+        PasswordAlgo::Bcrypt => {
+            let cost = args.cost.unwrap_or(12);
+            if !(4..=31).contains(&cost) {
+                anyhow::bail!("bcrypt cost must be between 4 and 31");
+            }
+            if password.len() > bcrypt::MAX_PASSWORD_LEN {
+                eprintln!(
+                    "Warning: bcrypt only uses the first {} bytes of the password",
+                    bcrypt::MAX_PASSWORD_LEN
+                );
+            }
+            let hash = bcrypt::hash(cost, &salt, &password);
+            format!("$2b${:02}${}{}", cost, bcrypt_b64(&salt), bcrypt_b64(&hash))
+        }
+        PasswordAlgo::Scrypt => {
+            let (log_n, r, p) = (
+                args.cost.unwrap_or(17),
+                args.block_size.unwrap_or(8),
+                args.parallelism.unwrap_or(1),
+            );
+            let hash = scrypt(&password, &salt, log_n, r, p, HASH_LEN)?;
+            format!(
+                "$scrypt$ln={},r={},p={}${}${}",
+                log_n,
+                r,
+                p,
+                b64(&salt),
+                b64(&hash)
+            )
+        }
+        PasswordAlgo::Pbkdf2Sha256 | PasswordAlgo::Pbkdf2Sha512 => {
+            let (default_iterations, md) = match args.algo {
+                PasswordAlgo::Pbkdf2Sha256 => (600_000, MessageDigest::sha256()),
+                _ => (210_000, MessageDigest::sha512()),
+            };
+            let iterations = args.iterations.unwrap_or(default_iterations);
+            let mut hash = vec![0; md.size()];
+            pbkdf2(&password, &salt, iterations, md, &mut hash)?;
+            format!(
+                "${}$i={},l={}${}${}",
+                args.algo.name(),
+                iterations,
+                hash.len(),
+                b64(&salt),
+                b64(&hash)
+            )
+        }
+    };
+
+    println!("{}", phc);
+    Ok(())
+}
+
+pub fn verify(args: PasswordVerifyArgs) -> anyhow::Result<()> {
+    // a malformed hash is reported before prompting for the password
+    let hash = PasswordHash::parse(args.hash.trim())?;
+    let password = read_password(args.password, false)?;
+
+    if hash.matches(&password)? {
+        println!("OK");
+        Ok(())
+    } else {
+        anyhow::bail!("Password does NOT match")
+    }
+}
+
+/// Whether `password` matches a hash in any of the formats `password-verify` supports.
+pub fn matches(hash: &str, password: &[u8]) -> anyhow::Result<bool> {
+    PasswordHash::parse(hash)?.matches(password)
+}
+
+enum PasswordHash<'a> {
+    Bcrypt(BcryptHash),
+    Phc(Phc<'a>),
+}
+
+impl<'a> PasswordHash<'a> {
+    fn parse(s: &'a str) -> anyhow::Result<Self> {
+        match BcryptHash::parse(s) {
+            Some(bcrypt) => Ok(PasswordHash::Bcrypt(bcrypt)),
+            None => Ok(PasswordHash::Phc(Phc::parse(s)?)),
+        }
+    }
+
+    fn matches(&self, password: &[u8]) -> anyhow::Result<bool> {
+        let (expected, computed) = match self {
+            PasswordHash::Bcrypt(bcrypt) => (
+                bcrypt.hash.to_vec(),
+                bcrypt::hash(bcrypt.cost, &bcrypt.salt, password).to_vec(),
+            ),
+            PasswordHash::Phc(phc) => (phc.hash.clone(), phc.compute(password)?),
+        };
+        Ok(openssl::memcmp::eq(&expected, &computed))
+    }
+}
+
+/// A parsed `$<id>[$v=<version>][$<param>=<value>[,...]]$<salt>$<hash>` string.
+struct Phc<'a> {
+    id: &'a str,
+    version: Option<u32>,
+    params: Vec<(&'a str, &'a str)>,
+    salt: Vec<u8>,
+    hash: Vec<u8>,
+}
+
+impl<'a> Phc<'a> {
+    fn parse(s: &'a str) -> anyhow::Result<Self> {
+        let mut fields = s
+            .strip_prefix('$')
+            .context("Hashes in the PHC string format start with `$`")?
+            .split('$');
+        let id = fields.next().unwrap_or_default();
+
+        let mut field = fields.next();
+        let version = match field.and_then(|f| f.strip_prefix("v=")) {
+            Some(v) => {
+                field = fields.next();
+                Some(v.parse().context("Invalid version")?)
+            }
+            None => None,
+        };
+
+        let mut params = Vec::new();
+        if let Some(f) = field.filter(|f| f.contains('=')) {
+            for param in f.split(',') {
+                let (name, value) = param
+                    .split_once('=')
+                    .with_context(|| format!("Invalid parameter '{}'", param))?;
+                params.push((name, value));
+            }
+            field = fields.next();
+        }
+
+        let decode = |name: &str, field: Option<&str>| {
+            let field = field.with_context(|| format!("The hash has no {}", name))?;
+            base64::decode_config(field, base64::STANDARD_NO_PAD)
+                .with_context(|| format!("Invalid {} '{}', expected base64", name, field))
+        };
+        let salt = decode("salt", field)?;
+        let hash = decode("hash", fields.next())?;
+        if fields.next().is_some() {
+            anyhow::bail!("Unexpected fields after the hash");
+        }
+
+        Ok(Phc {
+            id,
+            version,
+            params,
+            salt,
+            hash,
+        })
+    }
+
+    fn param(&self, name: &str) -> anyhow::Result<u32> {
+        let (_, value) = self
+            .params
+            .iter()
+            .find(|(n, _)| *n == name)
+            .with_context(|| format!("Missing parameter '{}'", name))?;
+        value
+            .parse()
+            .with_context(|| format!("Invalid parameter {}={}", name, value))
+    }
+
+    /// Hash of `password` with the same function, parameters and salt.
+    fn compute(&self, password: &[u8]) -> anyhow::Result<Vec<u8>> {
+        if let Some(variant) = Variant::from_name(self.id) {
+            let params = argon2::Params {
+                memory: self.param("m")?,
+                iterations: self.param("t")?,
+                parallelism: self.param("p")?,
+            };
+            let version = self.version.unwrap_or(argon2::VERSION_10);
+            if version != argon2::VERSION && version != argon2::VERSION_10 {
+                anyhow::bail!("Unsupported argon2 version {}", version);
+            }
+            return argon2::hash(
+                variant,
+                version,
+                &params,
+                password,
+                &self.salt,
+                self.hash.len(),
+            );
+        }
+
+        let mut hash = vec![0; self.hash.len()];
+        match self.id {
+            "scrypt" => {
+                hash = scrypt(
+                    password,
+                    &self.salt,
+                    self.param("ln")?,
+                    self.param("r")?,
+                    self.param("p")?,
+                    self.hash.len(),
+                )?
+            }
+            "pbkdf2-sha1" => pbkdf2(
+                password,
+                &self.salt,
+                self.param("i")?,
+                MessageDigest::sha1(),
+                &mut hash,
+            )?,
+            "pbkdf2-sha256" => pbkdf2(
+                password,
+                &self.salt,
+                self.param("i")?,
+                MessageDigest::sha256(),
+                &mut hash,
+            )?,
+            "pbkdf2-sha512" => pbkdf2(
+                password,
+                &self.salt,
+                self.param("i")?,
+                MessageDigest::sha512(),
+                &mut hash,
+            )?,
+            id => anyhow::bail!("Unsupported algorithm '{}'", id),
+        }
+        Ok(hash)
+    }
+}
+
+/// A parsed `$2a$`, `$2b$` or `$2y$` bcrypt hash, which only differ for very long
+/// passwords in the implementations that had the bugs these versions fixed.
+struct BcryptHash {
+    cost: u32,
+    salt: [u8; bcrypt::SALT_LEN],
+    hash: [u8; bcrypt::HASH_LEN],
+}
+
+impl BcryptHash {
+    fn parse(s: &str) -> Option<Self> {
+        let rest = ["$2a$", "$2b$", "$2y$"]
+            .iter()
+            .find_map(|v| s.strip_prefix(v))?;
+        let (cost, rest) = rest.split_once('$')?;
+        if rest.len() != 53 || !rest.is_ascii() {
+            return None;
+        }
+        let decode = |s: &str| base64::decode_config(s, bcrypt_config()).ok();
+        Some(BcryptHash {
+            cost: cost.parse().ok().filter(|c| (4..=31).contains(c))?,
+            salt: decode(&rest[..22])?.try_into().ok()?,
+            hash: decode(&rest[22..])?.try_into().ok()?,
+        })
+    }
+}
+
+fn scrypt(
+    password: &[u8],
+    salt: &[u8],
+    log_n: u32,
+    r: u32,
+    p: u32,
+    len: usize,
+) -> anyhow::Result<Vec<u8>> {
+    if !(1..64).contains(&log_n) {
+        anyhow::bail!("scrypt cost must be between 1 and 63");
+    }
+    if r == 0 || p == 0 {
+        anyhow::bail!("scrypt block size and parallelization must be at least 1");
+    }
+    let (n, r, p) = (1u64 << log_n, r as u64, p as u64);
+    // what OpenSSL allocates, its default limit is 32 MiB
+    let max_memory = n
+        .checked_add(p + 2)
+        .and_then(|blocks| blocks.checked_mul(128 * r))
+        .context("scrypt parameters need too much memory")?;
+    let mut hash = vec![0; len];
+    openssl::pkcs5::scrypt(password, salt, n, r, p, max_memory, &mut hash)
+        .context("Invalid scrypt parameters")?;
+    Ok(hash)
+}
+
+fn pbkdf2(
+    password: &[u8],
+    salt: &[u8],
+    iterations: u32,
+    md: MessageDigest,
+    hash: &mut [u8],
+) -> anyhow::Result<()> {
+    if iterations == 0 {
+        anyhow::bail!("PBKDF2 needs at least 1 iteration");
+    }
+    openssl::pkcs5::pbkdf2_hmac(password, salt, iterations as usize, md, hash)?;
+    Ok(())
+}
+
+/// The base64 variant of PHC strings.
+fn b64(bytes: &[u8]) -> String {
+    base64::encode_config(bytes, base64::STANDARD_NO_PAD)
+}
+
+fn bcrypt_config() -> base64::Config {
+    base64::Config::new(base64::CharacterSet::Bcrypt, false)
+}
+
+fn bcrypt_b64(bytes: &[u8]) -> String {
+    base64::encode_config(bytes, bcrypt_config())
+}
+
+/// Reads the password from `spec` (see [`read_key`]), a prompt on a terminal, or
+/// the first line of stdin.
+fn read_password(spec: Option<String>, confirm: bool) -> anyhow::Result<Vec<u8>> {
+    if let Some(spec) = spec {
+        return read_key(&spec);
+    }
+
+    if atty::is(atty::Stream::Stdin) {
+        let password = prompt("Password: ")?;
+        if confirm && prompt("Confirm password: ")? != password {
+            anyhow::bail!("Passwords do not match");
+        }
+        return Ok(password.into_bytes());
+    }
+
+    let mut line = String::new();
+    std::io::stdin()
+        .lock()
+        .read_line(&mut line)
+        .context("Reading the password from stdin")?;
+    Ok(trim_newline(line).into_bytes())
+}
+
+fn trim_newline(mut line: String) -> String {
+    if line.ends_with('\n') {
+        line.pop();
+        if line.ends_with('\r') {
+            line.pop();
+        }
+    }
+    line
+}
+
+/// Reads a line from the terminal with echo disabled.
+#[cfg(unix)]
+fn prompt(message: &str) -> anyhow::Result<String> {
+    eprint!("{}", message);
+    let fd = libc::STDIN_FILENO;
+    let mut original = std::mem::MaybeUninit::uninit();
+    // SAFETY: tcgetattr initialises the termios struct when it succeeds
+    let original = unsafe {
+        if libc::tcgetattr(fd, original.as_mut_ptr()) != 0 {
+            return Err(std::io::Error::last_os_error()).context("Reading terminal settings");
+        }
+        original.assume_init()
+    };
+    let mut silent = original;
+    silent.c_lflag &= !libc::ECHO;
+    silent.c_lflag |= libc::ECHONL;
+    // SAFETY: both termios structs are fully initialised
+    unsafe {
+        if libc::tcsetattr(fd, libc::TCSANOW, &silent) != 0 {
+            return Err(std::io::Error::last_os_error()).context("Disabling the terminal echo");
+        }
+    }
+    let _echo = RestoreEcho { fd, original };
+
+    let mut line = String::new();
+    std::io::stdin()
+        .lock()
+        .read_line(&mut line)
+        .context("Reading the password")?;
+    Ok(trim_newline(line))
+}
+
+/// Puts back the terminal settings when dropped, so that the echo comes back even
+/// when reading the password fails or panics.
+#[cfg(unix)]
+struct RestoreEcho {
+    fd: libc::c_int,
+    original: libc::termios,
+}
+
+#[cfg(unix)]
+impl Drop for RestoreEcho {
+    fn drop(&mut self) {
+        // SAFETY: `original` was initialised by tcgetattr and outlives the call
+        let restored = unsafe { libc::tcsetattr(self.fd, libc::TCSANOW, &self.original) };
+        if restored != 0 {
+            eprintln!(
+                "Warning: could not turn the terminal echo back on ({}), run `stty echo`",
+                std::io::Error::last_os_error()
+            );
+        }
+    }
+}
+
+/// Reads a line from the terminal, this platform can not disable the echo.
+#[cfg(not(unix))]
+fn prompt(message: &str) -> anyhow::Result<String> {
+    eprint!("{}", message);
+    let mut line = String::new();
+    std::io::stdin()
+        .lock()
+        .read_line(&mut line)
+        .context("Reading the password")?;
+    Ok(trim_newline(line))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::matches;
+
+    #[test]
+    fn argon2_phc_strings() {
+        let hashes = [
+            "$argon2i$v=19$m=256,t=2,p=1$c29tZXNhbHQ$iekCn0Y3spW+sCcFanM2xBT63UP2sghkUoHLIUpWRS8",
+            "$argon2d$v=19$m=256,t=2,p=2$c29tZXNhbHQ$e2nJLXw4iarRKB28i678Esw3yA8cdeM+8sLUDCjrxXM",
+            "$argon2id$v=19$m=256,t=2,p=1$c29tZXNhbHQ$nf65EOgLrQMR/uIPnA4rEsF5h7TKyQwu9U1bMCHGi/4",
+            "$argon2id$v=16$m=256,t=2,p=1$c29tZXNhbHQ$2gcOV25Q8vOKPIl8vdxsf7QCjocJcf+erntOGHkpXm4",
+        ];
+        for hash in hashes {
+            assert!(matches(hash, b"password").unwrap(), "{}", hash);
+            assert!(!matches(hash, b"Password").unwrap(), "{}", hash);
+        }
+    }
+
+    #[test]
+    fn bcrypt_strings() {
+        let hash = "$2b$05$CCCCCCCCCCCCCCCCCCCCC.E5YPO9kmyuRGyh0XouQYb4YMJKvyOeW";
+        assert!(matches(hash, b"U*U").unwrap());
+        assert!(!matches(hash, b"U*U*").unwrap());
+        assert!(matches(&hash.replace("$2b$", "$2y$"), b"U*U").unwrap());
+    }
+
+    #[test]
+    fn malformed_hashes() {
+        assert!(matches("argon2id$v=19", b"password").is_err());
+        assert!(matches("$argon2id$v=19$m=256,t=2,p=1$c29tZXNhbHQ", b"password").is_err());
+    }
+
+    #[test]
+    fn scrypt_limits() {
+        let hashes = [
+            "$scrypt$ln=60,r=8,p=1$c2FsdA$aGFzaA",
+            "$scrypt$ln=63,r=4294967295,p=4294967295$c2FsdA$aGFzaA",
+            "$scrypt$ln=4,r=0,p=1$c2FsdA$aGFzaA",
+            "$scrypt$ln=4,r=8,p=0$c2FsdA$aGFzaA",
+        ];
+        for hash in hashes {
+            assert!(matches(hash, b"x").is_err(), "{}", hash);
+        }
+    }
+}
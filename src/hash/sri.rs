@@ -0,0 +1,410 @@
+//! Subresource Integrity (https://www.w3.org/TR/SRI/) for files and the
+//! `<script>` and `<link>` tags of HTML pages.
+
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+
+use super::{digest_bytes, Algo};
+use crate::input::{read_all, InputSource};
+
+#[derive(clap::ArgEnum, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SriAlgo {
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl SriAlgo {
+    fn algo(self) -> Algo {
+        match self {
+            SriAlgo::Sha256 => Algo::Sha256,
+            SriAlgo::Sha384 => Algo::Sha384,
+            SriAlgo::Sha512 => Algo::Sha512,
+        }
+    }
+
+    fn prefix(self) -> &'static str {
+        match self {
+            SriAlgo::Sha256 => "sha256",
+            SriAlgo::Sha384 => "sha384",
+            SriAlgo::Sha512 => "sha512",
+        }
+    }
+
+    fn from_prefix(prefix: &str) -> Option<Self> {
+        [SriAlgo::Sha256, SriAlgo::Sha384, SriAlgo::Sha512]
+            .into_iter()
+            .find(|a| a.prefix().eq_ignore_ascii_case(prefix))
+    }
+}
+
+#[derive(clap::Args, Debug)]
+pub struct SriArgs {
+    /// Filenames to read from or raw input (must specify --raw)
+    inputs: Vec<String>,
+
+    /// must be provided if raw input is provided
+    #[clap(long)]
+    raw: bool,
+
+    /// Hash function, repeat to include several hashes
+    #[clap(long, arg_enum, default_value = "sha384")]
+    algo: Vec<SriAlgo>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct HtmlSriArgs {
+    /// HTML file with <script> and <link> tags referencing local files
+    file: String,
+
+    /// Directory that absolute paths such as `/app.js` are relative to
+    /// [default: the directory of the HTML file]
+    #[clap(long)]
+    root: Option<String>,
+
+    /// Hash function, repeat to include several hashes
+    #[clap(long, arg_enum, default_value = "sha384")]
+    algo: Vec<SriAlgo>,
+
+    /// Update the file in place instead of printing the updated HTML
+    #[clap(long)]
+    write: bool,
+
+    /// Verify the existing integrity attributes instead of updating them
+    #[clap(long, conflicts_with = "write")]
+    check: bool,
+}
+
+pub fn run(args: SriArgs) -> anyhow::Result<()> {
+    let sources = if args.inputs.is_empty() {
+        vec![None]
+    } else {
+        args.inputs.into_iter().map(Some).collect()
+    };
+    let with_filename = sources.len() > 1;
+
+    for source in sources {
+        let name = source.clone().unwrap_or_else(|| "-".to_string());
+        let bytes = read_all(InputSource {
+            input: source,
+            raw: args.raw,
+        })?;
+        let integrity = integrity(&args.algo, &bytes)?;
+
+        if with_filename {
+            println!("{}  {}", integrity, name);
+        } else {
+            println!("{}", integrity);
+        }
+    }
+
+    Ok(())
+}
+
+pub fn run_html(args: HtmlSriArgs) -> anyhow::Result<()> {
+    let html = std::fs::read_to_string(&args.file)
+        .with_context(|| format!("Reading HTML file '{}'", args.file))?;
+    let base = Path::new(&args.file)
+        .parent()
+        .unwrap_or_else(|| Path::new(""))
+        .to_path_buf();
+    let root = args.root.map(PathBuf::from).unwrap_or_else(|| base.clone());
+
+    let resources: Vec<_> = resource_tags(&html)
+        .into_iter()
+        .filter_map(|tag| local_path(&tag.url, &base, &root).map(|path| (tag, path)))
+        .collect();
+
+    if args.check {
+        return check_html(&html, &resources);
+    }
+
+    let mut integrities = Vec::new();
+    for (tag, path) in &resources {
+        let bytes = std::fs::read(path)
+            .with_context(|| format!("Reading '{}' for {}", path.display(), tag.url))?;
+        integrities.push(integrity(&args.algo, &bytes)?);
+    }
+
+    let mut updated = html.clone();
+    // edit from the end so the positions of the earlier tags stay valid
+    for ((tag, _), integrity) in resources.iter().zip(&integrities).rev() {
+        match &tag.integrity {
+            Some(attr) if attr.value.is_empty() => {
+                updated.insert_str(attr.value.start, &format!("=\"{}\"", integrity))
+            }
+            Some(attr) => updated.replace_range(attr.value.clone(), &format!("\"{}\"", integrity)),
+            None => updated.insert_str(tag.insert_at, &format!(" integrity=\"{}\"", integrity)),
+        }
+    }
+
+    if args.write {
+        for ((tag, _), integrity) in resources.iter().zip(&integrities) {
+            println!("{}: {}", tag.url, integrity);
+        }
+        std::fs::write(&args.file, updated)
+            .with_context(|| format!("Writing HTML file '{}'", args.file))
+    } else {
+        print!("{}", updated);
+        Ok(())
+    }
+}
+
+fn check_html(html: &str, resources: &[(Tag, PathBuf)]) -> anyhow::Result<()> {
+    let mut failed = 0;
+    for (tag, path) in resources {
+        let expected = match &tag.integrity {
+            Some(attr) => unquote(&html[attr.value.clone()]),
+            None => {
+                failed += 1;
+                println!("{}: MISSING", tag.url);
+                continue;
+            }
+        };
+
+        // only the strongest hash function listed is used, as browsers do
+        let hashes: Vec<_> = expected
+            .split_ascii_whitespace()
+            .filter_map(|h| {
+                let (algo, rest) = h.split_once('-')?;
+                // options such as `?ct=...` are not part of the digest
+                let digest = rest.split('?').next().unwrap_or(rest);
+                Some((SriAlgo::from_prefix(algo)?, digest))
+            })
+            .collect();
+        let strongest = match hashes.iter().map(|(a, _)| *a).max() {
+            Some(algo) => algo,
+            None => {
+                failed += 1;
+                println!("{}: FAILED unsupported integrity '{}'", tag.url, expected);
+                continue;
+            }
+        };
+
+        match std::fs::read(path) {
+            Ok(bytes) => {
+                let actual = base64::encode(digest_bytes(strongest.algo(), &bytes)?);
+                if hashes.iter().any(|(a, d)| *a == strongest && *d == actual) {
+                    println!("{}: OK", tag.url);
+                } else {
+                    failed += 1;
+                    println!("{}: FAILED", tag.url);
+                }
+            }
+            Err(e) => {
+                failed += 1;
+                eprintln!("{}: {}", path.display(), e);
+                println!("{}: FAILED open or read", tag.url);
+            }
+        }
+    }
+
+    if failed > 0 {
+        anyhow::bail!(
+            "{} of {} local resource(s) failed verification",
+            failed,
+            resources.len()
+        );
+    }
+    Ok(())
+}
+
+fn integrity(algos: &[SriAlgo], bytes: &[u8]) -> anyhow::Result<String> {
+    let mut hashes = Vec::new();
+    for algo in algos {
+        let digest = digest_bytes(algo.algo(), bytes)?;
+        hashes.push(format!("{}-{}", algo.prefix(), base64::encode(digest)));
+    }
+    Ok(hashes.join(" "))
+}
+
+/// Path of the file a URL refers to, `None` for remote and inline resources.
+fn local_path(url: &str, base: &Path, root: &Path) -> Option<PathBuf> {
+    let url = url.trim();
+    let is_remote = url.starts_with("//")
+        || url
+            .split_once(':')
+            .map(|(scheme, _)| {
+                scheme
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || b == b'+')
+            })
+            .unwrap_or(false);
+    if url.is_empty() || is_remote {
+        return None;
+    }
+
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    Some(match path.strip_prefix('/') {
+        Some(path) => root.join(path),
+        None => base.join(path),
+    })
+}
+
+struct Attr {
+    name: String,
+    /// Position of the value in the document, including quotes.
+    value: Range<usize>,
+}
+
+struct Tag {
+    url: String,
+    integrity: Option<Attr>,
+    /// Where new attributes can be inserted.
+    insert_at: usize,
+}
+
+/// `<script src>` and `<link href>` tags of the kinds that support integrity.
+fn resource_tags(html: &str) -> Vec<Tag> {
+    let bytes = html.as_bytes();
+    // same offsets as `html`, for case insensitive searches
+    let lower = html.to_ascii_lowercase();
+    let mut tags = Vec::new();
+    let mut i = 0;
+
+    while let Some(offset) = html[i..].find('<') {
+        i += offset;
+        if html[i..].starts_with("<!--") {
+            i = html[i..]
+                .find("-->")
+                .map(|end| i + end + 3)
+                .unwrap_or(html.len());
+            continue;
+        }
+
+        let name_start = i + 1;
+        let name_end = html[name_start..]
+            .find(|c: char| !c.is_ascii_alphanumeric())
+            .map(|n| name_start + n)
+            .unwrap_or(html.len());
+        let name = html[name_start..name_end].to_ascii_lowercase();
+        if name.is_empty() {
+            i += 1;
+            continue;
+        }
+
+        let (attrs, insert_at, end) = parse_attributes(html, name_end);
+        i = end;
+
+        let attr = |n: &str| attrs.iter().find(|a| a.name == n);
+        let value = |a: &Attr| unquote(&html[a.value.clone()]).to_string();
+        let url = match name.as_str() {
+            "script" => attr("src").map(value),
+            "link" => {
+                let rel = attr("rel")
+                    .map(value)
+                    .unwrap_or_default()
+                    .to_ascii_lowercase();
+                let has_integrity = rel
+                    .split_ascii_whitespace()
+                    .any(|r| matches!(r, "stylesheet" | "preload" | "modulepreload"));
+                attr("href").map(value).filter(|_| has_integrity)
+            }
+            _ => None,
+        };
+        if let Some(url) = url {
+            let integrity = attrs.into_iter().find(|a| a.name == "integrity");
+            tags.push(Tag {
+                url,
+                integrity,
+                insert_at,
+            });
+        }
+
+        // the content of these elements is not HTML
+        if matches!(name.as_str(), "script" | "style") && bytes.get(end - 2) != Some(&b'/') {
+            let closing = format!("</{}", name);
+            i = lower[i..]
+                .find(&closing)
+                .map(|n| i + n)
+                .unwrap_or(html.len());
+        }
+    }
+
+    tags
+}
+
+/// Parses attributes from `start` to the end of the tag, returns them along with
+/// the position before the closing `>` or `/>` and the position after it.
+fn parse_attributes(html: &str, start: usize) -> (Vec<Attr>, usize, usize) {
+    let bytes = html.as_bytes();
+    let mut attrs = Vec::new();
+    let mut i = start;
+
+    let skip_whitespace = |mut i: usize| {
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        i
+    };
+
+    loop {
+        let before_whitespace = i;
+        i = skip_whitespace(i);
+        if i >= bytes.len() {
+            return (attrs, bytes.len(), bytes.len());
+        }
+        if bytes[i] == b'>' {
+            return (attrs, before_whitespace, i + 1);
+        }
+        if bytes[i] == b'/' && bytes.get(i + 1) == Some(&b'>') {
+            return (attrs, before_whitespace, i + 2);
+        }
+
+        let name_start = i;
+        while i < bytes.len() && !bytes[i].is_ascii_whitespace() && !b"=>/".contains(&bytes[i]) {
+            i += 1;
+        }
+        if i == name_start {
+            // a stray `/` or `=`
+            i += 1;
+            continue;
+        }
+        let name = html[name_start..i].to_ascii_lowercase();
+
+        let after_name = i;
+        i = skip_whitespace(i);
+        if bytes.get(i) != Some(&b'=') {
+            attrs.push(Attr {
+                name,
+                value: after_name..after_name,
+            });
+            i = after_name;
+            continue;
+        }
+        i = skip_whitespace(i + 1);
+
+        let value_start = i;
+        match bytes.get(i) {
+            Some(&quote) if quote == b'"' || quote == b'\'' => {
+                i = html[i + 1..]
+                    .find(quote as char)
+                    .map(|n| i + n + 2)
+                    .unwrap_or(bytes.len());
+            }
+            _ => {
+                while i < bytes.len() && !bytes[i].is_ascii_whitespace() && bytes[i] != b'>' {
+                    i += 1;
+                }
+            }
+        }
+        attrs.push(Attr {
+            name,
+            value: value_start..i,
+        });
+    }
+}
+
+fn unquote(value: &str) -> &str {
+    for quote in ['"', '\''] {
+        if let Some(inner) = value
+            .strip_prefix(quote)
+            .and_then(|v| v.strip_suffix(quote))
+        {
+            return inner;
+        }
+    }
+    value
+}
@@ -0,0 +1,155 @@
+//! Checksum files in the formats of coreutils (`sha256sum`) and BSD (`sha256sum --tag`).
+
+use super::{digest, Algo};
+use crate::input::InputSource;
+
+/// `<digest>  <name>`, names with a backslash or newline are escaped like coreutils does.
+pub fn format_gnu(name: &str, digest: &str) -> String {
+    let (prefix, name) = escape(name);
+    format!("{}{}  {}", prefix, digest, name)
+}
+
+/// `<ALGO> (<name>) = <digest>`
+pub fn format_bsd(algo: Algo, name: &str, digest: &str) -> String {
+    let (prefix, name) = escape(name);
+    format!("{}{} ({}) = {}", prefix, algo.name(), name, digest)
+}
+
+/// Re-hashes every file listed in `sums_file`, fails if any of them does not match.
+pub fn check(
+    algo: Algo,
+    sums_file: &str,
+    length: Option<usize>,
+    progress: bool,
+) -> anyhow::Result<()> {
+    let content = std::fs::read_to_string(sums_file)
+        .map_err(|e| anyhow::anyhow!("Reading checksum file '{}': {}", sums_file, e))?;
+
+    let (mut checked, mut mismatched, mut unreadable, mut malformed) = (0, 0, 0, 0);
+    for line in content.lines() {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (expected, path) = match parse_line(algo, line) {
+            Some(entry) => entry,
+            None => {
+                malformed += 1;
+                continue;
+            }
+        };
+
+        checked += 1;
+        let source = InputSource {
+            input: Some(path.clone()),
+            raw: false,
+        };
+        match digest(algo, source, length, progress) {
+            Ok(actual) if matches(&actual, &expected) => println!("{}: OK", path),
+            Ok(_) => {
+                mismatched += 1;
+                println!("{}: FAILED", path);
+            }
+            Err(e) => {
+                unreadable += 1;
+                eprintln!("{}: {}", path, e.root_cause());
+                println!("{}: FAILED open or read", path);
+            }
+        }
+    }
+
+    if checked == 0 {
+        anyhow::bail!(
+            "{}: no properly formatted {} checksum lines found",
+            sums_file,
+            algo.name()
+        );
+    }
+    if malformed > 0 {
+        eprintln!("WARNING: {} line(s) improperly formatted", malformed);
+    }
+    if unreadable > 0 {
+        eprintln!("WARNING: {} listed file(s) could not be read", unreadable);
+    }
+    if mismatched > 0 {
+        eprintln!("WARNING: {} computed checksum(s) did NOT match", mismatched);
+    }
+    if unreadable > 0 || mismatched > 0 {
+        anyhow::bail!(
+            "{} of {} file(s) in '{}' failed verification",
+            unreadable + mismatched,
+            checked,
+            sums_file
+        );
+    }
+
+    Ok(())
+}
+
+/// Checksum files may have hex (of any case) or base64 digests.
+fn matches(actual: &[u8], expected: &str) -> bool {
+    hex::encode(actual).eq_ignore_ascii_case(expected) || base64::encode(actual) == expected
+}
+
+/// Parses either format into the expected digest and the file name.
+pub fn parse_line(algo: Algo, line: &str) -> Option<(String, String)> {
+    let (escaped, line) = match line.strip_prefix('\\') {
+        Some(rest) => (true, rest),
+        None => (false, line),
+    };
+
+    let (digest, name) = if let Some(rest) = line
+        .strip_prefix(algo.name())
+        .and_then(|r| r.strip_prefix(" ("))
+    {
+        let (name, digest) = rest.rsplit_once(") = ")?;
+        (digest, name)
+    } else {
+        let (digest, rest) = line.split_once(' ')?;
+        let name = rest.strip_prefix(' ').or_else(|| rest.strip_prefix('*'))?;
+        (digest, name)
+    };
+
+    let is_digest = digest
+        .bytes()
+        .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'/' | b'='));
+    if !is_digest || digest.is_empty() || name.is_empty() {
+        return None;
+    }
+
+    let name = if escaped {
+        unescape(name)?
+    } else {
+        name.to_string()
+    };
+    Some((digest.to_string(), name))
+}
+
+pub fn escape(name: &str) -> (&'static str, String) {
+    if !name.contains(['\\', '\n', '\r']) {
+        return ("", name.to_string());
+    }
+    let escaped = name
+        .replace('\\', "\\\\")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r");
+    ("\\", escaped)
+}
+
+fn unescape(name: &str) -> Option<String> {
+    let mut out = String::with_capacity(name.len());
+    let mut chars = name.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next()? {
+            '\\' => out.push('\\'),
+            'n' => out.push('\n'),
+            'r' => out.push('\r'),
+            _ => return None,
+        }
+    }
+    Some(out)
+}
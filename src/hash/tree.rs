@@ -0,0 +1,308 @@
+//! Deterministic digest of a directory tree.
+//!
+//! Every directory is hashed as a listing of its entries in byte order of their
+//! names, one `<kind> [<mode> ]<digest> <name>` line each, where the digest of a
+//! file is the digest of its content and the one of a directory is the digest of
+//! its own listing. Identical subtrees therefore have identical digests, and the
+//! digest of the root covers the whole tree.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+
+use super::ignore::{self, Rules};
+use super::{digest, digest_bytes, sums, Algo};
+use crate::input::InputSource;
+
+#[derive(clap::Args, Debug)]
+pub struct TreeArgs {
+    /// Directory to hash
+    dir: String,
+
+    #[clap(long, arg_enum, default_value = "sha256")]
+    algo: Algo,
+
+    /// Include the permissions of files and directories in the digest
+    #[clap(long)]
+    modes: bool,
+
+    /// Skip paths matching the pattern (.gitignore syntax), can be repeated
+    #[clap(long, value_name = "GLOB")]
+    exclude: Vec<String>,
+
+    /// Do not honour .gitignore files
+    #[clap(long)]
+    no_ignore: bool,
+
+    /// Print `<digest>  <path>` for every file (verifiable with --check), followed by the
+    /// root digest. Symlinks are only covered by the root digest
+    #[clap(long)]
+    manifest: bool,
+
+    /// Report the files that differ from another directory or manifest, fails if there is any
+    #[clap(long, value_name = "DIR|MANIFEST", conflicts_with = "manifest")]
+    compare: Option<String>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    File,
+    Dir,
+    Symlink,
+}
+
+impl Kind {
+    fn tag(self) -> char {
+        match self {
+            Kind::File => 'f',
+            Kind::Dir => 'd',
+            Kind::Symlink => 'l',
+        }
+    }
+}
+
+/// A file or symlink of the tree.
+struct Leaf {
+    digest: Vec<u8>,
+    mode: Option<u32>,
+    symlink: bool,
+}
+
+/// Files and symlinks by their path relative to the root.
+type Leaves = BTreeMap<String, Leaf>;
+
+struct Walker {
+    algo: Algo,
+    modes: bool,
+    no_ignore: bool,
+    excludes: Rules,
+    progress: bool,
+}
+
+pub fn run(args: TreeArgs, progress: bool) -> anyhow::Result<()> {
+    let walker = Walker {
+        algo: args.algo,
+        modes: args.modes,
+        no_ignore: args.no_ignore,
+        excludes: Rules::parse("", &args.exclude.join("\n")),
+        progress,
+    };
+
+    let (root, leaves) = walker.hash_tree(Path::new(&args.dir))?;
+
+    if let Some(other) = args.compare {
+        return compare(&walker, &root, &leaves, &other);
+    }
+
+    if args.manifest {
+        for (path, leaf) in leaves.iter().filter(|(_, l)| !l.symlink) {
+            println!("{}", sums::format_gnu(path, &hex::encode(&leaf.digest)));
+        }
+        println!("# {} tree {}", args.algo.name(), hex::encode(&root));
+    } else {
+        println!("{}", hex::encode(&root));
+    }
+    Ok(())
+}
+
+impl Walker {
+    fn hash_tree(&self, dir: &Path) -> anyhow::Result<(Vec<u8>, Leaves)> {
+        if !dir.is_dir() {
+            anyhow::bail!("'{}' is not a directory", dir.display());
+        }
+        let mut leaves = BTreeMap::new();
+        let mut rules = Vec::new();
+        let root = self.hash_dir(dir, "", &mut rules, &mut leaves)?;
+        Ok((root, leaves))
+    }
+
+    fn hash_dir(
+        &self,
+        dir: &Path,
+        rel: &str,
+        rules: &mut Vec<Rules>,
+        leaves: &mut Leaves,
+    ) -> anyhow::Result<Vec<u8>> {
+        let pushed = match Rules::load(dir, rel).filter(|_| !self.no_ignore) {
+            Some(r) => {
+                rules.push(r);
+                true
+            }
+            None => false,
+        };
+
+        let mut entries = std::fs::read_dir(dir)
+            .with_context(|| format!("Reading directory '{}'", dir.display()))?
+            .map(|e| e.map(|e| (e.file_name().to_string_lossy().into_owned(), e.path())))
+            .collect::<Result<Vec<_>, _>>()
+            .with_context(|| format!("Reading directory '{}'", dir.display()))?;
+        entries.sort();
+
+        let mut listing = String::new();
+        for (name, path) in entries {
+            if name == ".git" {
+                continue;
+            }
+            let rel = if rel.is_empty() {
+                name.clone()
+            } else {
+                format!("{}/{}", rel, name)
+            };
+            let meta = std::fs::symlink_metadata(&path)
+                .with_context(|| format!("Reading '{}'", path.display()))?;
+            let kind = if meta.file_type().is_symlink() {
+                Kind::Symlink
+            } else if meta.is_dir() {
+                Kind::Dir
+            } else {
+                Kind::File
+            };
+
+            let all_rules = rules.iter().chain(std::iter::once(&self.excludes));
+            if ignore::is_ignored(all_rules, &rel, kind == Kind::Dir) {
+                continue;
+            }
+
+            let digest = match kind {
+                Kind::Dir => self.hash_dir(&path, &rel, rules, leaves)?,
+                Kind::Symlink => {
+                    let target = std::fs::read_link(&path)
+                        .with_context(|| format!("Reading link '{}'", path.display()))?;
+                    digest_bytes(self.algo, target.to_string_lossy().as_bytes())?
+                }
+                Kind::File => {
+                    let source = InputSource {
+                        input: Some(path.to_string_lossy().into_owned()),
+                        raw: false,
+                    };
+                    digest(self.algo, source, None, self.progress)?
+                }
+            };
+            let mode = if self.modes { mode(&meta) } else { None };
+
+            listing.push(kind.tag());
+            if let Some(mode) = mode {
+                listing.push_str(&format!(" {:o}", mode));
+            }
+            let (_, escaped) = sums::escape(&name);
+            listing.push_str(&format!(" {} {}\n", hex::encode(&digest), escaped));
+
+            if kind != Kind::Dir {
+                let symlink = kind == Kind::Symlink;
+                leaves.insert(
+                    rel,
+                    Leaf {
+                        digest,
+                        mode,
+                        symlink,
+                    },
+                );
+            }
+        }
+
+        if pushed {
+            rules.pop();
+        }
+        digest_bytes(self.algo, listing.as_bytes())
+    }
+}
+
+#[cfg(unix)]
+fn mode(meta: &std::fs::Metadata) -> Option<u32> {
+    use std::os::unix::fs::PermissionsExt;
+    Some(meta.permissions().mode() & 0o7777)
+}
+
+#[cfg(not(unix))]
+fn mode(meta: &std::fs::Metadata) -> Option<u32> {
+    Some(if meta.permissions().readonly() {
+        0o444
+    } else {
+        0o644
+    })
+}
+
+fn compare(walker: &Walker, root: &[u8], leaves: &Leaves, other: &str) -> anyhow::Result<()> {
+    let other_path = PathBuf::from(other);
+    let is_manifest = !other_path.is_dir();
+    let (other_root, other_leaves) = if is_manifest {
+        read_manifest(walker.algo, other)?
+    } else {
+        let (root, leaves) = walker.hash_tree(&other_path)?;
+        (Some(root), leaves)
+    };
+
+    let mut differences = 0;
+    // manifests do not list symlinks
+    for (path, leaf) in leaves.iter().filter(|(_, l)| !(is_manifest && l.symlink)) {
+        match other_leaves.get(path) {
+            None => {
+                differences += 1;
+                println!("- {}", path);
+            }
+            Some(o) if o.digest != leaf.digest => {
+                differences += 1;
+                println!("M {}", path);
+            }
+            Some(o) if o.mode.is_some() && leaf.mode.is_some() && o.mode != leaf.mode => {
+                differences += 1;
+                println!(
+                    "M {} (mode {:o} -> {:o})",
+                    path,
+                    leaf.mode.unwrap_or(0),
+                    o.mode.unwrap_or(0)
+                );
+            }
+            Some(_) => {}
+        }
+    }
+    for path in other_leaves.keys() {
+        if !leaves.contains_key(path) {
+            differences += 1;
+            println!("+ {}", path);
+        }
+    }
+
+    // directories only differ in the root digest, e.g. an added empty directory
+    if differences == 0 && other_root.is_none_or(|r| r == root) {
+        eprintln!("Trees are identical");
+        return Ok(());
+    }
+    if differences == 0 {
+        anyhow::bail!("Trees differ in their directories or symlinks");
+    }
+    anyhow::bail!("{} path(s) differ", differences)
+}
+
+/// Reads a manifest printed by `--manifest`, its root digest is optional.
+fn read_manifest(algo: Algo, file: &str) -> anyhow::Result<(Option<Vec<u8>>, Leaves)> {
+    let content =
+        std::fs::read_to_string(file).with_context(|| format!("Reading manifest '{}'", file))?;
+
+    let root_prefix = format!("# {} tree ", algo.name());
+    let mut root = None;
+    let mut leaves = BTreeMap::new();
+    for (n, line) in content.lines().enumerate() {
+        if let Some(digest) = line.strip_prefix(&root_prefix) {
+            root = hex::decode(digest.trim()).ok();
+            continue;
+        }
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (digest, path) = sums::parse_line(algo, line)
+            .with_context(|| format!("Invalid manifest line {}: '{}'", n + 1, line))?;
+        let digest = hex::decode(&digest)
+            .map_err(|_| anyhow::anyhow!("Invalid digest on manifest line {}", n + 1))?;
+        leaves.insert(
+            path,
+            Leaf {
+                digest,
+                mode: None,
+                symlink: false,
+            },
+        );
+    }
+    Ok((root, leaves))
+}
@@ -0,0 +1,407 @@
+//! xxHash64 and XXH3 (64 bit) with seed 0 and the default secret, following
+//! https://github.com/Cyan4973/xxHash/blob/dev/doc/xxhash_spec.md
+
+use super::Digest;
+
+const PRIME32_1: u64 = 0x9E37_79B1;
+const PRIME32_2: u64 = 0x85EB_CA77;
+const PRIME32_3: u64 = 0xC2B2_AE3D;
+const PRIME64_1: u64 = 0x9E37_79B1_85EB_CA87;
+const PRIME64_2: u64 = 0xC2B2_AE3D_27D4_EB4F;
+const PRIME64_3: u64 = 0x1656_67B1_9E37_79F9;
+const PRIME64_4: u64 = 0x85EB_CA77_C2B2_AE63;
+const PRIME64_5: u64 = 0x27D4_EB2F_1656_67C5;
+const PRIME_MX1: u64 = 0x1656_6791_9E37_79F9;
+const PRIME_MX2: u64 = 0x9FB2_1C65_1E98_DF25;
+
+fn read32(b: &[u8]) -> u64 {
+    u32::from_le_bytes(b[..4].try_into().unwrap()) as u64
+}
+
+fn read64(b: &[u8]) -> u64 {
+    u64::from_le_bytes(b[..8].try_into().unwrap())
+}
+
+fn avalanche64(mut h: u64) -> u64 {
+    h ^= h >> 33;
+    h = h.wrapping_mul(PRIME64_2);
+    h ^= h >> 29;
+    h = h.wrapping_mul(PRIME64_3);
+    h ^ (h >> 32)
+}
+
+pub struct Xxh64 {
+    acc: [u64; 4],
+    buffer: Vec<u8>,
+    total_len: u64,
+}
+
+impl Xxh64 {
+    const STRIPE_LEN: usize = 32;
+
+    pub fn new() -> Self {
+        Self {
+            acc: [
+                PRIME64_1.wrapping_add(PRIME64_2),
+                PRIME64_2,
+                0,
+                PRIME64_1.wrapping_neg(),
+            ],
+            buffer: Vec::with_capacity(Self::STRIPE_LEN),
+            total_len: 0,
+        }
+    }
+
+    fn round(acc: u64, input: u64) -> u64 {
+        acc.wrapping_add(input.wrapping_mul(PRIME64_2))
+            .rotate_left(31)
+            .wrapping_mul(PRIME64_1)
+    }
+
+    fn merge_round(acc: u64, val: u64) -> u64 {
+        (acc ^ Self::round(0, val))
+            .wrapping_mul(PRIME64_1)
+            .wrapping_add(PRIME64_4)
+    }
+
+    fn stripe(&mut self, stripe: &[u8]) {
+        for (i, acc) in self.acc.iter_mut().enumerate() {
+            *acc = Self::round(*acc, read64(&stripe[i * 8..]));
+        }
+    }
+}
+
+impl Digest for Xxh64 {
+    fn update(&mut self, mut data: &[u8]) {
+        self.total_len += data.len() as u64;
+
+        if !self.buffer.is_empty() {
+            let take = (Self::STRIPE_LEN - self.buffer.len()).min(data.len());
+            self.buffer.extend_from_slice(&data[..take]);
+            data = &data[take..];
+            if self.buffer.len() < Self::STRIPE_LEN {
+                return;
+            }
+            let stripe = std::mem::take(&mut self.buffer);
+            self.stripe(&stripe);
+        }
+
+        let mut stripes = data.chunks_exact(Self::STRIPE_LEN);
+        for stripe in &mut stripes {
+            self.stripe(stripe);
+        }
+        self.buffer.extend_from_slice(stripes.remainder());
+    }
+
+    fn finish(&self) -> Vec<u8> {
+        let mut h = if self.total_len >= Self::STRIPE_LEN as u64 {
+            let [v1, v2, v3, v4] = self.acc;
+            let mut h = v1
+                .rotate_left(1)
+                .wrapping_add(v2.rotate_left(7))
+                .wrapping_add(v3.rotate_left(12))
+                .wrapping_add(v4.rotate_left(18));
+            for v in self.acc {
+                h = Self::merge_round(h, v);
+            }
+            h
+        } else {
+            PRIME64_5
+        };
+        h = h.wrapping_add(self.total_len);
+
+        let mut rest = self.buffer.as_slice();
+        while rest.len() >= 8 {
+            h ^= Self::round(0, read64(rest));
+            h = h
+                .rotate_left(27)
+                .wrapping_mul(PRIME64_1)
+                .wrapping_add(PRIME64_4);
+            rest = &rest[8..];
+        }
+        if rest.len() >= 4 {
+            h ^= read32(rest).wrapping_mul(PRIME64_1);
+            h = h
+                .rotate_left(23)
+                .wrapping_mul(PRIME64_2)
+                .wrapping_add(PRIME64_3);
+            rest = &rest[4..];
+        }
+        for &b in rest {
+            h ^= (b as u64).wrapping_mul(PRIME64_5);
+            h = h.rotate_left(11).wrapping_mul(PRIME64_1);
+        }
+
+        avalanche64(h).to_be_bytes().to_vec()
+    }
+}
+
+const SECRET: [u8; 192] = [
+    0xb8, 0xfe, 0x6c, 0x39, 0x23, 0xa4, 0x4b, 0xbe, 0x7c, 0x01, 0x81, 0x2c, 0xf7, 0x21, 0xad, 0x1c,
+    0xde, 0xd4, 0x6d, 0xe9, 0x83, 0x90, 0x97, 0xdb, 0x72, 0x40, 0xa4, 0xa4, 0xb7, 0xb3, 0x67, 0x1f,
+    0xcb, 0x79, 0xe6, 0x4e, 0xcc, 0xc0, 0xe5, 0x78, 0x82, 0x5a, 0xd0, 0x7d, 0xcc, 0xff, 0x72, 0x21,
+    0xb8, 0x08, 0x46, 0x74, 0xf7, 0x43, 0x24, 0x8e, 0xe0, 0x35, 0x90, 0xe6, 0x81, 0x3a, 0x26, 0x4c,
+    0x3c, 0x28, 0x52, 0xbb, 0x91, 0xc3, 0x00, 0xcb, 0x88, 0xd0, 0x65, 0x8b, 0x1b, 0x53, 0x2e, 0xa3,
+    0x71, 0x64, 0x48, 0x97, 0xa2, 0x0d, 0xf9, 0x4e, 0x38, 0x19, 0xef, 0x46, 0xa9, 0xde, 0xac, 0xd8,
+    0xa8, 0xfa, 0x76, 0x3f, 0xe3, 0x9c, 0x34, 0x3f, 0xf9, 0xdc, 0xbb, 0xc7, 0xc7, 0x0b, 0x4f, 0x1d,
+    0x8a, 0x51, 0xe0, 0x4b, 0xcd, 0xb4, 0x59, 0x31, 0xc8, 0x9f, 0x7e, 0xc9, 0xd9, 0x78, 0x73, 0x64,
+    0xea, 0xc5, 0xac, 0x83, 0x34, 0xd3, 0xeb, 0xc3, 0xc5, 0x81, 0xa0, 0xff, 0xfa, 0x13, 0x63, 0xeb,
+    0x17, 0x0d, 0xdd, 0x51, 0xb7, 0xf0, 0xda, 0x49, 0xd3, 0x16, 0x55, 0x26, 0x29, 0xd4, 0x68, 0x9e,
+    0x2b, 0x16, 0xbe, 0x58, 0x7d, 0x47, 0xa1, 0xfc, 0x8f, 0xf8, 0xb8, 0xd1, 0x7a, 0xd0, 0x31, 0xce,
+    0x45, 0xcb, 0x3a, 0x8f, 0x95, 0x16, 0x04, 0x28, 0xaf, 0xd7, 0xfb, 0xca, 0xbb, 0x4b, 0x40, 0x7e,
+];
+
+const STRIPE_LEN: usize = 64;
+const SECRET_CONSUME_RATE: usize = 8;
+const STRIPES_PER_BLOCK: usize = (SECRET.len() - STRIPE_LEN) / SECRET_CONSUME_RATE;
+/// Longest input hashed without the stripe accumulators.
+const MIDSIZE_MAX: usize = 240;
+
+fn mul128_fold64(a: u64, b: u64) -> u64 {
+    let product = a as u128 * b as u128;
+    (product as u64) ^ ((product >> 64) as u64)
+}
+
+fn avalanche3(mut h: u64) -> u64 {
+    h ^= h >> 37;
+    h = h.wrapping_mul(PRIME_MX1);
+    h ^ (h >> 32)
+}
+
+fn rrmxmx(mut h: u64, len: u64) -> u64 {
+    h ^= h.rotate_left(49) ^ h.rotate_left(24);
+    h = h.wrapping_mul(PRIME_MX2);
+    h ^= (h >> 35).wrapping_add(len);
+    h = h.wrapping_mul(PRIME_MX2);
+    h ^ (h >> 28)
+}
+
+fn mix16(input: &[u8], secret: &[u8]) -> u64 {
+    mul128_fold64(
+        read64(input) ^ read64(secret),
+        read64(&input[8..]) ^ read64(&secret[8..]),
+    )
+}
+
+/// XXH3 of inputs up to [`MIDSIZE_MAX`] bytes.
+fn xxh3_short(input: &[u8]) -> u64 {
+    let len = input.len();
+    let len64 = len as u64;
+    match len {
+        0 => avalanche64(read64(&SECRET[56..]) ^ read64(&SECRET[64..])),
+        1..=3 => {
+            let combined = ((input[0] as u64) << 16)
+                | ((input[len >> 1] as u64) << 24)
+                | (input[len - 1] as u64)
+                | (len64 << 8);
+            avalanche64(combined ^ (read32(&SECRET) ^ read32(&SECRET[4..])))
+        }
+        4..=8 => {
+            let input64 = read32(&input[len - 4..]).wrapping_add(read32(input) << 32);
+            rrmxmx(
+                input64 ^ (read64(&SECRET[8..]) ^ read64(&SECRET[16..])),
+                len64,
+            )
+        }
+        9..=16 => {
+            let lo = read64(input) ^ (read64(&SECRET[24..]) ^ read64(&SECRET[32..]));
+            let hi = read64(&input[len - 8..]) ^ (read64(&SECRET[40..]) ^ read64(&SECRET[48..]));
+            avalanche3(
+                len64
+                    .wrapping_add(lo.swap_bytes())
+                    .wrapping_add(hi)
+                    .wrapping_add(mul128_fold64(lo, hi)),
+            )
+        }
+        17..=128 => {
+            let mut acc = len64.wrapping_mul(PRIME64_1);
+            let pairs = (len - 1) / 32;
+            for i in (0..=pairs).rev() {
+                acc = acc.wrapping_add(mix16(&input[16 * i..], &SECRET[32 * i..]));
+                acc = acc.wrapping_add(mix16(&input[len - 16 * (i + 1)..], &SECRET[32 * i + 16..]));
+            }
+            avalanche3(acc)
+        }
+        _ => {
+            const START_OFFSET: usize = 3;
+            const LAST_OFFSET: usize = 17;
+            const SECRET_SIZE_MIN: usize = 136;
+
+            let mut acc = len64.wrapping_mul(PRIME64_1);
+            for i in 0..8 {
+                acc = acc.wrapping_add(mix16(&input[16 * i..], &SECRET[16 * i..]));
+            }
+            acc = avalanche3(acc);
+            for i in 8..len / 16 {
+                acc = acc.wrapping_add(mix16(
+                    &input[16 * i..],
+                    &SECRET[16 * (i - 8) + START_OFFSET..],
+                ));
+            }
+            acc = acc.wrapping_add(mix16(
+                &input[len - 16..],
+                &SECRET[SECRET_SIZE_MIN - LAST_OFFSET..],
+            ));
+            avalanche3(acc)
+        }
+    }
+}
+
+pub struct Xxh3 {
+    acc: [u64; 8],
+    /// Unprocessed input, preceded by up to one already processed stripe
+    /// which is needed when the input ends with a partial stripe.
+    buffer: Vec<u8>,
+    /// Where the unprocessed input starts in `buffer`.
+    pos: usize,
+    stripes_in_block: usize,
+    total_len: u64,
+}
+
+impl Xxh3 {
+    pub fn new() -> Self {
+        Self {
+            acc: [
+                PRIME32_3, PRIME64_1, PRIME64_2, PRIME64_3, PRIME64_4, PRIME32_2, PRIME64_5,
+                PRIME32_1,
+            ],
+            buffer: Vec::new(),
+            pos: 0,
+            stripes_in_block: 0,
+            total_len: 0,
+        }
+    }
+
+    fn accumulate(acc: &mut [u64; 8], stripe: &[u8], secret: &[u8]) {
+        for i in 0..8 {
+            let data = read64(&stripe[8 * i..]);
+            let key = data ^ read64(&secret[8 * i..]);
+            acc[i ^ 1] = acc[i ^ 1].wrapping_add(data);
+            acc[i] = acc[i].wrapping_add((key & 0xFFFF_FFFF).wrapping_mul(key >> 32));
+        }
+    }
+
+    fn scramble(acc: &mut [u64; 8]) {
+        let secret = &SECRET[SECRET.len() - STRIPE_LEN..];
+        for (i, acc) in acc.iter_mut().enumerate() {
+            let mut a = *acc;
+            a ^= a >> 47;
+            a ^= read64(&secret[8 * i..]);
+            *acc = a.wrapping_mul(PRIME32_1);
+        }
+    }
+}
+
+impl Digest for Xxh3 {
+    fn update(&mut self, data: &[u8]) {
+        self.total_len += data.len() as u64;
+        self.buffer.extend_from_slice(data);
+        if self.total_len <= MIDSIZE_MAX as u64 {
+            return;
+        }
+
+        // a stripe is only processed once more input follows it, the last
+        // stripe of the input is treated differently
+        while self.buffer.len() - self.pos > STRIPE_LEN {
+            let stripe = &self.buffer[self.pos..self.pos + STRIPE_LEN];
+            let secret = &SECRET[self.stripes_in_block * SECRET_CONSUME_RATE..];
+            Self::accumulate(&mut self.acc, stripe, secret);
+            self.pos += STRIPE_LEN;
+            self.stripes_in_block += 1;
+            if self.stripes_in_block == STRIPES_PER_BLOCK {
+                Self::scramble(&mut self.acc);
+                self.stripes_in_block = 0;
+            }
+        }
+
+        if self.pos > STRIPE_LEN {
+            self.buffer.drain(..self.pos - STRIPE_LEN);
+            self.pos = STRIPE_LEN;
+        }
+    }
+
+    fn finish(&self) -> Vec<u8> {
+        const LAST_ACC_START: usize = 7;
+        const MERGE_ACCS_START: usize = 11;
+
+        if self.total_len <= MIDSIZE_MAX as u64 {
+            return xxh3_short(&self.buffer).to_be_bytes().to_vec();
+        }
+
+        let mut acc = self.acc;
+        let last_stripe = &self.buffer[self.buffer.len() - STRIPE_LEN..];
+        let secret = &SECRET[SECRET.len() - STRIPE_LEN - LAST_ACC_START..];
+        Self::accumulate(&mut acc, last_stripe, secret);
+
+        let mut h = self.total_len.wrapping_mul(PRIME64_1);
+        for i in 0..4 {
+            let secret = &SECRET[MERGE_ACCS_START + 16 * i..];
+            h = h.wrapping_add(mul128_fold64(
+                acc[2 * i] ^ read64(secret),
+                acc[2 * i + 1] ^ read64(&secret[8..]),
+            ));
+        }
+
+        avalanche3(h).to_be_bytes().to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::hex_digest;
+    use super::{Xxh3, Xxh64};
+
+    fn sample(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    #[test]
+    fn xxh64() {
+        assert_eq!(hex_digest(Xxh64::new, b""), "ef46db3751d8e999");
+        assert_eq!(hex_digest(Xxh64::new, b"abc"), "44bc2cf5ad770999");
+        let cases = [
+            (17, "5603e60c527599b6"),
+            (32, "cbf59c5116ff32b4"),
+            (1025, "cfd73aedd2d6a39d"),
+        ];
+        for (len, expected) in cases {
+            assert_eq!(
+                hex_digest(Xxh64::new, &sample(len)),
+                expected,
+                "{} bytes",
+                len
+            );
+        }
+    }
+
+    /// Every length branch of XXH3, from the reference implementation.
+    #[test]
+    fn xxh3() {
+        assert_eq!(hex_digest(Xxh3::new, b"abc"), "78af5f94892f3950");
+        let cases = [
+            (0, "2d06800538d394c2"),
+            (1, "c44bdff4074eecdb"),
+            (3, "5f4299fc161c9cbb"),
+            (4, "60dab036a58211f2"),
+            (8, "3a1c2d7c85af88f8"),
+            (9, "e9612598145bb9dc"),
+            (16, "8355e3a6f61770db"),
+            (17, "9ef341a99de37328"),
+            (128, "85c6174c7ff4c46b"),
+            (129, "ec7642b431ba3e5a"),
+            (240, "375a384d957fe865"),
+            (241, "02e8cd95421c6d02"),
+            (1024, "e5d78bafa45b2aa5"),
+            (1025, "e95c42288f28186e"),
+            (2500, "76ebae7c9d5cdc5c"),
+        ];
+        for (len, expected) in cases {
+            assert_eq!(
+                hex_digest(Xxh3::new, &sample(len)),
+                expected,
+                "{} bytes",
+                len
+            );
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use std::fs::File;
+use std::io::{Cursor, Read, Stdin};
+
+use anyhow::Context;
+
+#[derive(clap::Args, Debug)]
+pub struct InputSource {
+    /// Filename to read from or raw input (must specify --raw)
+    pub input: Option<String>,
+
+    /// must be provided if raw input is provided
+    #[clap(long)]
+    pub raw: bool,
+}
+
+/// A byte stream over whichever source the input comes from.
+pub enum InputReader {
+    Raw(Cursor<Vec<u8>>),
+    File(File),
+    Stdin(Stdin),
+}
+
+impl InputReader {
+    /// Total number of bytes in the input, when known upfront.
+    pub fn len(&self) -> Option<u64> {
+        match self {
+            InputReader::Raw(c) => Some(c.get_ref().len() as u64),
+            InputReader::File(f) => f.metadata().ok().map(|m| m.len()),
+            InputReader::Stdin(_) => None,
+        }
+    }
+}
+
+impl Read for InputReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            InputReader::Raw(c) => c.read(buf),
+            InputReader::File(f) => f.read(buf),
+            InputReader::Stdin(s) => s.read(buf),
+        }
+    }
+}
+
+impl InputSource {
+    pub fn open(self) -> anyhow::Result<InputReader> {
+        if let Some(input) = self.input {
+            if self.raw {
+                return Ok(InputReader::Raw(Cursor::new(input.into_bytes())));
+            }
+            let file = File::open(&input).context(format!(
+                "Reading from file '{}', if this is raw input then specify --raw flag",
+                input
+            ))?;
+            return Ok(InputReader::File(file));
+        }
+
+        if !atty::is(atty::Stream::Stdin) {
+            return Ok(InputReader::Stdin(std::io::stdin()));
+        }
+
+        Err(anyhow::anyhow!(
+            "Not input source found. You can either pipe the input or specify a file or plaintext"
+        ))
+    }
+}
+
+/// Reads the exact bytes of the input, without any newline or encoding conversion.
+pub fn for_input(is: InputSource, f: impl Fn(Vec<u8>) -> anyhow::Result<()>) -> anyhow::Result<()> {
+    f(read_all(is)?)
+}
+
+pub fn read_all(is: InputSource) -> anyhow::Result<Vec<u8>> {
+    let mut reader = is.open()?;
+    let mut bytes = Vec::new();
+    reader
+        .read_to_end(&mut bytes)
+        .context("Could not read the input")?;
+
+    Ok(bytes)
+}
+
+/// Same as [`for_input`] for tools that only work on text, the input must be valid UTF-8.
+pub fn for_text_input(
+    is: InputSource,
+    f: impl Fn(String) -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
+    for_input(is, |bytes| f(decode_utf8(bytes)?))
+}
+
+pub fn decode_utf8(bytes: Vec<u8>) -> anyhow::Result<String> {
+    String::from_utf8(bytes).map_err(|e| {
+        let offset = e.utf8_error().valid_up_to();
+        anyhow::anyhow!(
+            "Input is not valid UTF-8: invalid byte 0x{:02x} at offset {}",
+            e.as_bytes()[offset],
+            offset
+        )
+    })
+}
@@ -0,0 +1,267 @@
+//! `json canonical`, the JSON Canonicalization Scheme of RFC 8785 (JCS).
+
+use anyhow::Context;
+use serde_json::{Map, Number, Value};
+
+use super::parse;
+use crate::hash::{self, Algo};
+use crate::input::{for_text_input, InputSource};
+
+#[derive(clap::Args, Debug)]
+pub struct CanonicalArgs {
+    /// Print the digest of the canonical form rather than the form itself
+    #[clap(long, arg_enum, value_name = "ALGO")]
+    hash: Option<Algo>,
+
+    #[clap(flatten)]
+    input: InputSource,
+}
+
+pub fn run(args: CanonicalArgs) -> anyhow::Result<()> {
+    let algo = args.hash;
+    for_text_input(args.input, |input| {
+        let json: Value = parse(&input).context("Parse Valid JSON")?;
+        let canonical = canonical(&json)?;
+        match algo {
+            Some(algo) => println!(
+                "{}",
+                hex::encode(hash::digest_bytes(algo, canonical.as_bytes())?)
+            ),
+            None => println!("{}", canonical),
+        }
+        Ok(())
+    })
+}
+
+/// The value without whitespace, its keys sorted by UTF-16 code units and its numbers
+/// written the way JavaScript does.
+pub fn canonical(value: &Value) -> anyhow::Result<String> {
+    let mut out = String::new();
+    write(value, &mut out)?;
+    Ok(out)
+}
+
+fn write(value: &Value, out: &mut String) -> anyhow::Result<()> {
+    match value {
+        Value::Number(n) => out.push_str(&number(n)?),
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write(item, out)?;
+            }
+            out.push(']');
+        }
+        Value::Object(map) => {
+            let mut members: Vec<(&String, &Value)> = map.iter().collect();
+            members.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+            out.push('{');
+            for (i, (key, value)) in members.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                // serde_json escapes strings the same way JCS does
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                write(value, out)?;
+            }
+            out.push('}');
+        }
+        value => out.push_str(&value.to_string()),
+    }
+    Ok(())
+}
+
+/// ECMAScript `Number.prototype.toString`, all numbers are IEEE 754 doubles to JCS.
+fn number(n: &Number) -> anyhow::Result<String> {
+    let f = n
+        .as_f64()
+        .filter(|f| f.is_finite())
+        .with_context(|| format!("{} is out of the range of IEEE 754 doubles", n))?;
+    if f == 0.0 {
+        return Ok("0".to_string());
+    }
+    // the shortest digits that round-trip, and the exponent of the first one
+    let scientific = format!("{:e}", f.abs());
+    let (mantissa, exponent) = scientific.split_once('e').unwrap_or((&scientific, "0"));
+    let n = exponent.parse::<i32>().unwrap_or_default() + 1;
+    let digits = mantissa.replace('.', "");
+    let digits = halfway_even(f.abs(), &digits, n).unwrap_or(digits);
+    let k = digits.len() as i32;
+
+    let body = if k <= n && n <= 21 {
+        format!("{}{}", digits, "0".repeat((n - k) as usize))
+    } else if 0 < n && n <= 21 {
+        format!("{}.{}", &digits[..n as usize], &digits[n as usize..])
+    } else if -6 < n && n <= 0 {
+        format!("0.{}{}", "0".repeat(-n as usize), digits)
+    } else {
+        let mantissa = match digits.len() {
+            1 => digits.clone(),
+            _ => format!("{}.{}", &digits[..1], &digits[1..]),
+        };
+        let sign = if n > 0 { '+' } else { '-' };
+        format!("{}e{}{}", mantissa, sign, (n - 1).abs())
+    };
+    match f < 0.0 {
+        true => Ok(format!("-{}", body)),
+        false => Ok(body),
+    }
+}
+
+/// The even candidate when the shortest digits are halfway between two, which ECMAScript
+/// takes where Rust rounds half up, 1424953923781206.25 is 1424953923781206.2.
+fn halfway_even(f: f64, digits: &str, n: i32) -> Option<String> {
+    // the exact value, a double has fewer than 800 significant digits
+    let exact = format!("{:.800e}", f);
+    let (mantissa, exponent) = exact.split_once('e')?;
+    let exact_digits = mantissa.replace('.', "");
+    if exponent.parse::<i32>().ok()? + 1 != n {
+        return None;
+    }
+    let (below, rest) = exact_digits.split_at(digits.len());
+    if !(rest.starts_with('5') && rest[1..].bytes().all(|b| b == b'0')) {
+        return None;
+    }
+
+    // the candidates are the digits below the exact value and the ones above, without a carry
+    let mut above = below.as_bytes().to_vec();
+    *above.last_mut()? += 1;
+    let above = String::from_utf8(above).ok().filter(|d| !d.contains(':'))?;
+    let even = [below.to_string(), above]
+        .into_iter()
+        .find(|d| d.bytes().last().is_some_and(|b| (b - b'0') % 2 == 0))?;
+    let round_trips = format!("0.{}e{}", even, n).parse::<f64>().ok() == Some(f);
+    round_trips.then_some(even)
+}
+
+/// The value with the keys of every object in order, for `--sort-keys`.
+pub fn sort_keys(value: &Value) -> Value {
+    match value {
+        Value::Array(items) => Value::Array(items.iter().map(sort_keys).collect()),
+        Value::Object(map) => {
+            let mut members: Vec<(&String, &Value)> = map.iter().collect();
+            members.sort_by_key(|(key, _)| *key);
+            let sorted: Map<String, Value> = members
+                .into_iter()
+                .map(|(key, value)| (key.clone(), sort_keys(value)))
+                .collect();
+            Value::Object(sorted)
+        }
+        value => value.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Number, Value};
+
+    use super::{canonical, number, sort_keys};
+    use crate::json::parse;
+
+    #[test]
+    fn numbers() {
+        // appendix B of RFC 8785, IEEE 754 bits and their canonical form
+        let cases: &[(u64, &str)] = &[
+            (0x0000000000000000, "0"),
+            (0x8000000000000000, "0"),
+            (0x0000000000000001, "5e-324"),
+            (0x8000000000000001, "-5e-324"),
+            (0x7fefffffffffffff, "1.7976931348623157e+308"),
+            (0xffefffffffffffff, "-1.7976931348623157e+308"),
+            (0x4340000000000000, "9007199254740992"),
+            (0xc340000000000000, "-9007199254740992"),
+            (0x4430000000000000, "295147905179352830000"),
+            (0x44b52d02c7e14af5, "9.999999999999997e+22"),
+            (0x44b52d02c7e14af6, "1e+23"),
+            (0x44b52d02c7e14af7, "1.0000000000000001e+23"),
+            (0x444b1ae4d6e2ef4e, "999999999999999700000"),
+            (0x444b1ae4d6e2ef4f, "999999999999999900000"),
+            (0x444b1ae4d6e2ef50, "1e+21"),
+            (0x3eb0c6f7a0b5ed8c, "9.999999999999997e-7"),
+            (0x3eb0c6f7a0b5ed8d, "0.000001"),
+            (0x41b3de4355555553, "333333333.3333332"),
+            (0x41b3de4355555554, "333333333.33333325"),
+            (0x41b3de4355555555, "333333333.3333333"),
+            (0x41b3de4355555556, "333333333.3333334"),
+            (0x41b3de4355555557, "333333333.33333343"),
+            (0xbecbf647612f3696, "-0.0000033333333333333333"),
+            (0x43143ff3c1cb0959, "1424953923781206.2"),
+        ];
+        for &(bits, expected) in cases {
+            let n = Number::from_f64(f64::from_bits(bits)).unwrap();
+            assert_eq!(number(&n).unwrap(), expected, "{:016x}", bits);
+        }
+
+        // the way the numbers are written does not matter, only their value
+        let parsed = parse("[1.0, 1e2, 100E-2, -0.0, 0.1e1, 4.50, 1E30, 2e-3]").unwrap();
+        assert_eq!(canonical(&parsed).unwrap(), "[1,100,1,0,1,4.5,1e+30,0.002]");
+        assert!(canonical(&parse("1e400").unwrap()).is_err());
+    }
+
+    #[test]
+    fn rfc_example() {
+        // section 3.2.2 of the RFC
+        let input = r#"{
+            "numbers": [333333333.33333329, 1E30, 4.50, 2e-3, 0.000000000000000000000000001],
+            "string": "\u20ac$\u000F\u000aA'\u0042\u0022\u005c\\\"\/",
+            "literals": [null, true, false]
+        }"#;
+        assert_eq!(
+            canonical(&parse(input).unwrap()).unwrap(),
+            r#"{"literals":[null,true,false],"numbers":[333333333.3333333,1e+30,4.5,0.002,1e-27],"string":"€$\u000f\nA'B\"\\\\\"/"}"#
+        );
+    }
+
+    #[test]
+    fn key_order() {
+        // section 3.2.3 of the RFC, keys are sorted by their UTF-16 code units
+        let input = r#"{
+            "€": "Euro Sign",
+            "\r": "Carriage Return",
+            "דּ": "Hebrew Letter Dalet With Dagesh",
+            "1": "One",
+            "😀": "Emoji: Grinning Face",
+            "\u0080": "Control",
+            "ö": "Latin Small Letter O With Diaeresis"
+        }"#;
+        let value = parse(input).unwrap();
+        let canonical = canonical(&value).unwrap();
+        let values: Vec<Value> = serde_json::from_str::<serde_json::Map<String, Value>>(&canonical)
+            .unwrap()
+            .into_iter()
+            .map(|(_, value)| value)
+            .collect();
+        assert_eq!(
+            values,
+            [
+                "Carriage Return",
+                "One",
+                "Control",
+                "Latin Small Letter O With Diaeresis",
+                "Euro Sign",
+                "Emoji: Grinning Face",
+                "Hebrew Letter Dalet With Dagesh",
+            ]
+        );
+        assert!(canonical.starts_with(r#"{"\r":"Carriage Return","1":"One","#));
+
+        // nested objects too, arrays keep their order
+        let value = json!({"b": [{"z": 1, "y": 2}, 0], "a": {"d": null, "c": true}});
+        assert_eq!(
+            super::canonical(&value).unwrap(),
+            r#"{"a":{"c":true,"d":null},"b":[{"y":2,"z":1},0]}"#
+        );
+    }
+
+    #[test]
+    fn sorted_keys() {
+        let value = json!({"b": [{"z": 1, "y": 2}], "a": {"d": null, "c": true}, "é": 1, "Z": 0});
+        assert_eq!(
+            sort_keys(&value).to_string(),
+            r#"{"Z":0,"a":{"c":true,"d":null},"b":[{"y":2,"z":1}],"é":1}"#
+        );
+    }
+}
@@ -0,0 +1,351 @@
+//! `json diff`, comparing two documents by structure rather than by text.
+
+use anyhow::Context;
+use serde_json::{json, Value};
+
+use super::merge;
+use super::pointer::{self, Step};
+use super::{read_file, values_equal};
+use crate::exit::ExitStatus;
+
+/// Past this many item comparisons, arrays are compared index by index.
+const MAX_ALIGNMENT: usize = 4_000_000;
+
+#[derive(clap::Args, Debug)]
+pub struct DiffArgs {
+    /// The original document
+    a: String,
+
+    /// The changed document
+    b: String,
+
+    /// Compare arrays as multisets, so that reordered items are equal
+    #[clap(long, short)]
+    ignore_array_order: bool,
+
+    #[clap(long, arg_enum, default_value = "tree")]
+    format: DiffFormat,
+}
+
+#[derive(clap::ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum DiffFormat {
+    /// Both documents merged into one tree, with the changes marked
+    Tree,
+    /// The JSON Pointer of every change
+    Paths,
+    /// An RFC 6902 JSON Patch from the original to the changed document
+    Patch,
+    /// An RFC 7396 JSON Merge Patch from the original to the changed document
+    MergePatch,
+}
+
+pub enum Change<'a> {
+    Add(&'a Value),
+    Remove,
+    Replace(&'a Value),
+}
+
+/// A change and where it is; `append` adds the value at the end of the array.
+pub struct Located<'a> {
+    pub path: Vec<Step>,
+    pub append: bool,
+    pub change: Change<'a>,
+}
+
+/// How the items of two arrays correspond.
+#[derive(Clone, Copy)]
+enum Edit {
+    Same(usize),
+    /// Different items at the same place, compared further.
+    Pair(usize, usize),
+    Remove(usize),
+    Add(usize),
+}
+
+/// The changes from `a` to `b`, in an order that can be applied one after the other:
+/// arrays have their items changed, then removed from the end, then added.
+pub fn changes<'a>(a: &'a Value, b: &'a Value, ignore_array_order: bool) -> Vec<Located<'a>> {
+    let mut changes = Vec::new();
+    let mut path = Vec::new();
+    compare(a, b, ignore_array_order, &mut path, &mut changes);
+    changes
+}
+
+fn compare<'a>(
+    a: &'a Value,
+    b: &'a Value,
+    unordered: bool,
+    path: &mut Vec<Step>,
+    changes: &mut Vec<Located<'a>>,
+) {
+    match (a, b) {
+        _ if values_equal(a, b) => {}
+        (Value::Object(a_map), Value::Object(b_map)) => {
+            for (key, a_value) in a_map {
+                path.push(Step::Key(key.clone()));
+                match b_map.get(key) {
+                    Some(b_value) => compare(a_value, b_value, unordered, path, changes),
+                    None => record(changes, path, false, Change::Remove),
+                }
+                path.pop();
+            }
+            for (key, b_value) in b_map.iter().filter(|(key, _)| !a_map.contains_key(*key)) {
+                path.push(Step::Key(key.clone()));
+                record(changes, path, false, Change::Add(b_value));
+                path.pop();
+            }
+        }
+        (Value::Array(a_items), Value::Array(b_items)) => {
+            let edits = align(a_items, b_items, unordered);
+            for edit in &edits {
+                if let Edit::Pair(i, j) = *edit {
+                    path.push(Step::Index(i));
+                    compare(&a_items[i], &b_items[j], unordered, path, changes);
+                    path.pop();
+                }
+            }
+            for edit in edits.iter().rev() {
+                if let Edit::Remove(i) = *edit {
+                    path.push(Step::Index(i));
+                    record(changes, path, false, Change::Remove);
+                    path.pop();
+                }
+            }
+            for edit in &edits {
+                if let Edit::Add(j) = *edit {
+                    path.push(Step::Index(j));
+                    record(changes, path, unordered, Change::Add(&b_items[j]));
+                    path.pop();
+                }
+            }
+        }
+        _ => record(changes, path, false, Change::Replace(b)),
+    }
+}
+
+fn record<'a>(changes: &mut Vec<Located<'a>>, path: &[Step], append: bool, change: Change<'a>) {
+    changes.push(Located {
+        path: path.to_vec(),
+        append,
+        change,
+    });
+}
+
+/// Matches the items of two arrays, the ones left over in between matches are paired up.
+fn align(a: &[Value], b: &[Value], unordered: bool) -> Vec<Edit> {
+    let mut edits = Vec::new();
+    if unordered {
+        let mut used = vec![false; a.len()];
+        let mut added = Vec::new();
+        for (j, item) in b.iter().enumerate() {
+            let found = (0..a.len()).find(|&i| !used[i] && values_equal(&a[i], item));
+            match found {
+                Some(i) => {
+                    used[i] = true;
+                    edits.push(Edit::Same(i));
+                }
+                None => added.push(j),
+            }
+        }
+        let removed: Vec<usize> = (0..a.len()).filter(|&i| !used[i]).collect();
+        pair_up(&removed, &added, &mut edits);
+        return edits;
+    }
+
+    if a.len().saturating_mul(b.len()) > MAX_ALIGNMENT {
+        let pairs = a.len().min(b.len());
+        let removed: Vec<usize> = (pairs..a.len()).collect();
+        let added: Vec<usize> = (pairs..b.len()).collect();
+        for i in 0..pairs {
+            match values_equal(&a[i], &b[i]) {
+                true => edits.push(Edit::Same(i)),
+                false => edits.push(Edit::Pair(i, i)),
+            }
+        }
+        pair_up(&removed, &added, &mut edits);
+        return edits;
+    }
+
+    // longest common subsequence, `lengths[i][j]` is the one of `a[i..]` and `b[j..]`
+    let mut lengths = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lengths[i][j] = match values_equal(&a[i], &b[j]) {
+                true => lengths[i + 1][j + 1] + 1,
+                false => lengths[i + 1][j].max(lengths[i][j + 1]),
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    let (mut removed, mut added) = (Vec::new(), Vec::new());
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && values_equal(&a[i], &b[j]) {
+            pair_up(&removed, &added, &mut edits);
+            removed.clear();
+            added.clear();
+            edits.push(Edit::Same(i));
+            i += 1;
+            j += 1;
+        } else if j == b.len() || (i < a.len() && lengths[i + 1][j] >= lengths[i][j + 1]) {
+            removed.push(i);
+            i += 1;
+        } else {
+            added.push(j);
+            j += 1;
+        }
+    }
+    pair_up(&removed, &added, &mut edits);
+    edits
+}
+
+fn pair_up(removed: &[usize], added: &[usize], edits: &mut Vec<Edit>) {
+    let pairs = removed.len().min(added.len());
+    edits.extend((0..pairs).map(|k| Edit::Pair(removed[k], added[k])));
+    edits.extend(removed[pairs..].iter().map(|&i| Edit::Remove(i)));
+    edits.extend(added[pairs..].iter().map(|&j| Edit::Add(j)));
+}
+
+/// The RFC 6902 JSON Patch of the changes.
+pub fn patch(changes: &[Located]) -> Value {
+    let operations = changes
+        .iter()
+        .map(|located| {
+            let mut path = pointer::format(&located.path);
+            if located.append {
+                path = format!("{}/-", &path[..path.rfind('/').unwrap_or_default()]);
+            }
+            match located.change {
+                Change::Add(value) => json!({"op": "add", "path": path, "value": value}),
+                Change::Remove => json!({"op": "remove", "path": path}),
+                Change::Replace(value) => json!({"op": "replace", "path": path, "value": value}),
+            }
+        })
+        .collect();
+    Value::Array(operations)
+}
+
+/// Prints both documents as one tree, with the removed lines marked `-` and the added `+`.
+struct Tree {
+    colour: bool,
+    unordered: bool,
+    lines: Vec<String>,
+}
+
+impl Tree {
+    fn line(&mut self, marker: char, depth: usize, text: &str) {
+        let colour = match (self.colour, marker) {
+            (true, '-') => "\x1b[31m",
+            (true, '+') => "\x1b[32m",
+            _ => "",
+        };
+        let reset = if colour.is_empty() { "" } else { "\x1b[0m" };
+        let indent = "  ".repeat(depth);
+        self.lines
+            .push(format!("{}{} {}{}{}", colour, marker, indent, text, reset));
+    }
+
+    /// A whole value, on as many lines as it takes when pretty printed.
+    fn value(&mut self, marker: char, depth: usize, label: &str, value: &Value) {
+        let text = serde_json::to_string_pretty(value).unwrap_or_default();
+        for (n, line) in text.lines().enumerate() {
+            match n {
+                0 => self.line(marker, depth, &format!("{}{}", label, line)),
+                _ => self.line(marker, depth, line),
+            }
+        }
+    }
+
+    fn compare(&mut self, depth: usize, label: &str, a: &Value, b: &Value) {
+        match (a, b) {
+            _ if values_equal(a, b) => {
+                let text = match a {
+                    Value::Object(map) if !map.is_empty() => {
+                        format!("{{…{} unchanged}}", map.len())
+                    }
+                    Value::Array(items) if !items.is_empty() => {
+                        format!("[…{} unchanged]", items.len())
+                    }
+                    value => value.to_string(),
+                };
+                self.line(' ', depth, &format!("{}{}", label, text));
+            }
+            (Value::Object(a_map), Value::Object(b_map)) => {
+                self.line(' ', depth, &format!("{}{{", label));
+                for (key, a_value) in a_map {
+                    let label = format!("{}: ", Value::String(key.clone()));
+                    match b_map.get(key) {
+                        Some(b_value) => self.compare(depth + 1, &label, a_value, b_value),
+                        None => self.value('-', depth + 1, &label, a_value),
+                    }
+                }
+                for (key, b_value) in b_map.iter().filter(|(key, _)| !a_map.contains_key(*key)) {
+                    let label = format!("{}: ", Value::String(key.clone()));
+                    self.value('+', depth + 1, &label, b_value);
+                }
+                self.line(' ', depth, "}");
+            }
+            (Value::Array(a_items), Value::Array(b_items)) => {
+                self.line(' ', depth, &format!("{}[", label));
+                for edit in align(a_items, b_items, self.unordered) {
+                    match edit {
+                        Edit::Same(i) => self.compare(depth + 1, "", &a_items[i], &a_items[i]),
+                        Edit::Pair(i, j) => self.compare(depth + 1, "", &a_items[i], &b_items[j]),
+                        Edit::Remove(i) => self.value('-', depth + 1, "", &a_items[i]),
+                        Edit::Add(j) => self.value('+', depth + 1, "", &b_items[j]),
+                    }
+                }
+                self.line(' ', depth, "]");
+            }
+            _ => {
+                self.value('-', depth, label, a);
+                self.value('+', depth, label, b);
+            }
+        }
+    }
+}
+
+pub fn run(args: DiffArgs) -> anyhow::Result<()> {
+    let a = read_file(&args.a)?;
+    let b = read_file(&args.b)?;
+    let changes = changes(&a, &b, args.ignore_array_order);
+
+    match args.format {
+        DiffFormat::Patch => println!("{}", serde_json::to_string_pretty(&patch(&changes))?),
+        DiffFormat::MergePatch => {
+            let patch = merge::generate(&a, &b);
+            let mut merged = a.clone();
+            merge::merge_patch(&mut merged, &patch);
+            if !values_equal(&merged, &b) {
+                eprintln!("Warning: the merge patch can not set members to null, it removes them");
+            }
+            println!("{}", serde_json::to_string_pretty(&patch)?);
+        }
+        DiffFormat::Paths => {
+            for located in &changes {
+                let marker = match located.change {
+                    Change::Add(_) => '+',
+                    Change::Remove => '-',
+                    Change::Replace(_) => '~',
+                };
+                println!("{} {}", marker, pointer::format(&located.path));
+            }
+        }
+        DiffFormat::Tree if !changes.is_empty() => {
+            let mut tree = Tree {
+                colour: atty::is(atty::Stream::Stdout),
+                unordered: args.ignore_array_order,
+                lines: Vec::new(),
+            };
+            tree.compare(0, "", &a, &b);
+            println!("{}", tree.lines.join("\n"));
+        }
+        DiffFormat::Tree => {}
+    }
+
+    if changes.is_empty() {
+        return Ok(());
+    }
+    let plural = if changes.len() == 1 { "" } else { "s" };
+    Err(anyhow::anyhow!("{} change{}", changes.len(), plural))
+        .context(ExitStatus(1, "The documents differ"))
+}
@@ -0,0 +1,252 @@
+//! `json infer`, generating a JSON Schema from sample documents.
+
+use std::collections::{BTreeSet, HashMap};
+
+use anyhow::Context;
+use serde_json::{json, Map, Value};
+
+use super::parse;
+use super::schema::format;
+use crate::input::{decode_utf8, read_all, InputSource};
+
+/// The formats looked for in strings, in the order they are tried.
+const FORMATS: &[&str] = &["date-time", "date", "uuid", "email", "uri"];
+
+#[derive(clap::Args, Debug)]
+pub struct InferArgs {
+    /// Sample files, each holding a JSON document or NDJSON; read from stdin when there
+    /// are none
+    files: Vec<String>,
+
+    /// The most distinct values a string can have to be inferred as an `enum`, 0 to
+    /// never infer one
+    #[clap(long, default_value = "8")]
+    max_enum: usize,
+}
+
+/// What was seen at one location of the samples.
+#[derive(Default)]
+struct Shape {
+    null: bool,
+    boolean: bool,
+    integer: bool,
+    number: bool,
+    strings: Option<Strings>,
+    /// Every item of every array, merged.
+    items: Option<Box<Shape>>,
+    object: Option<Object>,
+}
+
+struct Strings {
+    count: usize,
+    /// Capped just above the limit for an `enum`.
+    values: BTreeSet<String>,
+    /// The formats every string so far has.
+    formats: Vec<&'static str>,
+}
+
+#[derive(Default)]
+struct Object {
+    count: usize,
+    /// In the order they were first seen.
+    properties: Vec<(String, Shape, usize)>,
+    index: HashMap<String, usize>,
+}
+
+impl Shape {
+    fn add(&mut self, value: &Value, max_enum: usize) {
+        match value {
+            Value::Null => self.null = true,
+            Value::Bool(_) => self.boolean = true,
+            Value::Number(n) if n.is_i64() || n.is_u64() => self.integer = true,
+            Value::Number(_) => self.number = true,
+            Value::String(s) => {
+                let strings = self.strings.get_or_insert_with(|| Strings {
+                    count: 0,
+                    values: BTreeSet::new(),
+                    formats: FORMATS.to_vec(),
+                });
+                strings.count += 1;
+                if strings.values.len() <= max_enum {
+                    strings.values.insert(s.clone());
+                }
+                strings.formats.retain(|f| has_format(f, s));
+            }
+            Value::Array(items) => {
+                let shape = self.items.get_or_insert_with(Box::default);
+                for item in items {
+                    shape.add(item, max_enum);
+                }
+            }
+            Value::Object(map) => {
+                let object = self.object.get_or_insert_with(Object::default);
+                object.count += 1;
+                for (name, value) in map {
+                    let i = match object.index.get(name) {
+                        Some(&i) => i,
+                        None => {
+                            object.index.insert(name.clone(), object.properties.len());
+                            object.properties.push((name.clone(), Shape::default(), 0));
+                            object.properties.len() - 1
+                        }
+                    };
+                    let (_, shape, seen) = &mut object.properties[i];
+                    shape.add(value, max_enum);
+                    *seen += 1;
+                }
+            }
+        }
+    }
+
+    fn schema(&self, max_enum: usize) -> Map<String, Value> {
+        let mut types = Vec::new();
+        if self.null {
+            types.push("null");
+        }
+        if self.boolean {
+            types.push("boolean");
+        }
+        // integers are numbers too, so one `number` covers both
+        match (self.integer, self.number) {
+            (_, true) => types.push("number"),
+            (true, false) => types.push("integer"),
+            _ => {}
+        }
+        if self.strings.is_some() {
+            types.push("string");
+        }
+        if self.items.is_some() {
+            types.push("array");
+        }
+        if self.object.is_some() {
+            types.push("object");
+        }
+
+        let mut schema = Map::new();
+        match types.as_slice() {
+            [] => {}
+            [name] => {
+                schema.insert("type".to_string(), json!(name));
+            }
+            names => {
+                schema.insert("type".to_string(), json!(names));
+            }
+        }
+
+        if let Some(strings) = &self.strings {
+            if let Some(format) = strings.formats.first() {
+                schema.insert("format".to_string(), json!(format));
+            } else if strings.values.len() <= max_enum
+                && strings.count > strings.values.len()
+                && types.iter().all(|t| ["string", "null"].contains(t))
+            {
+                // a value repeats, so the strings are likely from a fixed set
+                let mut values: Vec<Value> = strings.values.iter().map(|s| json!(s)).collect();
+                if self.null {
+                    values.push(Value::Null);
+                }
+                schema.insert("enum".to_string(), Value::Array(values));
+            }
+        }
+
+        if let Some(items) = &self.items {
+            let items = items.schema(max_enum);
+            // only empty arrays were seen
+            if !items.is_empty() {
+                schema.insert("items".to_string(), Value::Object(items));
+            }
+        }
+
+        if let Some(object) = &self.object {
+            let properties: Map<String, Value> = object
+                .properties
+                .iter()
+                .map(|(name, shape, _)| (name.clone(), Value::Object(shape.schema(max_enum))))
+                .collect();
+            let required: Vec<&String> = object
+                .properties
+                .iter()
+                .filter(|(_, _, seen)| *seen == object.count)
+                .map(|(name, _, _)| name)
+                .collect();
+            if !properties.is_empty() {
+                schema.insert("properties".to_string(), Value::Object(properties));
+            }
+            if !required.is_empty() {
+                schema.insert("required".to_string(), json!(required));
+            }
+        }
+        schema
+    }
+}
+
+fn has_format(format: &str, s: &str) -> bool {
+    match format {
+        // `note: ...` is a URI too, only ones with an authority are likely meant as URIs
+        "uri" => s.contains("://") && format::is_valid(format, s),
+        format => format::is_valid(format, s),
+    }
+}
+
+pub fn run(args: InferArgs) -> anyhow::Result<()> {
+    let sources: Vec<(String, InputSource)> = match args.files.is_empty() {
+        true => vec![(
+            "<stdin>".to_string(),
+            InputSource {
+                input: None,
+                raw: false,
+            },
+        )],
+        false => args
+            .files
+            .iter()
+            .map(|file| {
+                let source = InputSource {
+                    input: Some(file.clone()),
+                    raw: false,
+                };
+                (file.clone(), source)
+            })
+            .collect(),
+    };
+
+    let mut shape = Shape::default();
+    let mut samples = 0;
+    for (name, source) in sources {
+        let text = decode_utf8(read_all(source)?)?;
+        let mut documents = Vec::new();
+        for document in serde_json::Deserializer::from_str(&text).into_iter::<Value>() {
+            match document {
+                Ok(document) => documents.push(document),
+                Err(e) => {
+                    // a single JSON5 document, with comments or trailing commas
+                    if documents.is_empty() {
+                        if let Ok(document) = parse(&text) {
+                            documents.push(document);
+                            break;
+                        }
+                    }
+                    let sample = documents.len() + 1;
+                    return Err(e)
+                        .with_context(|| format!("Parsing the sample {} of '{}'", sample, name));
+                }
+            }
+        }
+        for document in &documents {
+            shape.add(document, args.max_enum);
+            samples += 1;
+        }
+    }
+    if samples == 0 {
+        anyhow::bail!("No sample to infer a schema from");
+    }
+
+    let mut schema = Map::new();
+    schema.insert(
+        "$schema".to_string(),
+        json!("https://json-schema.org/draft/2020-12/schema"),
+    );
+    schema.extend(shape.schema(args.max_enum));
+    println!("{}", serde_json::to_string_pretty(&schema)?);
+    Ok(())
+}
@@ -0,0 +1,117 @@
+//! `json lint`, every syntax mistake of a document rather than only the first.
+
+use super::source_name;
+use super::syntax::{self, Lines};
+use crate::input::{decode_utf8, read_all, InputSource};
+
+#[derive(clap::Args, Debug)]
+pub struct LintArgs {
+    /// Only report what JSON5 does not allow either, for JSON5 and JSONC files
+    #[clap(long)]
+    json5: bool,
+
+    #[clap(flatten)]
+    input: InputSource,
+}
+
+pub fn run(args: LintArgs) -> anyhow::Result<()> {
+    let name = source_name(&args.input);
+    let text = decode_utf8(read_all(args.input)?)?;
+
+    let mut problems = syntax::parse(&text).problems;
+    if args.json5 {
+        problems.retain(|problem| !problem.json5);
+    }
+    if problems.is_empty() {
+        println!("OK");
+        return Ok(());
+    }
+    problems.sort_by_key(|problem| problem.offset);
+
+    let lines = Lines::new(&text);
+    for problem in &problems {
+        let position = lines.position(problem.offset);
+        let severity = if problem.warning { "warning" } else { "error" };
+        println!(
+            "{}:{}:{}: {}: {}",
+            name, position.line, position.column, severity, problem.message
+        );
+        println!("{}\n", lines.snippet(problem));
+    }
+
+    let errors = problems.iter().filter(|problem| !problem.warning).count();
+    let warnings = problems.len() - errors;
+    let summary = format!(
+        "{} error{}, {} warning{}",
+        errors,
+        plural(errors),
+        warnings,
+        plural(warnings)
+    );
+    match errors {
+        0 => {
+            eprintln!("{}", summary);
+            Ok(())
+        }
+        _ => Err(anyhow::anyhow!(summary)),
+    }
+}
+
+fn plural(n: usize) -> &'static str {
+    if n == 1 {
+        ""
+    } else {
+        "s"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{run, LintArgs};
+    use crate::input::InputSource;
+
+    /// The summary `json lint` fails with, or `None` when it succeeds.
+    fn lint(document: &str, json5: bool) -> Option<String> {
+        let args = LintArgs {
+            json5,
+            input: InputSource {
+                input: Some(document.to_string()),
+                raw: true,
+            },
+        };
+        run(args).err().map(|e| e.to_string())
+    }
+
+    #[test]
+    fn summary() {
+        assert_eq!(lint(r#"{"a": [1, 2]}"#, false), None);
+        assert_eq!(
+            lint(r#"{"a": 1 "b": [1, 2,, 3], "c": tru"#, false).as_deref(),
+            Some("4 errors, 0 warnings")
+        );
+        assert_eq!(
+            lint(r#"{"a": 1 "b": 2, "a": 3}"#, false).as_deref(),
+            Some("1 error, 1 warning")
+        );
+        // warnings alone do not fail
+        assert_eq!(lint(r#"{"a": 1, "a": 2}"#, false), None);
+    }
+
+    #[test]
+    fn json5() {
+        let jsonc = "{\n  // the port\n  port: 8080,\n  'hosts': ['a',],\n}";
+        assert_eq!(lint(jsonc, true), None);
+        assert_eq!(lint(jsonc, false).as_deref(), Some("6 errors, 0 warnings"));
+        // what JSON5 does not allow either is still reported
+        assert_eq!(
+            lint("{port: 0x1G}", true).as_deref(),
+            Some("1 error, 0 warnings")
+        );
+        assert_eq!(lint("[True]", true).as_deref(), Some("1 error, 0 warnings"));
+        assert_eq!(lint("{a: Infinity, b: -Infinity, c: NaN}", true), None);
+        assert_eq!(
+            lint("{\"a\": NaN}", false).as_deref(),
+            Some("1 error, 0 warnings")
+        );
+    }
+}
@@ -0,0 +1,192 @@
+//! `json merge`, layering documents with RFC 7396 JSON Merge Patch.
+
+use serde_json::{Map, Value};
+
+use super::{read_file, remove_member, values_equal};
+
+#[derive(clap::Args, Debug)]
+pub struct MergeArgs {
+    /// The document to start from
+    base: String,
+
+    /// Merge patches applied in order, `null` removes a member
+    #[clap(required = true)]
+    overlays: Vec<String>,
+}
+
+pub fn run(args: MergeArgs) -> anyhow::Result<()> {
+    let mut doc = read_file(&args.base)?;
+    for overlay in &args.overlays {
+        merge_patch(&mut doc, &read_file(overlay)?);
+    }
+    println!("{}", serde_json::to_string_pretty(&doc)?);
+    Ok(())
+}
+
+pub fn merge_patch(target: &mut Value, patch: &Value) {
+    let patch = match patch {
+        Value::Object(patch) => patch,
+        patch => {
+            *target = patch.clone();
+            return;
+        }
+    };
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    if let Value::Object(map) = target {
+        for (key, value) in patch {
+            match value {
+                Value::Null => {
+                    remove_member(map, key);
+                }
+                value => merge_patch(map.entry(key.clone()).or_insert(Value::Null), value),
+            }
+        }
+    }
+}
+
+/// The merge patch from `a` to `b`, the inverse of [`merge_patch`]. Nulls in `b` can not
+/// be expressed, a merge patch removes the members it sets to null.
+pub fn generate(a: &Value, b: &Value) -> Value {
+    match (a, b) {
+        (Value::Object(a), Value::Object(b)) => {
+            let mut patch = Map::new();
+            for key in a.keys().filter(|key| !b.contains_key(*key)) {
+                patch.insert(key.clone(), Value::Null);
+            }
+            for (key, b_value) in b {
+                match a.get(key) {
+                    Some(a_value) if values_equal(a_value, b_value) => {}
+                    Some(a_value) => {
+                        patch.insert(key.clone(), generate(a_value, b_value));
+                    }
+                    None => {
+                        patch.insert(key.clone(), b_value.clone());
+                    }
+                }
+            }
+            Value::Object(patch)
+        }
+        (_, b) => b.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::{generate, merge_patch};
+
+    /// The test cases of RFC 7396, appendix A.
+    fn rfc_examples() -> Vec<(Value, Value, Value)> {
+        vec![
+            (json!({"a": "b"}), json!({"a": "c"}), json!({"a": "c"})),
+            (
+                json!({"a": "b"}),
+                json!({"b": "c"}),
+                json!({"a": "b", "b": "c"}),
+            ),
+            (json!({"a": "b"}), json!({"a": null}), json!({})),
+            (
+                json!({"a": "b", "b": "c"}),
+                json!({"a": null}),
+                json!({"b": "c"}),
+            ),
+            (json!({"a": ["b"]}), json!({"a": "c"}), json!({"a": "c"})),
+            (json!({"a": "c"}), json!({"a": ["b"]}), json!({"a": ["b"]})),
+            (
+                json!({"a": {"b": "c"}}),
+                json!({"a": {"b": "d", "c": null}}),
+                json!({"a": {"b": "d"}}),
+            ),
+            (
+                json!({"a": [{"b": "c"}]}),
+                json!({"a": [1]}),
+                json!({"a": [1]}),
+            ),
+            (json!(["a", "b"]), json!(["c", "d"]), json!(["c", "d"])),
+            (json!({"a": "b"}), json!(["c"]), json!(["c"])),
+            (json!({"a": "foo"}), json!(null), json!(null)),
+            (json!({"a": "foo"}), json!("bar"), json!("bar")),
+            (
+                json!({"e": null}),
+                json!({"a": 1}),
+                json!({"e": null, "a": 1}),
+            ),
+            (
+                json!([1, 2]),
+                json!({"a": "b", "c": null}),
+                json!({"a": "b"}),
+            ),
+            (
+                json!({}),
+                json!({"a": {"bb": {"ccc": null}}}),
+                json!({"a": {"bb": {}}}),
+            ),
+        ]
+    }
+
+    #[test]
+    fn merge() {
+        for (target, patch, expected) in rfc_examples() {
+            let mut merged = target.clone();
+            merge_patch(&mut merged, &patch);
+            assert_eq!(merged, expected, "{} merged with {}", target, patch);
+        }
+
+        // the example of section 3
+        let mut doc = json!({
+            "title": "Goodbye!",
+            "author": {"givenName": "John", "familyName": "Doe"},
+            "tags": ["example", "sample"],
+            "content": "This will be unchanged"
+        });
+        let patch = json!({
+            "title": "Hello!",
+            "phoneNumber": "+01-123-456-7890",
+            "author": {"familyName": null},
+            "tags": ["example"]
+        });
+        merge_patch(&mut doc, &patch);
+        assert_eq!(
+            doc,
+            json!({
+                "title": "Hello!",
+                "author": {"givenName": "John"},
+                "tags": ["example"],
+                "content": "This will be unchanged",
+                "phoneNumber": "+01-123-456-7890"
+            })
+        );
+    }
+
+    #[test]
+    fn members_keep_their_order() {
+        let mut doc = json!({"a": 1, "b": 2, "c": 3});
+        merge_patch(&mut doc, &json!({"b": null, "d": 4, "a": 0}));
+        assert_eq!(doc.to_string(), r#"{"a":0,"c":3,"d":4}"#);
+    }
+
+    #[test]
+    fn generated_patches() {
+        // a generated patch takes the target to the result, when the result has no nulls
+        for (target, _, expected) in rfc_examples() {
+            if expected.to_string().contains("null") {
+                continue;
+            }
+            let patch = generate(&target, &expected);
+            let mut merged = target.clone();
+            merge_patch(&mut merged, &patch);
+            assert_eq!(merged, expected, "{} to {}", target, expected);
+        }
+        assert_eq!(
+            generate(
+                &json!({"a": 1, "b": {"c": 2, "d": 3}}),
+                &json!({"b": {"c": 2, "d": 4}})
+            ),
+            json!({"a": null, "b": {"d": 4}})
+        );
+        assert_eq!(generate(&json!({"a": 1}), &json!({"a": 1.0})), json!({}));
+    }
+}
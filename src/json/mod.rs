@@ -0,0 +1,187 @@
+//! JSON tools.
+
+use anyhow::Context;
+use serde_json::{Map, Value};
+
+use crate::input::{decode_utf8, for_text_input, read_all, InputSource};
+
+mod canonical;
+mod diff;
+mod infer;
+mod lint;
+mod merge;
+mod patch;
+mod path;
+mod pointer;
+mod pretty;
+mod query;
+mod regex;
+mod repair;
+mod schema;
+mod source;
+mod syntax;
+mod validate;
+
+#[derive(clap::Args, Debug)]
+pub struct JsonArg {
+    #[clap(subcommand)]
+    action: JsonAction,
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum JsonAction {
+    Minify(FormatArgs),
+    /// Pretty print, with syntax highlighting when the output is a terminal
+    Unminify(UnminifyArgs),
+
+    /// Extract values with a JSONPath (`$.items[*].name`) or a JSON Pointer (`/items/0/name`)
+    Query(query::QueryArgs),
+
+    /// Check a document against a JSON Schema, draft 7 or 2020-12. Exits with 1 when the
+    /// document does not match, 3 when the schema is invalid and 4 when either can not be
+    /// read or parsed
+    Validate(validate::ValidateArgs),
+
+    /// Generate a JSON Schema from sample documents, to start a contract for an API
+    Infer(infer::InferArgs),
+
+    /// Compare two documents, ignoring key order and formatting. Exits with 1 when they
+    /// differ
+    Diff(diff::DiffArgs),
+
+    /// Apply an RFC 6902 JSON Patch, all of its operations or none
+    Patch(patch::PatchArgs),
+
+    /// Layer documents with RFC 7396 JSON Merge Patch, such as config overrides
+    Merge(merge::MergeArgs),
+
+    /// Print the RFC 8785 canonical form (JCS), for signing and hashing
+    Canonical(canonical::CanonicalArgs),
+
+    /// Report every syntax mistake in a document, with hints, rather than only the first.
+    /// Exits with 1 when there are any
+    Lint(lint::LintArgs),
+
+    /// Turn relaxed or broken JSON into strict JSON: comments, trailing commas, single
+    /// quotes, unquoted keys, Python's `True` and `None`, and truncated documents
+    Repair(repair::RepairArgs),
+}
+
+#[derive(clap::Args, Debug)]
+struct UnminifyArgs {
+    #[clap(flatten)]
+    pretty: pretty::PrettyOptions,
+
+    #[clap(flatten)]
+    format: FormatArgs,
+}
+
+#[derive(clap::Args, Debug)]
+struct FormatArgs {
+    /// Sort the keys of every object
+    #[clap(long)]
+    sort_keys: bool,
+
+    #[clap(flatten)]
+    input: InputSource,
+}
+
+pub fn run(arg: JsonArg) -> anyhow::Result<()> {
+    match arg.action {
+        JsonAction::Minify(args) => {
+            let sort_keys = args.sort_keys;
+            for_text_input(args.input, |input| {
+                // strict JSON is minified as written, JSON5 is parsed for its comments to go
+                let strict = serde_json::from_str::<Value>(&input);
+                if !sort_keys && strict.is_ok() {
+                    println!("{}", minify::json::minify(&input));
+                    return Ok(());
+                }
+                let mut s = strict
+                    .or_else(|e| syntax::relaxed(&input, e))
+                    .context("Parse Valid JSON")?;
+                if sort_keys {
+                    s = canonical::sort_keys(&s);
+                }
+                println!("{}", serde_json::to_string(&s)?);
+                Ok(())
+            })
+            .context("Minify JSON")
+        }
+        JsonAction::Unminify(args) => {
+            let sort_keys = args.format.sort_keys;
+            let colour = atty::is(atty::Stream::Stdout);
+            for_text_input(args.format.input, |input| {
+                let mut s: Value = parse(&input).context("Parse Valid JSON")?;
+                if sort_keys {
+                    s = canonical::sort_keys(&s);
+                }
+                println!("{}", pretty::Printer::print(&args.pretty, colour, &s));
+                Ok(())
+            })
+            .context("Minify JSON")
+        }
+        JsonAction::Query(args) => query::run(args).context("JSON Query"),
+        JsonAction::Validate(args) => validate::run(args).context("JSON Validate"),
+        JsonAction::Infer(args) => infer::run(args).context("JSON Schema Inference"),
+        JsonAction::Diff(args) => diff::run(args).context("JSON Diff"),
+        JsonAction::Patch(args) => patch::run(args).context("JSON Patch"),
+        JsonAction::Merge(args) => merge::run(args).context("JSON Merge Patch"),
+        JsonAction::Canonical(args) => canonical::run(args).context("JSON Canonicalization"),
+        JsonAction::Lint(args) => lint::run(args).context("JSON Lint"),
+        JsonAction::Repair(args) => repair::run(args).context("JSON Repair"),
+    }
+}
+
+/// Equality of JSON values where numbers are equal by value, so `1` equals `1.0`.
+pub fn values_equal(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => match (a.as_i64(), b.as_i64()) {
+            (Some(a), Some(b)) => a == b,
+            _ => match (a.as_u64(), b.as_u64()) {
+                (Some(a), Some(b)) => a == b,
+                _ => a.as_f64() == b.as_f64(),
+            },
+        },
+        (Value::Array(a), Value::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| values_equal(a, b))
+        }
+        (Value::Object(a), Value::Object(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .all(|(key, a)| b.get(key).is_some_and(|b| values_equal(a, b)))
+        }
+        (a, b) => a == b,
+    }
+}
+
+/// Reads and parses a JSON file.
+pub fn read_file(path: &str) -> anyhow::Result<Value> {
+    let source = InputSource {
+        input: Some(path.to_string()),
+        raw: false,
+    };
+    let text = decode_utf8(read_all(source)?)?;
+    parse(&text).with_context(|| format!("Parsing '{}'", path))
+}
+
+/// The file name of an input for messages, `<input>` for stdin and raw input.
+pub fn source_name(input: &InputSource) -> String {
+    match &input.input {
+        Some(path) if !input.raw => path.clone(),
+        _ => "<input>".to_string(),
+    }
+}
+
+/// Parses JSON, or JSON5 and JSONC such as config files with comments and trailing
+/// commas. The error has the line of the mistake and a hint.
+pub fn parse(text: &str) -> anyhow::Result<Value> {
+    serde_json::from_str(text).or_else(|e| syntax::relaxed(text, e))
+}
+
+/// Removes a member and keeps the others in order, unlike [`Map::remove`].
+pub fn remove_member(map: &mut Map<String, Value>, key: &str) -> Option<Value> {
+    let value = map.get(key).cloned()?;
+    map.retain(|k, _| k != key);
+    Some(value)
+}
@@ -0,0 +1,297 @@
+//! `json patch`, applying an RFC 6902 JSON Patch.
+
+use anyhow::Context;
+use serde_json::Value;
+
+use super::{pointer, read_file, remove_member, values_equal};
+
+#[derive(clap::Args, Debug)]
+pub struct PatchArgs {
+    /// The document to patch
+    doc: String,
+
+    /// The JSON Patch, an array of operations
+    patch: String,
+}
+
+pub fn run(args: PatchArgs) -> anyhow::Result<()> {
+    let mut doc = read_file(&args.doc)?;
+    let patch = read_file(&args.patch)?;
+    apply(&mut doc, &patch)?;
+    println!("{}", serde_json::to_string_pretty(&doc)?);
+    Ok(())
+}
+
+/// Applies every operation or none, the document is left as it was on failure.
+pub fn apply(doc: &mut Value, patch: &Value) -> anyhow::Result<()> {
+    let operations = patch
+        .as_array()
+        .context("A JSON Patch must be an array of operations")?;
+    let mut patched = doc.clone();
+    for (i, operation) in operations.iter().enumerate() {
+        let op = operation.get("op").and_then(Value::as_str).unwrap_or("?");
+        let path = operation.get("path").and_then(Value::as_str).unwrap_or("?");
+        apply_operation(&mut patched, operation)
+            .map_err(|e| anyhow::anyhow!(e))
+            .with_context(|| format!("Operation {} ({} '{}') failed", i, op, path))?;
+    }
+    *doc = patched;
+    Ok(())
+}
+
+fn apply_operation(doc: &mut Value, operation: &Value) -> Result<(), String> {
+    let member = |name: &str| {
+        operation
+            .get(name)
+            .ok_or_else(|| format!("missing the `{}` member", name))
+    };
+    let location = |name: &str| -> Result<Vec<String>, String> {
+        let pointer = member(name)?
+            .as_str()
+            .ok_or_else(|| format!("`{}` must be a string", name))?;
+        if !pointer.is_empty() && !pointer.starts_with('/') {
+            return Err(format!(
+                "`{}` must be a JSON Pointer starting with '/'",
+                name
+            ));
+        }
+        pointer::parse(pointer).map_err(|e| e.to_string())
+    };
+
+    let op = member("op")?.as_str().ok_or("`op` must be a string")?;
+    let path = location("path")?;
+    match op {
+        "add" => add(doc, &path, member("value")?.clone()),
+        "remove" => remove(doc, &path).map(drop),
+        "replace" => {
+            let value = member("value")?.clone();
+            let target = get_mut(doc, &path).ok_or("nothing to replace at the path")?;
+            *target = value;
+            Ok(())
+        }
+        "move" => {
+            let from = location("from")?;
+            if path.len() > from.len() && path[..from.len()] == from[..] {
+                return Err("a value can not be moved into one of its children".to_string());
+            }
+            let value = remove(doc, &from).map_err(|e| format!("{} at `from`", e))?;
+            add(doc, &path, value)
+        }
+        "copy" => {
+            let from = location("from")?;
+            let value = pointer::get(doc, &from).ok_or("nothing to copy at `from`")?;
+            add(doc, &path, value.clone())
+        }
+        "test" => {
+            let expected = member("value")?;
+            match pointer::get(doc, &path) {
+                Some(actual) if values_equal(actual, expected) => Ok(()),
+                Some(actual) => Err(format!("the test failed, the value is {}", actual)),
+                None => Err("the test failed, there is no value at the path".to_string()),
+            }
+        }
+        op => Err(format!("unknown operation '{}'", op)),
+    }
+}
+
+fn get_mut<'a>(value: &'a mut Value, tokens: &[String]) -> Option<&'a mut Value> {
+    tokens.iter().try_fold(value, |value, token| match value {
+        Value::Object(map) => map.get_mut(token),
+        Value::Array(items) => items.get_mut(pointer::index(token)?),
+        _ => None,
+    })
+}
+
+/// The parent of the location and the token of the location in it.
+fn parent<'a, 'b>(
+    doc: &'a mut Value,
+    path: &'b [String],
+) -> Result<(&'a mut Value, &'b str), String> {
+    let (last, parent) = path
+        .split_last()
+        .ok_or("the whole document has no parent")?;
+    let parent = get_mut(doc, parent).ok_or("the parent of the path does not exist")?;
+    Ok((parent, last))
+}
+
+fn add(doc: &mut Value, path: &[String], value: Value) -> Result<(), String> {
+    if path.is_empty() {
+        *doc = value;
+        return Ok(());
+    }
+    match parent(doc, path)? {
+        (Value::Object(map), key) => {
+            map.insert(key.to_string(), value);
+            Ok(())
+        }
+        (Value::Array(items), "-") => {
+            items.push(value);
+            Ok(())
+        }
+        (Value::Array(items), token) => match pointer::index(token) {
+            Some(i) if i <= items.len() => {
+                items.insert(i, value);
+                Ok(())
+            }
+            _ => Err(format!("'{}' is not an index of the array", token)),
+        },
+        _ => Err("the parent of the path is not an object or an array".to_string()),
+    }
+}
+
+fn remove(doc: &mut Value, path: &[String]) -> Result<Value, String> {
+    match parent(doc, path)? {
+        (Value::Object(map), key) => remove_member(map, key),
+        (Value::Array(items), token) => pointer::index(token)
+            .filter(|&i| i < items.len())
+            .map(|i| items.remove(i)),
+        _ => None,
+    }
+    .ok_or_else(|| "nothing to remove at the path".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::apply;
+
+    fn patched(mut doc: Value, patch: Value) -> anyhow::Result<Value> {
+        apply(&mut doc, &patch)?;
+        Ok(doc)
+    }
+
+    // the examples of RFC 6902, appendix A
+    #[test]
+    fn rfc_examples() {
+        let cases = [
+            (
+                json!({"foo": "bar"}),
+                json!([{"op": "add", "path": "/baz", "value": "qux"}]),
+                json!({"baz": "qux", "foo": "bar"}),
+            ),
+            (
+                json!({"foo": ["bar", "baz"]}),
+                json!([{"op": "add", "path": "/foo/1", "value": "qux"}]),
+                json!({"foo": ["bar", "qux", "baz"]}),
+            ),
+            (
+                json!({"baz": "qux", "foo": "bar"}),
+                json!([{"op": "remove", "path": "/baz"}]),
+                json!({"foo": "bar"}),
+            ),
+            (
+                json!({"foo": ["bar", "qux", "baz"]}),
+                json!([{"op": "remove", "path": "/foo/1"}]),
+                json!({"foo": ["bar", "baz"]}),
+            ),
+            (
+                json!({"baz": "qux", "foo": "bar"}),
+                json!([{"op": "replace", "path": "/baz", "value": "boo"}]),
+                json!({"baz": "boo", "foo": "bar"}),
+            ),
+            (
+                json!({"foo": {"bar": "baz", "waldo": "fred"}, "qux": {"corge": "grault"}}),
+                json!([{"op": "move", "from": "/foo/waldo", "path": "/qux/thud"}]),
+                json!({"foo": {"bar": "baz"}, "qux": {"corge": "grault", "thud": "fred"}}),
+            ),
+            (
+                json!({"foo": ["all", "grass", "cows", "eat"]}),
+                json!([{"op": "move", "from": "/foo/1", "path": "/foo/3"}]),
+                json!({"foo": ["all", "cows", "eat", "grass"]}),
+            ),
+            (
+                json!({"baz": "qux", "foo": ["a", 2, "c"]}),
+                json!([
+                    {"op": "test", "path": "/baz", "value": "qux"},
+                    {"op": "test", "path": "/foo/1", "value": 2}
+                ]),
+                json!({"baz": "qux", "foo": ["a", 2, "c"]}),
+            ),
+            (
+                json!({"foo": "bar"}),
+                json!([{"op": "add", "path": "/child", "value": {"grandchild": {}}}]),
+                json!({"foo": "bar", "child": {"grandchild": {}}}),
+            ),
+            (
+                json!({"foo": "bar"}),
+                json!([{"op": "add", "path": "/baz", "value": "qux", "xyz": 123}]),
+                json!({"foo": "bar", "baz": "qux"}),
+            ),
+            (
+                json!({"/": 9, "~1": 10}),
+                json!([{"op": "test", "path": "/~01", "value": 10}]),
+                json!({"/": 9, "~1": 10}),
+            ),
+            (
+                json!({"foo": ["bar"]}),
+                json!([{"op": "add", "path": "/foo/-", "value": ["abc", "def"]}]),
+                json!({"foo": ["bar", ["abc", "def"]]}),
+            ),
+        ];
+        for (doc, patch, expected) in cases {
+            assert_eq!(patched(doc, patch.clone()).unwrap(), expected, "{}", patch);
+        }
+
+        // A.9, A.12 and A.15 fail, A.13 has duplicate members which do not survive parsing
+        let failing = [
+            (
+                json!({"baz": "qux"}),
+                json!([{"op": "test", "path": "/baz", "value": "bar"}]),
+            ),
+            (
+                json!({"foo": "bar"}),
+                json!([{"op": "add", "path": "/baz/bat", "value": "qux"}]),
+            ),
+            (
+                json!({"/": 9, "~1": 10}),
+                json!([{"op": "test", "path": "/~01", "value": "10"}]),
+            ),
+        ];
+        for (doc, patch) in failing {
+            assert!(patched(doc, patch.clone()).is_err(), "{}", patch);
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let doc = json!({"a": [1, 2], "b": {"c": 3}});
+        for patch in [
+            json!({"op": "add", "path": "/d", "value": 1}),
+            json!([{"op": "add", "path": "/d"}]),
+            json!([{"op": "add", "path": "d", "value": 1}]),
+            json!([{"op": "add", "path": "/a/3", "value": 1}]),
+            json!([{"op": "add", "path": "/a/01", "value": 1}]),
+            json!([{"op": "remove", "path": "/a/2"}]),
+            json!([{"op": "remove", "path": ""}]),
+            json!([{"op": "replace", "path": "/d", "value": 1}]),
+            json!([{"op": "move", "from": "/b", "path": "/b/c/d"}]),
+            json!([{"op": "copy", "from": "/d", "path": "/e"}]),
+            json!([{"op": "test", "path": "/d", "value": null}]),
+            json!([{"op": "frobnicate", "path": "/a"}]),
+        ] {
+            assert!(patched(doc.clone(), patch.clone()).is_err(), "{}", patch);
+        }
+
+        // all or nothing, the document is as it was when an operation fails
+        let mut unchanged = doc.clone();
+        let patch = json!([
+            {"op": "remove", "path": "/a"},
+            {"op": "test", "path": "/b/c", "value": 4}
+        ]);
+        assert!(apply(&mut unchanged, &patch).is_err());
+        assert_eq!(unchanged, doc);
+    }
+
+    #[test]
+    fn whole_document() {
+        let patch = json!([
+            {"op": "replace", "path": "", "value": [1]},
+            {"op": "copy", "from": "/0", "path": "/-"},
+            {"op": "test", "path": "", "value": [1.0, 1]}
+        ]);
+        assert_eq!(patched(json!({"a": 1}), patch).unwrap(), json!([1, 1]));
+        let patch = json!([{"op": "move", "from": "/a", "path": "/a"}]);
+        assert_eq!(patched(json!({"a": 1}), patch).unwrap(), json!({"a": 1}));
+    }
+}
@@ -0,0 +1,1072 @@
+//! JSONPath, RFC 9535.
+
+use serde_json::Value;
+
+use super::pointer::Step;
+use super::regex::Regex;
+use super::values_equal;
+
+/// The largest integer an index or slice bound can be, as in I-JSON.
+const MAX_INT: i64 = (1 << 53) - 1;
+
+#[derive(Debug)]
+pub struct JsonPath {
+    segments: Vec<Segment>,
+}
+
+/// A value selected by a query and where it is in the document.
+pub struct Node<'a> {
+    pub path: Vec<Step>,
+    pub value: &'a Value,
+}
+
+#[derive(Debug)]
+struct Segment {
+    /// `..`, the selectors apply to the node and all of its descendants.
+    descendant: bool,
+    selectors: Vec<Selector>,
+}
+
+#[derive(Debug)]
+enum Selector {
+    Name(String),
+    Wildcard,
+    Index(i64),
+    Slice(Option<i64>, Option<i64>, Option<i64>),
+    Filter(Logical),
+}
+
+#[derive(Debug)]
+enum Logical {
+    Or(Vec<Logical>),
+    And(Vec<Logical>),
+    Not(Box<Logical>),
+    Compare(Comparable, Op, Comparable),
+    /// A query on its own, true when it selects anything.
+    Exists(Query),
+    Function(Function),
+}
+
+#[derive(Debug)]
+enum Comparable {
+    Literal(Value),
+    Query(Query),
+    Function(Function),
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug)]
+struct Query {
+    /// Starts at `@`, the current node, rather than `$`.
+    relative: bool,
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug)]
+struct Function {
+    name: FunctionName,
+    args: Vec<Comparable>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum FunctionName {
+    Length,
+    Count,
+    Match,
+    Search,
+    Value,
+}
+
+impl JsonPath {
+    pub fn parse(expr: &str) -> anyhow::Result<JsonPath> {
+        let mut parser = Parser {
+            chars: expr.chars().collect(),
+            pos: 0,
+        };
+        if parser.next() != Some('$') {
+            return Err(parser.error("a JSONPath starts with '$'"));
+        }
+        let segments = parser.segments()?;
+        if parser.pos < parser.chars.len() {
+            return Err(parser.error("unexpected character"));
+        }
+        Ok(JsonPath { segments })
+    }
+
+    pub fn select<'a>(&self, root: &'a Value) -> Vec<Node<'a>> {
+        select(&self.segments, root, root)
+    }
+}
+
+/// The normalized path of a location, `$['a'][0]`.
+pub fn normalized_path(path: &[Step]) -> String {
+    let mut normalized = String::from("$");
+    for step in path {
+        match step {
+            Step::Index(i) => normalized.push_str(&format!("[{}]", i)),
+            Step::Key(key) => {
+                normalized.push_str("['");
+                for c in key.chars() {
+                    match c {
+                        '\'' => normalized.push_str("\\'"),
+                        '\\' => normalized.push_str("\\\\"),
+                        '\u{8}' => normalized.push_str("\\b"),
+                        '\u{c}' => normalized.push_str("\\f"),
+                        '\n' => normalized.push_str("\\n"),
+                        '\r' => normalized.push_str("\\r"),
+                        '\t' => normalized.push_str("\\t"),
+                        c if c < ' ' => normalized.push_str(&format!("\\u{:04x}", c as u32)),
+                        c => normalized.push(c),
+                    }
+                }
+                normalized.push_str("']");
+            }
+        }
+    }
+    normalized
+}
+
+fn select<'a>(segments: &[Segment], root: &'a Value, start: &'a Value) -> Vec<Node<'a>> {
+    let mut nodes = vec![Node {
+        path: Vec::new(),
+        value: start,
+    }];
+    for segment in segments {
+        let mut selected = Vec::new();
+        for node in &nodes {
+            segment.apply(root, node, &mut selected);
+        }
+        nodes = selected;
+    }
+    nodes
+}
+
+fn child<'a>(parent: &Node, step: Step, value: &'a Value) -> Node<'a> {
+    let mut path = parent.path.clone();
+    path.push(step);
+    Node { path, value }
+}
+
+/// The children of a node in document order.
+fn children<'a>(node: &Node<'a>) -> Vec<Node<'a>> {
+    match node.value {
+        Value::Object(map) => map
+            .iter()
+            .map(|(key, value)| child(node, Step::Key(key.clone()), value))
+            .collect(),
+        Value::Array(items) => items
+            .iter()
+            .enumerate()
+            .map(|(i, value)| child(node, Step::Index(i), value))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+impl Segment {
+    fn apply<'a>(&self, root: &'a Value, node: &Node<'a>, selected: &mut Vec<Node<'a>>) {
+        for selector in &self.selectors {
+            selector.apply(root, node, selected);
+        }
+        if self.descendant {
+            for child in children(node) {
+                self.apply(root, &child, selected);
+            }
+        }
+    }
+}
+
+impl Selector {
+    fn apply<'a>(&self, root: &'a Value, node: &Node<'a>, selected: &mut Vec<Node<'a>>) {
+        match (self, node.value) {
+            (Selector::Name(name), Value::Object(map)) => {
+                if let Some(value) = map.get(name) {
+                    selected.push(child(node, Step::Key(name.clone()), value));
+                }
+            }
+            (Selector::Wildcard, _) => selected.extend(children(node)),
+            (Selector::Index(i), Value::Array(items)) => {
+                let len = items.len() as i64;
+                let i = if *i < 0 { len + i } else { *i };
+                if (0..len).contains(&i) {
+                    let i = i as usize;
+                    selected.push(child(node, Step::Index(i), &items[i]));
+                }
+            }
+            (Selector::Slice(start, end, step), Value::Array(items)) => {
+                for i in slice_indices(items.len() as i64, *start, *end, step.unwrap_or(1)) {
+                    let i = i as usize;
+                    selected.push(child(node, Step::Index(i), &items[i]));
+                }
+            }
+            (Selector::Filter(filter), _) => selected.extend(
+                children(node)
+                    .into_iter()
+                    .filter(|child| filter.test(root, child.value)),
+            ),
+            _ => {}
+        }
+    }
+}
+
+/// The indices selected by `[start:end:step]`, section 2.3.4.2.2 of the RFC.
+fn slice_indices(len: i64, start: Option<i64>, end: Option<i64>, step: i64) -> Vec<i64> {
+    let normalize = |i: i64| if i >= 0 { i } else { len + i };
+    let mut indices = Vec::new();
+    if step > 0 {
+        let lower = normalize(start.unwrap_or(0)).clamp(0, len);
+        let upper = normalize(end.unwrap_or(len)).clamp(0, len);
+        let mut i = lower;
+        while i < upper {
+            indices.push(i);
+            i += step;
+        }
+    } else if step < 0 {
+        let upper = normalize(start.unwrap_or(len - 1)).clamp(-1, len - 1);
+        let lower = end.map_or(-1, |end| normalize(end).clamp(-1, len - 1));
+        let mut i = upper;
+        while lower < i {
+            indices.push(i);
+            i += step;
+        }
+    }
+    indices
+}
+
+impl Logical {
+    fn test(&self, root: &Value, current: &Value) -> bool {
+        match self {
+            Logical::Or(all) => all.iter().any(|l| l.test(root, current)),
+            Logical::And(all) => all.iter().all(|l| l.test(root, current)),
+            Logical::Not(l) => !l.test(root, current),
+            Logical::Compare(left, op, right) => {
+                let left = left.value(root, current);
+                let right = right.value(root, current);
+                compare(left.as_ref(), *op, right.as_ref())
+            }
+            Logical::Exists(query) => !query.select(root, current).is_empty(),
+            Logical::Function(function) => function.test(root, current),
+        }
+    }
+}
+
+fn compare(left: Option<&Value>, op: Op, right: Option<&Value>) -> bool {
+    let equal = |l: Option<&Value>, r: Option<&Value>| match (l, r) {
+        (None, None) => true,
+        (Some(l), Some(r)) => values_equal(l, r),
+        _ => false,
+    };
+    let less = |l: Option<&Value>, r: Option<&Value>| match (l, r) {
+        (Some(Value::Number(l)), Some(Value::Number(r))) => l.as_f64() < r.as_f64(),
+        (Some(Value::String(l)), Some(Value::String(r))) => l < r,
+        _ => false,
+    };
+    match op {
+        Op::Eq => equal(left, right),
+        Op::Ne => !equal(left, right),
+        Op::Lt => less(left, right),
+        Op::Le => less(left, right) || equal(left, right),
+        Op::Gt => less(right, left),
+        Op::Ge => less(right, left) || equal(left, right),
+    }
+}
+
+impl Query {
+    fn select<'a>(&self, root: &'a Value, current: &'a Value) -> Vec<Node<'a>> {
+        let start = if self.relative { current } else { root };
+        select(&self.segments, root, start)
+    }
+
+    /// A query that selects at most one node, by names and indices only.
+    fn is_singular(&self) -> bool {
+        self.segments.iter().all(|segment| {
+            !segment.descendant
+                && matches!(
+                    segment.selectors.as_slice(),
+                    [Selector::Name(_) | Selector::Index(_)]
+                )
+        })
+    }
+}
+
+impl Comparable {
+    /// The value of a literal, singular query or function, `None` is "Nothing".
+    fn value(&self, root: &Value, current: &Value) -> Option<Value> {
+        match self {
+            Comparable::Literal(value) => Some(value.clone()),
+            Comparable::Query(query) => query
+                .select(root, current)
+                .first()
+                .map(|node| node.value.clone()),
+            Comparable::Function(function) => function.value(root, current),
+        }
+    }
+
+    fn is_value(&self) -> bool {
+        match self {
+            Comparable::Literal(_) => true,
+            Comparable::Query(query) => query.is_singular(),
+            Comparable::Function(function) => function.name.returns_value(),
+        }
+    }
+}
+
+impl FunctionName {
+    fn from_name(name: &str) -> Option<FunctionName> {
+        Some(match name {
+            "length" => FunctionName::Length,
+            "count" => FunctionName::Count,
+            "match" => FunctionName::Match,
+            "search" => FunctionName::Search,
+            "value" => FunctionName::Value,
+            _ => return None,
+        })
+    }
+
+    /// ValueType functions, the others are LogicalType.
+    fn returns_value(self) -> bool {
+        !matches!(self, FunctionName::Match | FunctionName::Search)
+    }
+}
+
+impl Function {
+    fn value(&self, root: &Value, current: &Value) -> Option<Value> {
+        let nodes = |arg: &Comparable| match arg {
+            Comparable::Query(query) => query.select(root, current),
+            _ => Vec::new(),
+        };
+        match self.name {
+            FunctionName::Length => match self.args[0].value(root, current)? {
+                Value::String(s) => Some(s.chars().count().into()),
+                Value::Array(items) => Some(items.len().into()),
+                Value::Object(map) => Some(map.len().into()),
+                _ => None,
+            },
+            FunctionName::Count => Some(nodes(&self.args[0]).len().into()),
+            FunctionName::Value => match nodes(&self.args[0]).as_slice() {
+                [node] => Some(node.value.clone()),
+                _ => None,
+            },
+            FunctionName::Match | FunctionName::Search => None,
+        }
+    }
+
+    fn test(&self, root: &Value, current: &Value) -> bool {
+        let text = self.args[0].value(root, current);
+        let pattern = self.args[1].value(root, current);
+        let (text, pattern) = match (text, pattern) {
+            (Some(Value::String(text)), Some(Value::String(pattern))) => (text, pattern),
+            _ => return false,
+        };
+        // an invalid pattern does not match, as the RFC requires
+        match Regex::new(&pattern) {
+            Ok(regex) if self.name == FunctionName::Match => regex.is_full_match(&text),
+            Ok(regex) => regex.is_match(&text),
+            Err(_) => false,
+        }
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn error(&self, message: &str) -> anyhow::Error {
+        anyhow::anyhow!("Invalid JSONPath at position {}: {}", self.pos, message)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek();
+        self.pos += 1;
+        c
+    }
+
+    fn eat(&mut self, s: &str) -> bool {
+        let matches = s
+            .chars()
+            .enumerate()
+            .all(|(i, c)| self.chars.get(self.pos + i) == Some(&c));
+        if matches {
+            self.pos += s.chars().count();
+        }
+        matches
+    }
+
+    fn expect(&mut self, s: &str) -> anyhow::Result<()> {
+        match self.eat(s) {
+            true => Ok(()),
+            false => Err(self.error(&format!("expected '{}'", s))),
+        }
+    }
+
+    fn skip_blank(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t' | '\n' | '\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn segments(&mut self) -> anyhow::Result<Vec<Segment>> {
+        let mut segments = Vec::new();
+        loop {
+            let before = self.pos;
+            self.skip_blank();
+            let descendant = self.eat("..");
+            let selectors = if self.peek() == Some('[') {
+                self.bracketed()?
+            } else if descendant || self.eat(".") {
+                if self.eat("*") {
+                    vec![Selector::Wildcard]
+                } else {
+                    vec![Selector::Name(self.member_name()?)]
+                }
+            } else {
+                // the blank space belongs to whatever follows the query
+                self.pos = before;
+                return Ok(segments);
+            };
+            segments.push(Segment {
+                descendant,
+                selectors,
+            });
+        }
+    }
+
+    fn member_name(&mut self) -> anyhow::Result<String> {
+        let first = |c: char| c.is_ascii_alphabetic() || c == '_' || !c.is_ascii();
+        let mut name = String::new();
+        while let Some(c) = self.peek() {
+            if !(first(c) || (!name.is_empty() && c.is_ascii_digit())) {
+                break;
+            }
+            name.push(c);
+            self.pos += 1;
+        }
+        if name.is_empty() {
+            return Err(self.error("expected a member name, `*` or `[`"));
+        }
+        Ok(name)
+    }
+
+    fn bracketed(&mut self) -> anyhow::Result<Vec<Selector>> {
+        self.expect("[")?;
+        let mut selectors = Vec::new();
+        loop {
+            self.skip_blank();
+            selectors.push(self.selector()?);
+            self.skip_blank();
+            if self.eat("]") {
+                return Ok(selectors);
+            }
+            self.expect(",")?;
+        }
+    }
+
+    fn selector(&mut self) -> anyhow::Result<Selector> {
+        match self.peek() {
+            Some('\'' | '"') => Ok(Selector::Name(self.string()?)),
+            Some('*') => {
+                self.pos += 1;
+                Ok(Selector::Wildcard)
+            }
+            Some('?') => {
+                self.pos += 1;
+                self.skip_blank();
+                Ok(Selector::Filter(self.logical_or()?))
+            }
+            _ => {
+                let start = self.integer()?;
+                self.skip_blank();
+                if !self.eat(":") {
+                    return start
+                        .map(Selector::Index)
+                        .ok_or_else(|| self.error("expected a selector"));
+                }
+                self.skip_blank();
+                let end = self.integer()?;
+                self.skip_blank();
+                let step = match self.eat(":") {
+                    true => {
+                        self.skip_blank();
+                        self.integer()?
+                    }
+                    false => None,
+                };
+                Ok(Selector::Slice(start, end, step))
+            }
+        }
+    }
+
+    fn integer(&mut self) -> anyhow::Result<Option<i64>> {
+        let start = self.pos;
+        self.eat("-");
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        let text: String = self.chars[start..self.pos].iter().collect();
+        if text.is_empty() {
+            return Ok(None);
+        }
+        let digits = text.trim_start_matches('-');
+        if digits.is_empty() || (digits.starts_with('0') && text.len() > 1) {
+            return Err(self.error(&format!("invalid integer '{}'", text)));
+        }
+        match text.parse::<i64>() {
+            Ok(n) if (-MAX_INT..=MAX_INT).contains(&n) => Ok(Some(n)),
+            _ => Err(self.error(&format!("integer '{}' is out of range", text))),
+        }
+    }
+
+    fn string(&mut self) -> anyhow::Result<String> {
+        let quote = self.next().unwrap_or_default();
+        let mut s = String::new();
+        loop {
+            let c = self
+                .next()
+                .ok_or_else(|| self.error("unterminated string"))?;
+            match c {
+                c if c == quote => return Ok(s),
+                '\\' => {
+                    let escaped = match self.next() {
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some(c @ ('/' | '\\')) => c,
+                        Some(c) if c == quote => c,
+                        Some('u') => self.unicode_escape()?,
+                        _ => return Err(self.error("invalid escape")),
+                    };
+                    s.push(escaped);
+                }
+                c if c < ' ' => return Err(self.error("control character in string")),
+                c => s.push(c),
+            }
+        }
+    }
+
+    fn unicode_escape(&mut self) -> anyhow::Result<char> {
+        let hex4 = |parser: &mut Parser| -> anyhow::Result<u32> {
+            let hex: String = parser.chars.iter().skip(parser.pos).take(4).collect();
+            parser.pos += 4;
+            u32::from_str_radix(&hex, 16).map_err(|_| parser.error("invalid \\u escape"))
+        };
+        let high = hex4(self)?;
+        let code = if (0xd800..0xdc00).contains(&high) {
+            self.expect("\\u")?;
+            let low = hex4(self)?;
+            if !(0xdc00..0xe000).contains(&low) {
+                return Err(self.error("invalid surrogate pair"));
+            }
+            0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)
+        } else {
+            high
+        };
+        char::from_u32(code).ok_or_else(|| self.error("invalid \\u escape"))
+    }
+
+    fn logical_or(&mut self) -> anyhow::Result<Logical> {
+        let mut all = vec![self.logical_and()?];
+        loop {
+            let before = self.pos;
+            self.skip_blank();
+            if !self.eat("||") {
+                self.pos = before;
+                break;
+            }
+            self.skip_blank();
+            all.push(self.logical_and()?);
+        }
+        Ok(match all.len() {
+            1 => all.remove(0),
+            _ => Logical::Or(all),
+        })
+    }
+
+    fn logical_and(&mut self) -> anyhow::Result<Logical> {
+        let mut all = vec![self.basic()?];
+        loop {
+            let before = self.pos;
+            self.skip_blank();
+            if !self.eat("&&") {
+                self.pos = before;
+                break;
+            }
+            self.skip_blank();
+            all.push(self.basic()?);
+        }
+        Ok(match all.len() {
+            1 => all.remove(0),
+            _ => Logical::And(all),
+        })
+    }
+
+    fn basic(&mut self) -> anyhow::Result<Logical> {
+        if self.eat("!") {
+            self.skip_blank();
+            let negated = match self.peek() {
+                Some('(') => self.parenthesized()?,
+                _ => self.test()?,
+            };
+            return Ok(Logical::Not(Box::new(negated)));
+        }
+        if self.peek() == Some('(') {
+            return self.parenthesized();
+        }
+
+        let start = self.pos;
+        let left = self.comparable()?;
+        let before = self.pos;
+        self.skip_blank();
+        let op = match self.comparison_op() {
+            Some(op) => op,
+            None => {
+                self.pos = start;
+                return self.test();
+            }
+        };
+        self.skip_blank();
+        let right = self.comparable()?;
+        for side in [&left, &right] {
+            if !side.is_value() {
+                self.pos = before;
+                return Err(self
+                    .error("only literals, singular queries and value functions can be compared"));
+            }
+        }
+        Ok(Logical::Compare(left, op, right))
+    }
+
+    fn parenthesized(&mut self) -> anyhow::Result<Logical> {
+        self.expect("(")?;
+        self.skip_blank();
+        let logical = self.logical_or()?;
+        self.skip_blank();
+        self.expect(")")?;
+        Ok(logical)
+    }
+
+    /// A query or a logical function on its own.
+    fn test(&mut self) -> anyhow::Result<Logical> {
+        match self.comparable()? {
+            Comparable::Query(query) => Ok(Logical::Exists(query)),
+            Comparable::Function(function) if !function.name.returns_value() => {
+                Ok(Logical::Function(function))
+            }
+            Comparable::Function(_) => {
+                Err(self.error("the result of a value function must be compared"))
+            }
+            Comparable::Literal(_) => Err(self.error("a literal must be compared")),
+        }
+    }
+
+    fn comparison_op(&mut self) -> Option<Op> {
+        let ops = [
+            ("==", Op::Eq),
+            ("!=", Op::Ne),
+            ("<=", Op::Le),
+            (">=", Op::Ge),
+            ("<", Op::Lt),
+            (">", Op::Gt),
+        ];
+        ops.into_iter()
+            .find(|(text, _)| self.eat(text))
+            .map(|(_, op)| op)
+    }
+
+    fn comparable(&mut self) -> anyhow::Result<Comparable> {
+        match self.peek() {
+            Some(c @ ('@' | '$')) => {
+                self.pos += 1;
+                Ok(Comparable::Query(Query {
+                    relative: c == '@',
+                    segments: self.segments()?,
+                }))
+            }
+            Some('\'' | '"') => Ok(Comparable::Literal(Value::String(self.string()?))),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.number(),
+            Some(c) if c.is_ascii_lowercase() => {
+                let start = self.pos;
+                while self
+                    .peek()
+                    .is_some_and(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+                {
+                    self.pos += 1;
+                }
+                let name: String = self.chars[start..self.pos].iter().collect();
+                match name.as_str() {
+                    "true" => Ok(Comparable::Literal(Value::Bool(true))),
+                    "false" => Ok(Comparable::Literal(Value::Bool(false))),
+                    "null" => Ok(Comparable::Literal(Value::Null)),
+                    _ => {
+                        self.pos = start;
+                        Ok(Comparable::Function(self.function(&name)?))
+                    }
+                }
+            }
+            _ => Err(self.error("expected a query, literal or function")),
+        }
+    }
+
+    fn number(&mut self) -> anyhow::Result<Comparable> {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_digit() || "-+.eE".contains(c))
+        {
+            self.pos += 1;
+        }
+        let text: String = self.chars[start..self.pos].iter().collect();
+        let number: serde_json::Number = serde_json::from_str(&text)
+            .map_err(|_| self.error(&format!("invalid number '{}'", text)))?;
+        Ok(Comparable::Literal(Value::Number(number)))
+    }
+
+    fn function(&mut self, name: &str) -> anyhow::Result<Function> {
+        let function = FunctionName::from_name(name)
+            .ok_or_else(|| self.error(&format!("unknown function '{}'", name)))?;
+        self.pos += name.chars().count();
+        self.expect("(")?;
+
+        let mut args = Vec::new();
+        self.skip_blank();
+        if !self.eat(")") {
+            loop {
+                self.skip_blank();
+                args.push(self.comparable()?);
+                self.skip_blank();
+                if self.eat(")") {
+                    break;
+                }
+                self.expect(",")?;
+            }
+        }
+
+        // the arguments must be of the types the function declares
+        let valid = match (function, args.as_slice()) {
+            (FunctionName::Length, [arg]) => arg.is_value(),
+            (FunctionName::Count | FunctionName::Value, [arg]) => {
+                matches!(arg, Comparable::Query(_))
+            }
+            (FunctionName::Match | FunctionName::Search, [text, pattern]) => {
+                text.is_value() && pattern.is_value()
+            }
+            _ => false,
+        };
+        if !valid {
+            return Err(self.error(&format!("invalid arguments for {}()", name)));
+        }
+        Ok(Function {
+            name: function,
+            args,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{normalized_path, JsonPath};
+    use serde_json::Value;
+
+    /// The normalized paths of the nodes `expr` selects in `doc`.
+    fn paths(expr: &str, doc: &str) -> Vec<String> {
+        let doc: Value = serde_json::from_str(doc).unwrap();
+        let path = JsonPath::parse(expr).unwrap();
+        let nodes = path.select(&doc);
+        nodes
+            .iter()
+            .map(|node| normalized_path(&node.path))
+            .collect()
+    }
+
+    // the examples of RFC 9535, section 2
+    #[test]
+    fn name_wildcard_and_index() {
+        let doc = r#"{"o": {"j j": {"k.k": 3}}, "'": {"@": 2}}"#;
+        assert_eq!(paths("$.o['j j']", doc), ["$['o']['j j']"]);
+        assert_eq!(paths("$.o['j j']['k.k']", doc), ["$['o']['j j']['k.k']"]);
+        assert_eq!(paths(r#"$.o["j j"]["k.k"]"#, doc), ["$['o']['j j']['k.k']"]);
+        assert_eq!(paths(r#"$["'"]["@"]"#, doc), [r"$['\'']['@']"]);
+
+        let doc = r#"{"o": {"j": 1, "k": 2}, "a": [5, 3]}"#;
+        assert_eq!(paths("$[*]", doc), ["$['o']", "$['a']"]);
+        assert_eq!(paths("$.o[*]", doc), ["$['o']['j']", "$['o']['k']"]);
+        assert_eq!(
+            paths("$.o[*, *]", doc),
+            ["$['o']['j']", "$['o']['k']", "$['o']['j']", "$['o']['k']"]
+        );
+        assert_eq!(paths("$.a[*]", doc), ["$['a'][0]", "$['a'][1]"]);
+
+        let doc = r#"["a", "b"]"#;
+        assert_eq!(paths("$[1]", doc), ["$[1]"]);
+        assert_eq!(paths("$[-2]", doc), ["$[0]"]);
+        assert!(paths("$[2]", doc).is_empty());
+        assert!(paths("$[-3]", doc).is_empty());
+    }
+
+    #[test]
+    fn slices() {
+        let doc = r#"["a", "b", "c", "d", "e", "f", "g"]"#;
+        assert_eq!(paths("$[1:3]", doc), ["$[1]", "$[2]"]);
+        assert_eq!(paths("$[5:]", doc), ["$[5]", "$[6]"]);
+        assert_eq!(paths("$[1:5:2]", doc), ["$[1]", "$[3]"]);
+        assert_eq!(paths("$[5:1:-2]", doc), ["$[5]", "$[3]"]);
+        assert_eq!(
+            paths("$[::-1]", doc),
+            ["$[6]", "$[5]", "$[4]", "$[3]", "$[2]", "$[1]", "$[0]"]
+        );
+        assert_eq!(paths("$[-2:]", doc), ["$[5]", "$[6]"]);
+        assert_eq!(paths("$[:-5]", doc), ["$[0]", "$[1]"]);
+        assert_eq!(paths("$[-100:2]", doc), ["$[0]", "$[1]"]);
+        assert!(paths("$[1:5:0]", doc).is_empty());
+        assert!(paths("$[3:1]", doc).is_empty());
+        assert!(paths("$[0:2]", r#"{"0": 1}"#).is_empty());
+    }
+
+    #[test]
+    fn filters() {
+        let doc = r#"{
+            "a": [3, 5, 1, 2, 4, 6, {"b": "j"}, {"b": "k"}, {"b": {}}, {"b": "kilo"}],
+            "o": {"p": 1, "q": 2, "r": 3, "s": 5, "t": {"u": 6}},
+            "e": "f"
+        }"#;
+        assert_eq!(paths("$.a[?@.b == 'kilo']", doc), ["$['a'][9]"]);
+        assert_eq!(paths("$.a[?(@.b == 'kilo')]", doc), ["$['a'][9]"]);
+        assert_eq!(
+            paths("$.a[?@>3.5]", doc),
+            ["$['a'][1]", "$['a'][4]", "$['a'][5]"]
+        );
+        assert_eq!(
+            paths("$.a[?@.b]", doc),
+            ["$['a'][6]", "$['a'][7]", "$['a'][8]", "$['a'][9]"]
+        );
+        assert_eq!(paths("$[?@.*]", doc), ["$['a']", "$['o']"]);
+        assert_eq!(paths("$[?@[?@.b]]", doc), ["$['a']"]);
+        assert_eq!(
+            paths("$.o[?@<3, ?@<3]", doc),
+            ["$['o']['p']", "$['o']['q']", "$['o']['p']", "$['o']['q']"]
+        );
+        assert_eq!(
+            paths(r#"$.a[?@<2 || @.b == "k"]"#, doc),
+            ["$['a'][2]", "$['a'][7]"]
+        );
+        assert_eq!(
+            paths("$.o[?@>1 && @<4]", doc),
+            ["$['o']['q']", "$['o']['r']"]
+        );
+        assert_eq!(paths("$.o[?@.u || @.x]", doc), ["$['o']['t']"]);
+        assert_eq!(
+            paths("$.a[?@.b == $.x]", doc),
+            [
+                "$['a'][0]",
+                "$['a'][1]",
+                "$['a'][2]",
+                "$['a'][3]",
+                "$['a'][4]",
+                "$['a'][5]"
+            ]
+        );
+        assert_eq!(paths("$.a[?@ == @]", doc).len(), 10);
+        assert_eq!(paths("$.a[?!@.b]", doc).len(), 6);
+        assert_eq!(paths("$.a[?!(@ > 1 && @ < 6)]", doc).len(), 6);
+    }
+
+    #[test]
+    fn comparisons() {
+        // table 11 of the RFC, `true` when the filter selects the two members
+        let doc = r#"{"obj": {"x": "y"}, "arr": [2, 3]}"#;
+        let holds = |comparison: &str| match paths(&format!("$[?{}]", comparison), doc).len() {
+            2 => true,
+            0 => false,
+            n => panic!("{} selected {} nodes", comparison, n),
+        };
+        for comparison in [
+            "$.absent1 == $.absent2",
+            "$.absent1 <= $.absent2",
+            "$.absent != 'g'",
+            "1 <= 2",
+            "'a' <= 'b'",
+            "$.obj != $.arr",
+            "$.obj == $.obj",
+            "$.arr == $.arr",
+            "$.obj != 17",
+            "$.obj <= $.obj",
+            "$.arr <= $.arr",
+            "true <= true",
+            "1 == 1.0",
+            "$.arr[0] == 2",
+        ] {
+            assert!(holds(comparison), "{}", comparison);
+        }
+        for comparison in [
+            "$.absent == 'g'",
+            "$.absent1 != $.absent2",
+            "1 > 2",
+            "13 == '13'",
+            "'a' > 'b'",
+            "$.obj == $.arr",
+            "$.obj != $.obj",
+            "$.arr != $.arr",
+            "$.obj == 17",
+            "$.obj <= $.arr",
+            "$.obj < $.arr",
+            "1 <= $.arr",
+            "1 >= $.arr",
+            "1 > $.arr",
+            "1 < $.arr",
+            "true > true",
+        ] {
+            assert!(!holds(comparison), "{}", comparison);
+        }
+    }
+
+    #[test]
+    fn functions() {
+        let doc = r#"["ab", "abc", "été", [1, 2, 3], {"a": 1}, 12345, {"b": [1]}]"#;
+        assert_eq!(paths("$[?length(@) == 3]", doc), ["$[1]", "$[2]", "$[3]"]);
+        assert_eq!(paths("$[?length(@) < 2]", doc), ["$[4]", "$[6]"]);
+        assert_eq!(paths("$[?count(@.*) == 1]", doc), ["$[4]", "$[6]"]);
+        assert_eq!(paths("$[?count(@..*) == 2]", doc), ["$[6]"]);
+        assert_eq!(paths("$[?value(@.a) == 1]", doc), ["$[4]"]);
+        assert_eq!(
+            paths("$[?value(@..*) == 1]", r#"[{"a": 1}, {"a": {"b": 1}}]"#),
+            ["$[0]"]
+        );
+
+        let doc = r#"[{"timezone": "Europe/Paris"}, {"timezone": "America/Europe/x"},
+            {"timezone": 1}, {"d": "1974-05-01"}, {"d": "1974-05-011"}]"#;
+        assert_eq!(paths("$[?match(@.timezone, 'Europe/.*')]", doc), ["$[0]"]);
+        assert_eq!(
+            paths("$[?search(@.timezone, 'Europe/')]", doc),
+            ["$[0]", "$[1]"]
+        );
+        assert_eq!(paths("$[?match(@.d, '1974-05-..')]", doc), ["$[3]"]);
+        assert_eq!(paths("$[?search(@.d, '05-..$')]", doc), ["$[3]"]);
+        // the pattern can be a query too, an invalid one matches nothing
+        let doc = r#"[{"s": "abc", "p": "a.c"}, {"s": "abc", "p": "a(c"}]"#;
+        assert_eq!(paths("$[?match(@.s, @.p)]", doc), ["$[0]"]);
+        assert_eq!(paths("$[?!match(@.s, @.p)]", doc), ["$[1]"]);
+    }
+
+    #[test]
+    fn well_typed() {
+        // section 2.4.9 of the RFC
+        for valid in [
+            "$[?length(@) < 3]",
+            "$[?count(@.*) == 1]",
+            "$[?match(@.timezone, 'Europe/.*')]",
+            "$[?value(@..color) == \"red\"]",
+            "$[?length(@.a.b) == 1]",
+        ] {
+            assert!(JsonPath::parse(valid).is_ok(), "{}", valid);
+        }
+        for invalid in [
+            "$[?length(@.*) < 3]",
+            "$[?count(1) == 1]",
+            "$[?count(foo(@.*)) == 1]",
+            "$[?match(@.timezone, 'Europe/.*') == true]",
+            "$[?value(@..color)]",
+            "$[?bar(@.a)]",
+            "$[?length(@)]",
+            "$[?@.* == 1]",
+            "$[?1]",
+        ] {
+            assert!(JsonPath::parse(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn descendants() {
+        let doc = r#"{"o": {"j": 1, "k": 2}, "a": [5, 3, [{"j": 4}, {"k": 6}]]}"#;
+        assert_eq!(paths("$..j", doc), ["$['o']['j']", "$['a'][2][0]['j']"]);
+        assert_eq!(paths("$..[0]", doc), ["$['a'][0]", "$['a'][2][0]"]);
+        assert_eq!(
+            paths("$..[*]", doc),
+            [
+                "$['o']",
+                "$['a']",
+                "$['o']['j']",
+                "$['o']['k']",
+                "$['a'][0]",
+                "$['a'][1]",
+                "$['a'][2]",
+                "$['a'][2][0]",
+                "$['a'][2][1]",
+                "$['a'][2][0]['j']",
+                "$['a'][2][1]['k']",
+            ]
+        );
+        assert_eq!(paths("$..*", doc), paths("$..[*]", doc));
+        assert_eq!(paths("$..o", doc), ["$['o']"]);
+        assert_eq!(
+            paths("$.o..[*, *]", doc),
+            ["$['o']['j']", "$['o']['k']", "$['o']['j']", "$['o']['k']"]
+        );
+        assert_eq!(
+            paths("$.a..[0, 1]", doc),
+            ["$['a'][0]", "$['a'][1]", "$['a'][2][0]", "$['a'][2][1]"]
+        );
+        assert_eq!(paths("$..[?@.k]", doc), ["$['o']", "$['a'][2][1]"]);
+    }
+
+    #[test]
+    fn nulls() {
+        // section 2.6.1 of the RFC
+        let doc = r#"{"a": null, "b": [null], "c": [{}], "null": 1}"#;
+        assert_eq!(paths("$.a", doc), ["$['a']"]);
+        assert!(paths("$.a[0]", doc).is_empty());
+        assert!(paths("$.a.d", doc).is_empty());
+        assert_eq!(paths("$.b[0]", doc), ["$['b'][0]"]);
+        assert_eq!(paths("$.b[*]", doc), ["$['b'][0]"]);
+        assert_eq!(paths("$.b[?@]", doc), ["$['b'][0]"]);
+        assert_eq!(paths("$.b[?@==null]", doc), ["$['b'][0]"]);
+        assert!(paths("$.c[?@.d==null]", doc).is_empty());
+        assert_eq!(paths("$.null", doc), ["$['null']"]);
+    }
+
+    #[test]
+    fn syntax() {
+        for invalid in [
+            "",
+            " $",
+            "$ ",
+            "$.1",
+            "$[01]",
+            "$[-0]",
+            "$[9007199254740992]",
+            "$['a'",
+            "$[?@ == 1",
+            "$['\\x']",
+            "$.a[1:2:3:4]",
+            "$..",
+        ] {
+            assert!(JsonPath::parse(invalid).is_err(), "{:?}", invalid);
+        }
+        assert_eq!(
+            paths("$[ 'a' , \"b\" ]", r#"{"a": 1, "b": 2}"#),
+            ["$['a']", "$['b']"]
+        );
+        assert_eq!(paths("$['\\u263a']", r#"{"☺": 1}"#), ["$['☺']"]);
+        assert_eq!(paths("$['\\ud83d\\ude00']", r#"{"😀": 1}"#), ["$['😀']"]);
+        assert_eq!(paths("$.☺", r#"{"☺": 1}"#), ["$['☺']"]);
+        assert_eq!(
+            normalized_path(&[super::Step::Key("a\n\u{1}'\\".to_string())]),
+            r"$['a\n\u0001\'\\']"
+        );
+    }
+}
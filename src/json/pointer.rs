@@ -0,0 +1,78 @@
+//! JSON Pointer, RFC 6901.
+
+use serde_json::Value;
+
+/// A step from a value to one of its children.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Step {
+    Key(String),
+    Index(usize),
+}
+
+/// Parses `/a/0/b~1c` or its URI fragment form `#/a/0/b~1c` into reference tokens.
+pub fn parse(pointer: &str) -> anyhow::Result<Vec<String>> {
+    let decoded;
+    let pointer = match pointer.strip_prefix('#') {
+        Some(fragment) => {
+            decoded = String::from_utf8(crate::url::percent_decode(fragment))?;
+            decoded.as_str()
+        }
+        None => pointer,
+    };
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    }
+    let rest = pointer
+        .strip_prefix('/')
+        .ok_or_else(|| anyhow::anyhow!("JSON Pointer '{}' must start with '/'", pointer))?;
+
+    rest.split('/')
+        .map(|token| {
+            let mut unescaped = String::with_capacity(token.len());
+            let mut chars = token.chars();
+            while let Some(c) = chars.next() {
+                match c {
+                    '~' => match chars.next() {
+                        Some('0') => unescaped.push('~'),
+                        Some('1') => unescaped.push('/'),
+                        _ => anyhow::bail!("Invalid escape in JSON Pointer token '{}'", token),
+                    },
+                    c => unescaped.push(c),
+                }
+            }
+            Ok(unescaped)
+        })
+        .collect()
+}
+
+/// The array index a token refers to, `-` (past the end) is not one.
+pub fn index(token: &str) -> Option<usize> {
+    let digits = token.bytes().all(|b| b.is_ascii_digit());
+    let leading_zero = token.len() > 1 && token.starts_with('0');
+    match !token.is_empty() && digits && !leading_zero {
+        true => token.parse().ok(),
+        false => None,
+    }
+}
+
+pub fn get<'a>(value: &'a Value, tokens: &[String]) -> Option<&'a Value> {
+    tokens.iter().try_fold(value, |value, token| match value {
+        Value::Object(map) => map.get(token),
+        Value::Array(items) => items.get(index(token)?),
+        _ => None,
+    })
+}
+
+pub fn escape(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+/// The JSON Pointer of a location.
+pub fn format(path: &[Step]) -> String {
+    path.iter()
+        .map(|step| match step {
+            Step::Key(key) => format!("/{}", escape(key)),
+            Step::Index(i) => format!("/{}", i),
+        })
+        .collect()
+}
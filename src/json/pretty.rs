@@ -0,0 +1,198 @@
+//! The pretty printer of `json unminify`.
+
+use serde_json::Value;
+
+#[derive(clap::Args, Debug)]
+pub struct PrettyOptions {
+    /// Spaces per level, or `tab`
+    #[clap(long, value_name = "N|tab", default_value = "2", parse(try_from_str = parse_indent))]
+    indent: String,
+
+    /// Keep arrays and objects on one line when they fit in --width columns
+    #[clap(long)]
+    compact_arrays: bool,
+
+    /// The columns lines can take with --compact-arrays
+    #[clap(long, default_value = "80", requires = "compact-arrays")]
+    width: usize,
+
+    /// Escape every non-ASCII character as `\uXXXX`
+    #[clap(long)]
+    ascii: bool,
+}
+
+fn parse_indent(s: &str) -> Result<String, String> {
+    match (s, s.parse::<usize>()) {
+        ("tab", _) => Ok("\t".to_string()),
+        (_, Ok(n)) if n <= 16 => Ok(" ".repeat(n)),
+        _ => Err("must be a number of spaces up to 16, or `tab`".to_string()),
+    }
+}
+
+/// ANSI colours of the kinds of tokens, close to the ones of jq.
+const KEY: &str = "\x1b[34;1m";
+const STRING: &str = "\x1b[32m";
+const NUMBER: &str = "\x1b[36m";
+const LITERAL: &str = "\x1b[33m";
+const NULL: &str = "\x1b[90m";
+const RESET: &str = "\x1b[0m";
+
+pub struct Printer<'a> {
+    options: &'a PrettyOptions,
+    colour: bool,
+    out: String,
+}
+
+impl Printer<'_> {
+    /// Formats a value, with syntax highlighting when `colour` is set.
+    pub fn print(options: &PrettyOptions, colour: bool, value: &Value) -> String {
+        let mut printer = Printer {
+            options,
+            colour,
+            out: String::new(),
+        };
+        printer.value(value, 0, 0);
+        printer.out
+    }
+
+    fn paint(&mut self, colour: &str, text: &str) {
+        if self.colour {
+            self.out.push_str(colour);
+            self.out.push_str(text);
+            self.out.push_str(RESET);
+        } else {
+            self.out.push_str(text);
+        }
+    }
+
+    fn newline(&mut self, depth: usize) {
+        self.out.push('\n');
+        for _ in 0..depth {
+            self.out.push_str(&self.options.indent);
+        }
+    }
+
+    /// `column` is where the value starts on its line, to tell whether it fits on it.
+    fn value(&mut self, value: &Value, depth: usize, column: usize) {
+        match value {
+            Value::Null => self.paint(NULL, "null"),
+            Value::Bool(b) => self.paint(LITERAL, &b.to_string()),
+            // the literal as it was written, the numbers are not parsed into floats
+            Value::Number(n) => self.paint(NUMBER, &n.to_string()),
+            Value::String(s) => {
+                let quoted = quote(s, self.options.ascii);
+                self.paint(STRING, &quoted)
+            }
+            Value::Array(items) if items.is_empty() => self.out.push_str("[]"),
+            Value::Object(map) if map.is_empty() => self.out.push_str("{}"),
+            value if self.fits(value, column) => self.inline(value),
+            Value::Array(items) => {
+                self.out.push('[');
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        self.out.push(',');
+                    }
+                    self.newline(depth + 1);
+                    self.value(item, depth + 1, self.indent_width(depth + 1));
+                }
+                self.newline(depth);
+                self.out.push(']');
+            }
+            Value::Object(map) => {
+                self.out.push('{');
+                for (i, (key, value)) in map.iter().enumerate() {
+                    if i > 0 {
+                        self.out.push(',');
+                    }
+                    self.newline(depth + 1);
+                    let key = quote(key, self.options.ascii);
+                    self.paint(KEY, &key);
+                    self.out.push_str(": ");
+                    let column = self.indent_width(depth + 1) + key.chars().count() + 2;
+                    self.value(value, depth + 1, column);
+                }
+                self.newline(depth);
+                self.out.push('}');
+            }
+        }
+    }
+
+    fn indent_width(&self, depth: usize) -> usize {
+        // a tab counts as the usual 8 columns
+        let unit = match self.options.indent.as_str() {
+            "\t" => 8,
+            indent => indent.len(),
+        };
+        depth * unit
+    }
+
+    fn fits(&self, value: &Value, column: usize) -> bool {
+        if !self.options.compact_arrays {
+            return false;
+        }
+        let mut printer = Printer {
+            options: self.options,
+            colour: false,
+            out: String::new(),
+        };
+        printer.inline(value);
+        // the comma after the value takes a column too
+        column + printer.out.chars().count() < self.options.width
+    }
+
+    /// The value on a single line, `[1, 2]` and `{"a": 1}`.
+    fn inline(&mut self, value: &Value) {
+        match value {
+            Value::Array(items) => {
+                self.out.push('[');
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        self.out.push_str(", ");
+                    }
+                    self.inline(item);
+                }
+                self.out.push(']');
+            }
+            Value::Object(map) => {
+                self.out.push('{');
+                for (i, (key, value)) in map.iter().enumerate() {
+                    if i > 0 {
+                        self.out.push_str(", ");
+                    }
+                    let key = quote(key, self.options.ascii);
+                    self.paint(KEY, &key);
+                    self.out.push_str(": ");
+                    self.inline(value);
+                }
+                self.out.push('}');
+            }
+            value => self.value(value, 0, 0),
+        }
+    }
+}
+
+/// A JSON string literal, with non-ASCII characters escaped as UTF-16 when `ascii` is set.
+pub fn quote(s: &str, ascii: bool) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            '\u{8}' => quoted.push_str("\\b"),
+            '\u{c}' => quoted.push_str("\\f"),
+            c if (c as u32) < 0x20 || (ascii && !c.is_ascii()) => {
+                let mut units = [0; 2];
+                for unit in c.encode_utf16(&mut units) {
+                    quoted.push_str(&format!("\\u{:04x}", unit));
+                }
+            }
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
@@ -0,0 +1,120 @@
+//! `json query`, extracting values with JSONPath or JSON Pointer.
+
+use anyhow::Context;
+use serde_json::Value;
+
+use super::parse;
+use super::path::{normalized_path, JsonPath, Node};
+use super::pointer::{self, Step};
+use crate::input::{for_text_input, InputSource};
+
+#[derive(clap::Args, Debug)]
+pub struct QueryArgs {
+    /// JSONPath (RFC 9535) starting with `$`, or JSON Pointer (RFC 6901) starting with
+    /// `/` or `#`
+    expr: String,
+
+    #[clap(flatten)]
+    options: QueryOptions,
+
+    #[clap(flatten)]
+    input: InputSource,
+}
+
+#[derive(clap::Args, Debug)]
+struct QueryOptions {
+    /// Print strings without quotes and escapes
+    #[clap(long, short)]
+    raw_output: bool,
+
+    /// Print every match on a single line
+    #[clap(long, short)]
+    compact: bool,
+
+    /// Print where the matches are, as normalized paths or JSON Pointers, instead of
+    /// their values
+    #[clap(long, conflicts_with_all = &["raw-output", "compact"])]
+    paths: bool,
+}
+
+enum Expression {
+    Path(JsonPath),
+    Pointer(Vec<String>),
+}
+
+impl Expression {
+    fn parse(expr: &str) -> anyhow::Result<Expression> {
+        if expr.starts_with('$') {
+            Ok(Expression::Path(JsonPath::parse(expr)?))
+        } else if expr.is_empty() || expr.starts_with('/') || expr.starts_with('#') {
+            Ok(Expression::Pointer(pointer::parse(expr)?))
+        } else {
+            anyhow::bail!(
+                "'{}' is neither a JSONPath, starting with `$`, nor a JSON Pointer, starting with `/`",
+                expr
+            )
+        }
+    }
+
+    fn select<'a>(&self, json: &'a Value) -> Vec<Node<'a>> {
+        match self {
+            Expression::Path(path) => path.select(json),
+            Expression::Pointer(tokens) => match pointer::get(json, tokens) {
+                Some(value) => vec![Node {
+                    path: pointer_path(json, tokens),
+                    value,
+                }],
+                None => Vec::new(),
+            },
+        }
+    }
+
+    fn format_path(&self, path: &[Step]) -> String {
+        match self {
+            Expression::Path(_) => normalized_path(path),
+            Expression::Pointer(_) => pointer::format(path),
+        }
+    }
+}
+
+/// The steps of a pointer that resolves, the tokens are indices in arrays.
+fn pointer_path(json: &Value, tokens: &[String]) -> Vec<Step> {
+    let mut value = json;
+    let mut path = Vec::new();
+    for token in tokens {
+        let step = match value {
+            Value::Array(_) => Step::Index(pointer::index(token).unwrap_or_default()),
+            _ => Step::Key(token.clone()),
+        };
+        value = pointer::get(value, std::slice::from_ref(token)).unwrap_or(&Value::Null);
+        path.push(step);
+    }
+    path
+}
+
+pub fn run(args: QueryArgs) -> anyhow::Result<()> {
+    let expression = Expression::parse(&args.expr)?;
+    let options = &args.options;
+    let expr = &args.expr;
+
+    for_text_input(args.input, |input| {
+        let json: Value = parse(&input).context("Parse Valid JSON")?;
+        let nodes = expression.select(&json);
+        if nodes.is_empty() {
+            anyhow::bail!("Nothing matches '{}'", expr);
+        }
+
+        for node in nodes {
+            if options.paths {
+                println!("{}", expression.format_path(&node.path));
+                continue;
+            }
+            match node.value {
+                Value::String(s) if options.raw_output => println!("{}", s),
+                value if options.compact => println!("{}", serde_json::to_string(value)?),
+                value => println!("{}", serde_json::to_string_pretty(value)?),
+            }
+        }
+        Ok(())
+    })
+}
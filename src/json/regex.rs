@@ -0,0 +1,523 @@
+//! A backtracking regular expression engine for the ECMAScript subset used by JSON
+//! Schema `pattern` and the I-Regexp (RFC 9485) of JSONPath `match()` and `search()`.
+//!
+//! Supported: literals and escapes, `.`, classes with ranges and `\d \w \s` (and their
+//! negations), `\p{..}` for the general Unicode categories, `^ $ \b \B`, groups,
+//! alternation and greedy or lazy quantifiers. Backreferences and lookaround are not.
+
+use std::iter::Peekable;
+use std::str::Chars;
+
+#[derive(Debug)]
+pub struct Regex {
+    node: Node,
+}
+
+#[derive(Debug)]
+enum Node {
+    Empty,
+    Char(char),
+    /// `.`, anything but a line terminator.
+    Any,
+    Class(Class),
+    Start,
+    End,
+    WordBoundary(bool),
+    Concat(Vec<Node>),
+    Alternation(Vec<Node>),
+    Repeat {
+        node: Box<Node>,
+        min: usize,
+        max: Option<usize>,
+        greedy: bool,
+    },
+}
+
+#[derive(Debug)]
+struct Class {
+    negated: bool,
+    items: Vec<ClassItem>,
+}
+
+#[derive(Debug)]
+enum ClassItem {
+    Range(char, char),
+    /// A predefined class such as `\d`, or its negation such as `\D`.
+    Builtin(Builtin, bool),
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Builtin {
+    Digit,
+    Word,
+    Space,
+    Letter,
+    Uppercase,
+    Lowercase,
+    Number,
+    Punctuation,
+}
+
+impl Builtin {
+    fn matches(self, c: char) -> bool {
+        match self {
+            Builtin::Digit => c.is_ascii_digit(),
+            Builtin::Word => c.is_ascii_alphanumeric() || c == '_',
+            Builtin::Space => c.is_whitespace() || c == '\u{feff}',
+            Builtin::Letter => c.is_alphabetic(),
+            Builtin::Uppercase => c.is_uppercase(),
+            Builtin::Lowercase => c.is_lowercase(),
+            Builtin::Number => c.is_numeric(),
+            Builtin::Punctuation => c.is_ascii_punctuation(),
+        }
+    }
+}
+
+impl Class {
+    fn matches(&self, c: char) -> bool {
+        let found = self.items.iter().any(|item| match *item {
+            ClassItem::Range(from, to) => from <= c && c <= to,
+            ClassItem::Builtin(builtin, negated) => builtin.matches(c) != negated,
+        });
+        found != self.negated
+    }
+}
+
+impl Regex {
+    pub fn new(pattern: &str) -> anyhow::Result<Regex> {
+        let mut parser = Parser {
+            chars: pattern.chars().peekable(),
+        };
+        let node = parser.alternation()?;
+        if let Some(c) = parser.chars.next() {
+            anyhow::bail!(
+                "Invalid regular expression '{}': unexpected '{}'",
+                pattern,
+                c
+            );
+        }
+        Ok(Regex { node })
+    }
+
+    /// Whether the pattern matches anywhere in the text, as in ECMAScript.
+    pub fn is_match(&self, text: &str) -> bool {
+        let chars: Vec<char> = text.chars().collect();
+        (0..=chars.len()).any(|start| match_node(&self.node, &chars, start, &mut |_| true))
+    }
+
+    /// Whether the pattern matches the whole text.
+    pub fn is_full_match(&self, text: &str) -> bool {
+        let chars: Vec<char> = text.chars().collect();
+        match_node(&self.node, &chars, 0, &mut |end| end == chars.len())
+    }
+}
+
+/// Matches `node` at `pos` and calls `next` with every position it can end at, until
+/// `next` accepts one.
+fn match_node(node: &Node, text: &[char], pos: usize, next: &mut dyn FnMut(usize) -> bool) -> bool {
+    let current = text.get(pos).copied();
+    match node {
+        Node::Empty => next(pos),
+        Node::Char(c) => current == Some(*c) && next(pos + 1),
+        Node::Any => current.is_some_and(|c| !is_line_terminator(c)) && next(pos + 1),
+        Node::Class(class) => current.is_some_and(|c| class.matches(c)) && next(pos + 1),
+        Node::Start => pos == 0 && next(pos),
+        Node::End => pos == text.len() && next(pos),
+        Node::WordBoundary(expected) => {
+            let word = |i: Option<&char>| i.is_some_and(|&c| Builtin::Word.matches(c));
+            let before = pos.checked_sub(1).and_then(|i| text.get(i));
+            (word(before) != word(text.get(pos))) == *expected && next(pos)
+        }
+        Node::Concat(nodes) => match_sequence(nodes, text, pos, next),
+        Node::Alternation(nodes) => nodes
+            .iter()
+            .any(|node| match_node(node, text, pos, &mut *next)),
+        Node::Repeat {
+            node,
+            min,
+            max,
+            greedy,
+        } => match_repeat(node, (*min, *max, *greedy), 0, text, pos, next),
+    }
+}
+
+fn match_sequence(
+    nodes: &[Node],
+    text: &[char],
+    pos: usize,
+    next: &mut dyn FnMut(usize) -> bool,
+) -> bool {
+    match nodes.split_first() {
+        None => next(pos),
+        Some((first, rest)) => match_node(first, text, pos, &mut |end| {
+            match_sequence(rest, text, end, &mut *next)
+        }),
+    }
+}
+
+fn match_repeat(
+    node: &Node,
+    bounds: (usize, Option<usize>, bool),
+    count: usize,
+    text: &[char],
+    pos: usize,
+    next: &mut dyn FnMut(usize) -> bool,
+) -> bool {
+    let (min, max, greedy) = bounds;
+    let once_more = |next: &mut dyn FnMut(usize) -> bool| {
+        max.is_none_or(|max| count < max)
+            && match_node(node, text, pos, &mut |end| {
+                // an empty iteration can not make progress once the minimum is reached
+                (end != pos || count < min)
+                    && match_repeat(node, bounds, count + 1, text, end, &mut *next)
+            })
+    };
+
+    if count < min {
+        once_more(next)
+    } else if greedy {
+        once_more(&mut *next) || next(pos)
+    } else {
+        next(pos) || once_more(next)
+    }
+}
+
+fn is_line_terminator(c: char) -> bool {
+    matches!(c, '\n' | '\r' | '\u{2028}' | '\u{2029}')
+}
+
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+
+impl Parser<'_> {
+    fn alternation(&mut self) -> anyhow::Result<Node> {
+        let mut alternatives = vec![self.concatenation()?];
+        while self.chars.next_if_eq(&'|').is_some() {
+            alternatives.push(self.concatenation()?);
+        }
+        Ok(match alternatives.len() {
+            1 => alternatives.remove(0),
+            _ => Node::Alternation(alternatives),
+        })
+    }
+
+    fn concatenation(&mut self) -> anyhow::Result<Node> {
+        let mut nodes = Vec::new();
+        while let Some(&c) = self.chars.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            let atom = self.atom()?;
+            nodes.push(self.quantified(atom)?);
+        }
+        Ok(match nodes.len() {
+            0 => Node::Empty,
+            1 => nodes.remove(0),
+            _ => Node::Concat(nodes),
+        })
+    }
+
+    fn atom(&mut self) -> anyhow::Result<Node> {
+        let c = self.chars.next().unwrap_or_default();
+        Ok(match c {
+            '.' => Node::Any,
+            '^' => Node::Start,
+            '$' => Node::End,
+            '[' => Node::Class(self.class()?),
+            '(' => {
+                // only non-capturing groups are distinguished, nothing is captured
+                if self.chars.next_if_eq(&'?').is_some() && self.chars.next() != Some(':') {
+                    anyhow::bail!("Lookaround and named groups are not supported");
+                }
+                let node = self.alternation()?;
+                if self.chars.next() != Some(')') {
+                    anyhow::bail!("Missing ')'");
+                }
+                node
+            }
+            '\\' => match self.chars.peek() {
+                Some('b') => {
+                    self.chars.next();
+                    Node::WordBoundary(true)
+                }
+                Some('B') => {
+                    self.chars.next();
+                    Node::WordBoundary(false)
+                }
+                _ => match self.escape()? {
+                    ClassItem::Range(c, _) => Node::Char(c),
+                    builtin => Node::Class(Class {
+                        negated: false,
+                        items: vec![builtin],
+                    }),
+                },
+            },
+            '*' | '+' | '?' => anyhow::bail!("Nothing to repeat before '{}'", c),
+            c => Node::Char(c),
+        })
+    }
+
+    fn quantified(&mut self, atom: Node) -> anyhow::Result<Node> {
+        let (min, max) = match self.chars.peek() {
+            Some('*') => (0, None),
+            Some('+') => (1, None),
+            Some('?') => (0, Some(1)),
+            Some('{') => match self.bounds() {
+                Some(bounds) => bounds,
+                // a `{` that does not start a quantifier is a literal
+                None => return Ok(atom),
+            },
+            _ => return Ok(atom),
+        };
+        // the quantifier itself, or the `}` closing its bounds
+        self.chars.next();
+        if max.is_some_and(|max| max < min) {
+            anyhow::bail!("Quantifier range out of order");
+        }
+        let greedy = self.chars.next_if_eq(&'?').is_none();
+        Ok(Node::Repeat {
+            node: Box::new(atom),
+            min,
+            max,
+            greedy,
+        })
+    }
+
+    /// `{n}`, `{n,}` or `{n,m}`, consumed up to the closing `}` which is left for the caller.
+    fn bounds(&mut self) -> Option<(usize, Option<usize>)> {
+        let rest: String = self.chars.clone().take_while(|&c| c != '}').collect();
+        let (min, max) = match rest.strip_prefix('{')?.split_once(',') {
+            Some((min, "")) => (min.parse().ok()?, None),
+            Some((min, max)) => (min.parse().ok()?, Some(max.parse().ok()?)),
+            None => {
+                let n = rest[1..].parse().ok()?;
+                (n, Some(n))
+            }
+        };
+        let len = rest.chars().count();
+        if self.chars.clone().nth(len) != Some('}') {
+            return None;
+        }
+        for _ in 0..len {
+            self.chars.next();
+        }
+        Some((min, max))
+    }
+
+    fn class(&mut self) -> anyhow::Result<Class> {
+        let negated = self.chars.next_if_eq(&'^').is_some();
+        let mut items = Vec::new();
+        let mut first = true;
+        loop {
+            let item = match self.chars.next() {
+                None => anyhow::bail!("Missing ']'"),
+                Some(']') if !first => break,
+                Some('\\') => self.escape()?,
+                Some(c) => ClassItem::Range(c, c),
+            };
+            first = false;
+
+            // a `-` between two characters is a range, anywhere else it is literal
+            let item = match item {
+                ClassItem::Range(from, _) if self.chars.peek() == Some(&'-') => {
+                    let mut lookahead = self.chars.clone();
+                    lookahead.next();
+                    match lookahead.peek() {
+                        Some(']') | None => item,
+                        Some(_) => {
+                            self.chars.next();
+                            let to = match self.chars.next() {
+                                Some('\\') => match self.escape()? {
+                                    ClassItem::Range(to, _) => to,
+                                    _ => anyhow::bail!("Invalid class range"),
+                                },
+                                Some(to) => to,
+                                None => anyhow::bail!("Missing ']'"),
+                            };
+                            if to < from {
+                                anyhow::bail!("Class range out of order");
+                            }
+                            ClassItem::Range(from, to)
+                        }
+                    }
+                }
+                item => item,
+            };
+            items.push(item);
+        }
+        Ok(Class { negated, items })
+    }
+
+    /// The escape after a `\`, a single character as a one character range.
+    fn escape(&mut self) -> anyhow::Result<ClassItem> {
+        let c = self
+            .chars
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("Trailing '\\'"))?;
+        let builtin = |b, negated| Ok(ClassItem::Builtin(b, negated));
+        let c = match c {
+            'd' => return builtin(Builtin::Digit, false),
+            'D' => return builtin(Builtin::Digit, true),
+            'w' => return builtin(Builtin::Word, false),
+            'W' => return builtin(Builtin::Word, true),
+            's' => return builtin(Builtin::Space, false),
+            'S' => return builtin(Builtin::Space, true),
+            'p' | 'P' => return self.category(c == 'P'),
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            'f' => '\u{c}',
+            'v' => '\u{b}',
+            '0' => '\0',
+            'x' => self.hex(2)?,
+            'u' if self.chars.next_if_eq(&'{').is_some() => {
+                let hex: String = self.chars.by_ref().take_while(|&c| c != '}').collect();
+                char_from_hex(&hex)?
+            }
+            'u' => self.hex(4)?,
+            c if c.is_ascii_alphanumeric() => anyhow::bail!("Unsupported escape '\\{}'", c),
+            c => c,
+        };
+        Ok(ClassItem::Range(c, c))
+    }
+
+    fn hex(&mut self, len: usize) -> anyhow::Result<char> {
+        let hex: String = self.chars.by_ref().take(len).collect();
+        char_from_hex(&hex)
+    }
+
+    fn category(&mut self, negated: bool) -> anyhow::Result<ClassItem> {
+        if self.chars.next() != Some('{') {
+            anyhow::bail!("Expected '{{' after \\p");
+        }
+        let name: String = self.chars.by_ref().take_while(|&c| c != '}').collect();
+        let builtin = match name.as_str() {
+            "L" | "Letter" => Builtin::Letter,
+            "Lu" | "Uppercase_Letter" => Builtin::Uppercase,
+            "Ll" | "Lowercase_Letter" => Builtin::Lowercase,
+            "N" | "Nd" | "Number" => Builtin::Number,
+            "P" | "Punctuation" => Builtin::Punctuation,
+            _ => anyhow::bail!("Unsupported Unicode category '{}'", name),
+        };
+        Ok(ClassItem::Builtin(builtin, negated))
+    }
+}
+
+fn char_from_hex(hex: &str) -> anyhow::Result<char> {
+    u32::from_str_radix(hex, 16)
+        .ok()
+        .and_then(char::from_u32)
+        .ok_or_else(|| anyhow::anyhow!("Invalid escape '{}'", hex))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Regex;
+
+    fn full(pattern: &str, text: &str) -> bool {
+        Regex::new(pattern).unwrap().is_full_match(text)
+    }
+
+    fn search(pattern: &str, text: &str) -> bool {
+        Regex::new(pattern).unwrap().is_match(text)
+    }
+
+    #[test]
+    fn quantifiers() {
+        let cases = [
+            ("a{2}", ["aa"].as_slice(), ["a", "aaa"].as_slice()),
+            ("a{2,}", &["aa", "aaaaa"], &["", "a"]),
+            ("a{2,3}", &["aa", "aaa"], &["a", "aaaa"]),
+            ("a{0}b", &["b"], &["ab"]),
+            ("a{0,1}b", &["b", "ab"], &["aab"]),
+            ("a*", &["", "aaaa"], &["b"]),
+            ("a+", &["a", "aa"], &[""]),
+            ("a?b", &["b", "ab"], &["aab"]),
+            ("(ab)+", &["ab", "ababab"], &["aba", ""]),
+            ("(?:a|bc){2}d", &["aad", "bcad", "bcbcd"], &["ad", "abd"]),
+            ("a+?b", &["aaab"], &["b"]),
+            ("x{10}", &["xxxxxxxxxx"], &["xxxxxxxxx"]),
+            // a brace that is not a quantifier is a literal
+            ("a{,2}", &["a{,2}"], &["aa"]),
+            ("a{2", &["a{2"], &["aa"]),
+            ("a{x}", &["a{x}"], &["a"]),
+        ];
+        for (pattern, matching, other) in cases {
+            for text in matching {
+                assert!(full(pattern, text), "{} {:?}", pattern, text);
+            }
+            for text in other {
+                assert!(!full(pattern, text), "{} {:?}", pattern, text);
+            }
+        }
+        for invalid in ["a{3,2}", "*a", "a|+", "(?=a)", "(?<n>a)"] {
+            assert!(Regex::new(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn classes() {
+        let cases = [
+            (
+                "[a-c]",
+                ["a", "b", "c"].as_slice(),
+                ["d", "A", ""].as_slice(),
+            ),
+            ("[^a-c]", &["d", "é"], &["a", ""]),
+            ("[a-]", &["a", "-"], &["b"]),
+            ("[-a]", &["a", "-"], &["b"]),
+            ("[a\\-z]", &["a", "-", "z"], &["b"]),
+            ("[\\]x]", &["]", "x"], &["\\"]),
+            ("[\\d.]", &["7", "."], &["a"]),
+            ("[\\d-z]", &["1", "-", "z"], &["y"]),
+            ("[\\u0041-\\u0043]", &["A", "C"], &["D"]),
+            ("\\d\\D", &["1a"], &["11", "١a"]),
+            ("\\w\\W", &["_ "], &["é "]),
+            ("\\s\\S", &["\tx", "\u{a0}x", "\u{feff}x"], &["xx"]),
+            ("\\p{Lu}\\p{Ll}", &["Ab", "Éé"], &["aB"]),
+            ("\\P{L}", &["1", " "], &["a", "ж"]),
+            ("[\\p{L}\\d]+", &["añ9"], &["a_"]),
+            ("\\p{Nd}", &["7"], &["x"]),
+            ("\\x41\\u00e9\\u{1F600}", &["Aé😀"], &["Ae😀"]),
+            // `.` is a character, not a byte, and not a line terminator
+            (".", &["é", "😀", "\t"], &["\n", "\r", "\u{2028}", "ab"]),
+        ];
+        for (pattern, matching, other) in cases {
+            for text in matching {
+                assert!(full(pattern, text), "{} {:?}", pattern, text);
+            }
+            for text in other {
+                assert!(!full(pattern, text), "{} {:?}", pattern, text);
+            }
+        }
+        for invalid in ["[a", "[z-a]", "[a-\\d]", "\\p{Xx}", "\\pL", "\\k", "\\"] {
+            assert!(Regex::new(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn anchoring() {
+        // `match()` and JSON Schema use the whole text, `search()` any part of it
+        assert!(search("b", "abc"));
+        assert!(!full("b", "abc"));
+        assert!(full("a|bc", "bc"));
+        assert!(!full("a|bc", "abc"));
+        assert!(search("a|bc", "xbc"));
+        assert!(!search("^b", "abc"));
+        assert!(search("^a", "abc"));
+        assert!(search("c$", "abc"));
+        assert!(!search("b$", "abc"));
+        assert!(full("^abc$", "abc"));
+        assert!(!search("^$", "a"));
+        assert!(search("", "a"));
+        assert!(full("", ""));
+        assert!(search("\\bfoo\\b", "a foo b"));
+        assert!(!search("\\bfoo\\b", "afoo"));
+        assert!(search("\\Boo", "foo"));
+        assert!(!search("\\Bfoo", "foo"));
+        // `^` and `$` are the ends of the text, not of its lines
+        assert!(!search("^b", "a\nb"));
+        assert!(!search("a$", "a\nb"));
+    }
+}
@@ -0,0 +1,72 @@
+//! `json repair`, strict JSON from relaxed or slightly broken JSON.
+
+use anyhow::Context;
+use serde_json::Value;
+
+use super::source_name;
+use super::syntax::{self, Lines};
+use crate::input::{decode_utf8, read_all, InputSource};
+
+#[derive(clap::Args, Debug)]
+pub struct RepairArgs {
+    /// Print the document on a single line
+    #[clap(long)]
+    minify: bool,
+
+    #[clap(flatten)]
+    input: InputSource,
+}
+
+pub fn run(args: RepairArgs) -> anyhow::Result<()> {
+    let name = source_name(&args.input);
+    let text = decode_utf8(read_all(args.input)?)?;
+
+    let value = repair(&text, &name)?;
+    match args.minify {
+        true => println!("{}", serde_json::to_string(&value)?),
+        false => println!("{}", serde_json::to_string_pretty(&value)?),
+    }
+    Ok(())
+}
+
+/// The repaired document, what was repaired goes to stderr to keep the document apart.
+fn repair(text: &str, name: &str) -> anyhow::Result<Value> {
+    let mut parsed = syntax::parse(text);
+    parsed.problems.sort_by_key(|problem| problem.offset);
+    let lines = Lines::new(text);
+    for problem in &parsed.problems {
+        let position = lines.position(problem.offset);
+        eprintln!(
+            "{}:{}:{}: {}",
+            name, position.line, position.column, problem.message
+        );
+    }
+
+    parsed
+        .value
+        .context("There is no document to repair in the input")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::repair;
+    use serde_json::json;
+
+    #[test]
+    fn documents() {
+        assert_eq!(
+            repair("{a: 'b', c: [True, None, NaN,], // done\n}", "<input>").unwrap(),
+            json!({"a": "b", "c": [true, null, null]})
+        );
+        assert_eq!(
+            repair(r#"{"a": 1 "b": [1, 2,, 3], "c": tru"#, "<input>").unwrap(),
+            json!({"a": 1, "b": [1, 2, 3], "c": true})
+        );
+        assert_eq!(
+            repair("[0x10, .5, 'it\\'s', 01]", "<input>").unwrap(),
+            json!([16, 0.5, "it's", 1])
+        );
+        assert!(repair("", "<input>").is_err());
+        assert!(repair("  // nothing but a comment\n", "<input>").is_err());
+    }
+}
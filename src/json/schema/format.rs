@@ -0,0 +1,104 @@
+//! The `format` values that are checked with `--formats`, others always pass.
+
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use chrono::{DateTime, NaiveDate};
+
+use super::super::pointer;
+use super::super::regex::Regex;
+
+pub fn is_valid(format: &str, s: &str) -> bool {
+    match format {
+        "date-time" => is_date_time(s),
+        "date" => is_date(s),
+        "time" => is_date_time(&format!("1970-01-01T{}", s)),
+        "email" => is_email(s),
+        "hostname" => is_hostname(s),
+        "ipv4" => s.parse::<Ipv4Addr>().is_ok(),
+        "ipv6" => s.parse::<Ipv6Addr>().is_ok(),
+        "uri" => is_uri(s),
+        "uri-reference" => s.chars().all(is_uri_char),
+        "uuid" => is_uuid(s),
+        "regex" => Regex::new(s).is_ok(),
+        "json-pointer" => (s.is_empty() || s.starts_with('/')) && pointer::parse(s).is_ok(),
+        _ => true,
+    }
+}
+
+/// RFC 3339 `date-time`, which needs the `T` and the offset.
+fn is_date_time(s: &str) -> bool {
+    let separator = s.as_bytes().get(10).copied();
+    matches!(separator, Some(b'T' | b't'))
+        && s.get(..10).is_some_and(is_date)
+        && DateTime::parse_from_rfc3339(s).is_ok()
+}
+
+/// RFC 3339 `full-date`, `2022-02-28`.
+fn is_date(s: &str) -> bool {
+    let shape = s.len() == 10
+        && s.bytes()
+            .enumerate()
+            .all(|(i, b)| matches!(i, 4 | 7) == (b == b'-') && (b == b'-' || b.is_ascii_digit()));
+    shape && NaiveDate::parse_from_str(s, "%Y-%m-%d").is_ok()
+}
+
+fn is_email(s: &str) -> bool {
+    match s.rsplit_once('@') {
+        Some((local, domain)) => {
+            let local_valid = !local.is_empty()
+                && local.len() <= 64
+                && !local.starts_with('.')
+                && !local.ends_with('.')
+                && !local.contains("..")
+                && local
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "!#$%&'*+-/=?^_`{|}~.".contains(c));
+            let domain_valid = match domain.strip_prefix('[').and_then(|d| d.strip_suffix(']')) {
+                Some(address) => match address.strip_prefix("IPv6:") {
+                    Some(address) => address.parse::<Ipv6Addr>().is_ok(),
+                    None => address.parse::<Ipv4Addr>().is_ok(),
+                },
+                None => is_hostname(domain),
+            };
+            local_valid && domain_valid
+        }
+        None => false,
+    }
+}
+
+fn is_hostname(s: &str) -> bool {
+    let s = s.strip_suffix('.').unwrap_or(s);
+    !s.is_empty()
+        && s.len() <= 253
+        && s.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+}
+
+fn is_uri(s: &str) -> bool {
+    let scheme = s.split_once(':').map(|(scheme, _)| scheme);
+    let scheme_valid = scheme.is_some_and(|scheme| {
+        scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+    });
+    scheme_valid && s.chars().all(is_uri_char)
+}
+
+/// The characters a URI can have, anything else has to be percent-encoded.
+fn is_uri_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "-._~:/?#[]@!$&'()*+,;=%".contains(c)
+}
+
+fn is_uuid(s: &str) -> bool {
+    s.len() == 36
+        && s.char_indices().all(|(i, c)| match i {
+            8 | 13 | 18 | 23 => c == '-',
+            _ => c.is_ascii_hexdigit(),
+        })
+}
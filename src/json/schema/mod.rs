@@ -0,0 +1,864 @@
+//! JSON Schema validation, drafts 7 and 2020-12.
+
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+
+use serde_json::{Map, Value};
+
+use super::pointer::{self, Step};
+use super::regex::Regex;
+use super::values_equal;
+use registry::{Registry, Target};
+
+pub mod format;
+mod registry;
+
+pub use registry::LoadError;
+
+#[derive(clap::ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Draft {
+    #[clap(name = "7")]
+    Draft7,
+    #[clap(name = "2020-12")]
+    Draft2020,
+}
+
+impl Draft {
+    /// The draft a `$schema` names, 2019-09 is close enough to 2020-12 and draft 6 to 7.
+    pub fn from_uri(uri: &str) -> Option<Draft> {
+        let uri = uri.trim_end_matches('#');
+        match uri.split_once("://").map(|(_, rest)| rest) {
+            Some("json-schema.org/draft-07/schema" | "json-schema.org/draft-06/schema") => {
+                Some(Draft::Draft7)
+            }
+            Some(
+                "json-schema.org/draft/2020-12/schema" | "json-schema.org/draft/2019-09/schema",
+            ) => Some(Draft::Draft2020),
+            _ => None,
+        }
+    }
+}
+
+/// A keyword the instance does not satisfy.
+pub struct Violation {
+    pub instance: Vec<Step>,
+    /// Where the keyword is, `#/properties/id/type` or `other.json#/type`.
+    pub keyword: String,
+    pub message: String,
+}
+
+pub struct Validator {
+    draft: Draft,
+    formats: bool,
+    registry: Registry,
+    regexes: RefCell<HashMap<String, Option<Regex>>>,
+}
+
+/// What a schema found, and the properties and items it evaluated for the
+/// `unevaluated*` keywords.
+#[derive(Default)]
+struct Outcome {
+    violations: Vec<Violation>,
+    properties: HashSet<String>,
+    items: HashSet<usize>,
+}
+
+impl Outcome {
+    fn is_valid(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    /// Takes the violations and, when `annotations` is set, what was evaluated.
+    fn merge(&mut self, other: Outcome, annotations: bool) {
+        self.violations.extend(other.violations);
+        if annotations {
+            self.properties.extend(other.properties);
+            self.items.extend(other.items);
+        }
+    }
+}
+
+/// Where the validation is, in the schema and in the instance.
+struct Context<'a> {
+    target: Target,
+    instance: &'a [Step],
+    /// The resources entered so far, outermost first, for `$dynamicRef`.
+    scope: &'a [String],
+}
+
+impl Validator {
+    /// `uri` is where the schema is from, for the references relative to it.
+    pub fn new(
+        schema: Value,
+        uri: String,
+        draft: Draft,
+        formats: bool,
+    ) -> Result<Validator, LoadError> {
+        Ok(Validator {
+            draft,
+            formats,
+            registry: Registry::load(schema, uri.clone(), draft)?,
+            regexes: RefCell::new(HashMap::new()),
+        })
+    }
+
+    pub fn validate(&self, instance: &Value) -> Vec<Violation> {
+        let root = Target {
+            doc: 0,
+            pointer: String::new(),
+            base: self.registry.documents[0].uri.clone(),
+        };
+        let scope = [root.base.clone()];
+        let context = Context {
+            target: root,
+            instance: &[],
+            scope: &scope,
+        };
+        self.schema(&context, instance).violations
+    }
+
+    fn location(&self, target: &Target, keyword: &str) -> String {
+        let name = &self.registry.documents[target.doc].name;
+        match keyword {
+            "" => format!("{}#{}", name, target.pointer),
+            keyword => format!("{}#{}/{}", name, target.pointer, pointer::escape(keyword)),
+        }
+    }
+
+    fn is_match(&self, pattern: &str, text: &str) -> bool {
+        let mut regexes = self.regexes.borrow_mut();
+        // the patterns were checked when loading the schema
+        let regex = regexes
+            .entry(pattern.to_string())
+            .or_insert_with(|| Regex::new(pattern).ok());
+        regex.as_ref().is_some_and(|r| r.is_match(text))
+    }
+
+    fn schema(&self, cx: &Context, instance: &Value) -> Outcome {
+        let mut outcome = Outcome::default();
+        let map = match self.registry.schema(&cx.target) {
+            Value::Object(map) => map,
+            Value::Bool(false) => {
+                outcome.violations.push(Violation {
+                    instance: cx.instance.to_vec(),
+                    keyword: self.location(&cx.target, ""),
+                    message: "no value is allowed here".to_string(),
+                });
+                return outcome;
+            }
+            _ => return outcome,
+        };
+
+        // an `$id` starts a new resource, that references are relative to
+        let mut target = cx.target.clone();
+        let mut scope = cx.scope.to_vec();
+        let draft7_ref = self.draft == Draft::Draft7 && map.contains_key("$ref");
+        if let Some(Value::String(id)) = map.get("$id").filter(|_| !draft7_ref) {
+            if !(self.draft == Draft::Draft7 && id.starts_with('#')) {
+                let (base, _) = registry::split_fragment(&registry::resolve(&target.base, id));
+                target.base = base.clone();
+                scope.push(base);
+            }
+        }
+        let cx = Context {
+            target,
+            instance: cx.instance,
+            scope: &scope,
+        };
+
+        let mut keywords = Keywords {
+            validator: self,
+            cx: &cx,
+            map,
+            instance,
+            outcome,
+        };
+        if let Some(Value::String(reference)) = map.get("$ref") {
+            keywords.reference(reference, false);
+            // the other keywords are ignored next to a draft 7 reference
+            if self.draft == Draft::Draft7 {
+                return keywords.outcome;
+            }
+        }
+        if let Some(Value::String(reference)) = map.get("$dynamicRef") {
+            keywords.reference(reference, true);
+        }
+        keywords.apply();
+        keywords.outcome
+    }
+}
+
+/// The keywords of one schema object applied to an instance.
+struct Keywords<'a> {
+    validator: &'a Validator,
+    cx: &'a Context<'a>,
+    map: &'a Map<String, Value>,
+    instance: &'a Value,
+    outcome: Outcome,
+}
+
+impl Keywords<'_> {
+    fn draft7(&self) -> bool {
+        self.validator.draft == Draft::Draft7
+    }
+
+    fn violation(&mut self, keyword: &str, message: String) {
+        self.violation_at(keyword, None, message);
+    }
+
+    /// A violation by a child of the instance, such as a property that is not allowed.
+    fn violation_at(&mut self, keyword: &str, child: Option<Step>, message: String) {
+        let mut instance = self.cx.instance.to_vec();
+        instance.extend(child);
+        self.outcome.violations.push(Violation {
+            instance,
+            keyword: self.validator.location(&self.cx.target, keyword),
+            message,
+        });
+    }
+
+    /// Validates the instance, or one of its children, against a subschema at `suffix`.
+    fn subschema(&self, suffix: &str, child: Option<(Step, &Value)>) -> Outcome {
+        let mut target = self.cx.target.clone();
+        target.pointer.push_str(suffix);
+        let mut instance_path = self.cx.instance.to_vec();
+        let instance = match child {
+            Some((step, value)) => {
+                instance_path.push(step);
+                value
+            }
+            None => self.instance,
+        };
+        let cx = Context {
+            target,
+            instance: &instance_path,
+            scope: self.cx.scope,
+        };
+        self.validator.schema(&cx, instance)
+    }
+
+    fn reference(&mut self, reference: &str, dynamic: bool) {
+        let registry = &self.validator.registry;
+        let mut target = match registry.resolve(&self.cx.target.base, reference) {
+            Some(target) => target,
+            // the references were all resolved when loading the schema
+            None => return,
+        };
+
+        // a `$dynamicRef` to a `$dynamicAnchor` goes to the outermost resource with the same one
+        if dynamic {
+            let anchor = reference.rsplit_once('#').map(|(_, name)| name);
+            let declared = registry.schema(&target).get("$dynamicAnchor");
+            if let Some(name) = anchor.filter(|name| declared.and_then(Value::as_str) == Some(name))
+            {
+                let outermost = self
+                    .cx
+                    .scope
+                    .iter()
+                    .find(|resource| registry.has_dynamic_anchor(resource, name))
+                    .and_then(|resource| registry.resolve(resource, &format!("#{}", name)));
+                if let Some(outermost) = outermost {
+                    target = outermost;
+                }
+            }
+        }
+
+        let mut scope = self.cx.scope.to_vec();
+        if scope.last() != Some(&target.base) {
+            scope.push(target.base.clone());
+        }
+        let cx = Context {
+            target,
+            instance: self.cx.instance,
+            scope: &scope,
+        };
+        let outcome = self.validator.schema(&cx, self.instance);
+        self.outcome.merge(outcome, true);
+    }
+
+    fn apply(&mut self) {
+        self.generic();
+        self.applicators();
+        match self.instance {
+            Value::Number(_) => self.number(),
+            Value::String(s) => self.string(s),
+            Value::Array(items) => self.array(items),
+            Value::Object(map) => self.object(map),
+            _ => {}
+        }
+        // after everything else, as they depend on what the others evaluated
+        if !self.draft7() {
+            match self.instance {
+                Value::Array(items) => self.unevaluated_items(items),
+                Value::Object(map) => self.unevaluated_properties(map),
+                _ => {}
+            }
+        }
+    }
+
+    fn generic(&mut self) {
+        if let Some(expected) = self.map.get("type") {
+            let names: Vec<&str> = match expected {
+                Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+                Value::String(name) => vec![name.as_str()],
+                _ => Vec::new(),
+            };
+            if !names.iter().any(|name| has_type(self.instance, name)) {
+                let message = format!(
+                    "expected {}, found {}",
+                    names.join(" or "),
+                    type_name(self.instance)
+                );
+                self.violation("type", message);
+            }
+        }
+        if let Some(Value::Array(allowed)) = self.map.get("enum") {
+            if !allowed.iter().any(|v| values_equal(v, self.instance)) {
+                let message = format!("must be one of {}", short(&Value::Array(allowed.clone())));
+                self.violation("enum", message);
+            }
+        }
+        if let Some(expected) = self.map.get("const") {
+            if !values_equal(expected, self.instance) {
+                self.violation("const", format!("must be {}", short(expected)));
+            }
+        }
+    }
+
+    fn applicators(&mut self) {
+        if let Some(Value::Array(all)) = self.map.get("allOf") {
+            for i in 0..all.len() {
+                let outcome = self.subschema(&format!("/allOf/{}", i), None);
+                self.outcome.merge(outcome, true);
+            }
+        }
+        for keyword in ["anyOf", "oneOf"] {
+            let count = match self.map.get(keyword) {
+                Some(Value::Array(all)) => all.len(),
+                _ => continue,
+            };
+            let mut valid = Vec::new();
+            for i in 0..count {
+                let outcome = self.subschema(&format!("/{}/{}", keyword, i), None);
+                if outcome.is_valid() {
+                    valid.push(i);
+                    self.outcome.merge(outcome, true);
+                }
+            }
+            match (keyword, valid.len()) {
+                (_, 0) => {
+                    self.violation(keyword, format!("does not match any schema of {}", keyword))
+                }
+                ("oneOf", n) if n > 1 => {
+                    let indices: Vec<String> = valid.iter().map(usize::to_string).collect();
+                    let message = format!(
+                        "matches more than one schema of oneOf, {}",
+                        indices.join(", ")
+                    );
+                    self.violation(keyword, message)
+                }
+                _ => {}
+            }
+        }
+        if self.map.contains_key("not") && self.subschema("/not", None).is_valid() {
+            self.violation("not", "must not match the schema of not".to_string());
+        }
+        if self.map.contains_key("if") {
+            let condition = self.subschema("/if", None);
+            let branch = match condition.is_valid() {
+                true => "then",
+                false => "else",
+            };
+            if condition.is_valid() {
+                self.outcome.merge(condition, true);
+            }
+            if self.map.contains_key(branch) {
+                let outcome = self.subschema(&format!("/{}", branch), None);
+                self.outcome.merge(outcome, true);
+            }
+        }
+    }
+
+    fn number(&mut self) {
+        let value = self.instance.as_f64().unwrap_or_default();
+        let limit = |keyword: &str| self.map.get(keyword).and_then(Value::as_f64);
+        let shown = short(self.instance);
+
+        if let Some(max) = limit("maximum") {
+            if value > max {
+                self.violation(
+                    "maximum",
+                    format!("{} is greater than the maximum of {}", shown, max),
+                );
+            }
+        }
+        if let Some(max) = limit("exclusiveMaximum") {
+            if value >= max {
+                self.violation(
+                    "exclusiveMaximum",
+                    format!("{} must be less than {}", shown, max),
+                );
+            }
+        }
+        if let Some(min) = limit("minimum") {
+            if value < min {
+                self.violation(
+                    "minimum",
+                    format!("{} is less than the minimum of {}", shown, min),
+                );
+            }
+        }
+        if let Some(min) = limit("exclusiveMinimum") {
+            if value <= min {
+                self.violation(
+                    "exclusiveMinimum",
+                    format!("{} must be greater than {}", shown, min),
+                );
+            }
+        }
+        if let Some(divisor) = self.map.get("multipleOf") {
+            if !is_multiple(self.instance, divisor) {
+                self.violation(
+                    "multipleOf",
+                    format!("{} is not a multiple of {}", shown, divisor),
+                );
+            }
+        }
+    }
+
+    fn string(&mut self, s: &str) {
+        let length = s.chars().count();
+        if let Some(max) = self.count("maxLength") {
+            if length > max {
+                self.violation("maxLength", format!("longer than {} characters", max));
+            }
+        }
+        if let Some(min) = self.count("minLength") {
+            if length < min {
+                self.violation("minLength", format!("shorter than {} characters", min));
+            }
+        }
+        if let Some(Value::String(pattern)) = self.map.get("pattern") {
+            if !self.validator.is_match(pattern, s) {
+                self.violation("pattern", format!("does not match the pattern {}", pattern));
+            }
+        }
+        if let Some(Value::String(name)) = self.map.get("format") {
+            if self.validator.formats && !format::is_valid(name, s) {
+                self.violation("format", format!("is not a valid {}", name));
+            }
+        }
+    }
+
+    fn count(&self, keyword: &str) -> Option<usize> {
+        self.map
+            .get(keyword)
+            .and_then(Value::as_f64)
+            .map(|n| n as usize)
+    }
+
+    fn array(&mut self, items: &[Value]) {
+        if let Some(max) = self.count("maxItems") {
+            if items.len() > max {
+                self.violation("maxItems", format!("more than {} items", max));
+            }
+        }
+        if let Some(min) = self.count("minItems") {
+            if items.len() < min {
+                self.violation("minItems", format!("fewer than {} items", min));
+            }
+        }
+        if self.map.get("uniqueItems") == Some(&Value::Bool(true)) {
+            let duplicate = (0..items.len()).find(|&i| {
+                items[..i]
+                    .iter()
+                    .any(|other| values_equal(other, &items[i]))
+            });
+            if let Some(i) = duplicate {
+                self.violation_at(
+                    "uniqueItems",
+                    Some(Step::Index(i)),
+                    "duplicate item".to_string(),
+                );
+            }
+        }
+
+        // the first items can have schemas of their own, the others share one
+        let (positional, rest) = match self.draft7() {
+            true => ("items", "additionalItems"),
+            false => ("prefixItems", "items"),
+        };
+        let (prefix, rest) = match (self.draft7(), self.map.get(positional)) {
+            (_, Some(Value::Array(schemas))) => (schemas.len(), rest),
+            // a draft 7 `items` that is a schema applies to every item
+            (true, _) => (0, "items"),
+            (false, _) => (0, rest),
+        };
+        for (i, item) in items.iter().enumerate().take(prefix) {
+            let suffix = format!("/{}/{}", positional, i);
+            let outcome = self.subschema(&suffix, Some((Step::Index(i), item)));
+            self.outcome.merge(outcome, false);
+            self.outcome.items.insert(i);
+        }
+        if self.map.contains_key(rest) {
+            let suffix = format!("/{}", rest);
+            for (i, item) in items.iter().enumerate().skip(prefix) {
+                let outcome = self.subschema(&suffix, Some((Step::Index(i), item)));
+                self.outcome.merge(outcome, false);
+                self.outcome.items.insert(i);
+            }
+        }
+
+        if self.map.contains_key("contains") {
+            let mut matched = 0;
+            for (i, item) in items.iter().enumerate() {
+                if self
+                    .subschema("/contains", Some((Step::Index(i), item)))
+                    .is_valid()
+                {
+                    matched += 1;
+                    self.outcome.items.insert(i);
+                }
+            }
+            let (min, max) = match self.draft7() {
+                true => (1, None),
+                false => (
+                    self.count("minContains").unwrap_or(1),
+                    self.count("maxContains"),
+                ),
+            };
+            if matched < min {
+                let message = match matched {
+                    0 => "no item matches the schema of contains".to_string(),
+                    n => format!(
+                        "{} items match the schema of contains, at least {} must",
+                        n, min
+                    ),
+                };
+                let keyword = if self.map.contains_key("minContains") {
+                    "minContains"
+                } else {
+                    "contains"
+                };
+                self.violation(keyword, message);
+            }
+            if let Some(max) = max.filter(|&max| matched > max) {
+                let message = format!(
+                    "{} items match the schema of contains, at most {} may",
+                    matched, max
+                );
+                self.violation("maxContains", message);
+            }
+        }
+    }
+
+    fn object(&mut self, map: &Map<String, Value>) {
+        if let Some(max) = self.count("maxProperties") {
+            if map.len() > max {
+                self.violation("maxProperties", format!("more than {} properties", max));
+            }
+        }
+        if let Some(min) = self.count("minProperties") {
+            if map.len() < min {
+                self.violation("minProperties", format!("fewer than {} properties", min));
+            }
+        }
+        if let Some(Value::Array(required)) = self.map.get("required") {
+            for name in required.iter().filter_map(Value::as_str) {
+                if !map.contains_key(name) {
+                    self.violation(
+                        "required",
+                        format!("missing the required property '{}'", name),
+                    );
+                }
+            }
+        }
+
+        // draft 7 has both kinds of dependencies in one keyword
+        let keywords: &[&str] = match self.draft7() {
+            true => &["dependencies"],
+            false => &["dependentRequired", "dependentSchemas"],
+        };
+        for keyword in keywords {
+            let dependencies = match self.map.get(*keyword) {
+                Some(Value::Object(dependencies)) => dependencies,
+                _ => continue,
+            };
+            for (name, dependency) in dependencies {
+                if !map.contains_key(name) {
+                    continue;
+                }
+                match dependency {
+                    Value::Array(required) => {
+                        for other in required.iter().filter_map(Value::as_str) {
+                            if !map.contains_key(other) {
+                                let message = format!(
+                                    "missing the property '{}', required when '{}' is present",
+                                    other, name
+                                );
+                                self.violation(keyword, message);
+                            }
+                        }
+                    }
+                    _ => {
+                        let suffix = format!("/{}/{}", keyword, pointer::escape(name));
+                        let outcome = self.subschema(&suffix, None);
+                        self.outcome.merge(outcome, true);
+                    }
+                }
+            }
+        }
+
+        let mut matched = HashSet::new();
+        if let Some(Value::Object(properties)) = self.map.get("properties") {
+            for (name, value) in map
+                .iter()
+                .filter(|(name, _)| properties.contains_key(*name))
+            {
+                let suffix = format!("/properties/{}", pointer::escape(name));
+                let outcome = self.subschema(&suffix, Some((Step::Key(name.clone()), value)));
+                self.outcome.merge(outcome, false);
+                matched.insert(name.clone());
+            }
+        }
+        if let Some(Value::Object(patterns)) = self.map.get("patternProperties") {
+            for pattern in patterns.keys() {
+                for (name, value) in map {
+                    if self.validator.is_match(pattern, name) {
+                        let suffix = format!("/patternProperties/{}", pointer::escape(pattern));
+                        let outcome =
+                            self.subschema(&suffix, Some((Step::Key(name.clone()), value)));
+                        self.outcome.merge(outcome, false);
+                        matched.insert(name.clone());
+                    }
+                }
+            }
+        }
+        if let Some(additional) = self.map.get("additionalProperties") {
+            let others: Vec<_> = map
+                .iter()
+                .filter(|(name, _)| !matched.contains(*name))
+                .collect();
+            for (name, value) in others {
+                let step = Step::Key(name.clone());
+                if additional == &Value::Bool(false) {
+                    let message = format!("the property '{}' is not allowed", name);
+                    self.violation_at("additionalProperties", Some(step), message);
+                } else {
+                    let outcome = self.subschema("/additionalProperties", Some((step, value)));
+                    self.outcome.merge(outcome, false);
+                }
+                matched.insert(name.clone());
+            }
+        }
+        if self.map.contains_key("propertyNames") {
+            for name in map.keys() {
+                let outcome = self.subschema(
+                    "/propertyNames",
+                    Some((Step::Key(name.clone()), &Value::String(name.clone()))),
+                );
+                for violation in outcome.violations {
+                    let message = format!("the property name '{}' {}", name, violation.message);
+                    self.outcome.violations.push(Violation {
+                        message,
+                        ..violation
+                    });
+                }
+            }
+        }
+        self.outcome.properties.extend(matched);
+    }
+
+    fn unevaluated_items(&mut self, items: &[Value]) {
+        if !self.map.contains_key("unevaluatedItems") {
+            return;
+        }
+        for (i, item) in items.iter().enumerate() {
+            if self.outcome.items.contains(&i) {
+                continue;
+            }
+            let outcome = self.subschema("/unevaluatedItems", Some((Step::Index(i), item)));
+            if !outcome.is_valid() && self.map.get("unevaluatedItems") == Some(&Value::Bool(false))
+            {
+                self.violation_at(
+                    "unevaluatedItems",
+                    Some(Step::Index(i)),
+                    "the item is not allowed".to_string(),
+                );
+            } else {
+                self.outcome.merge(outcome, false);
+            }
+        }
+        self.outcome.items.extend(0..items.len());
+    }
+
+    fn unevaluated_properties(&mut self, map: &Map<String, Value>) {
+        if !self.map.contains_key("unevaluatedProperties") {
+            return;
+        }
+        for (name, value) in map {
+            if self.outcome.properties.contains(name) {
+                continue;
+            }
+            let step = Step::Key(name.clone());
+            let outcome = self.subschema("/unevaluatedProperties", Some((step.clone(), value)));
+            if !outcome.is_valid()
+                && self.map.get("unevaluatedProperties") == Some(&Value::Bool(false))
+            {
+                let message = format!("the property '{}' is not allowed", name);
+                self.violation_at("unevaluatedProperties", Some(step), message);
+            } else {
+                self.outcome.merge(outcome, false);
+            }
+        }
+        self.outcome.properties.extend(map.keys().cloned());
+    }
+}
+
+fn has_type(value: &Value, name: &str) -> bool {
+    match (name, value) {
+        ("null", Value::Null) => true,
+        ("boolean", Value::Bool(_)) => true,
+        ("object", Value::Object(_)) => true,
+        ("array", Value::Array(_)) => true,
+        ("string", Value::String(_)) => true,
+        ("number", Value::Number(_)) => true,
+        // 1.0 is an integer too
+        ("integer", Value::Number(n)) => {
+            n.is_i64() || n.is_u64() || n.as_f64().is_some_and(|f| f.fract() == 0.0)
+        }
+        _ => false,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Object(_) => "object",
+        Value::Array(_) => "array",
+        Value::String(_) => "string",
+        Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        Value::Number(_) => "number",
+    }
+}
+
+fn is_multiple(value: &Value, divisor: &Value) -> bool {
+    if let (Some(value), Some(divisor)) = (value.as_i64(), divisor.as_i64()) {
+        return divisor != 0 && value % divisor == 0;
+    }
+    let (value, divisor) = (
+        value.as_f64().unwrap_or_default(),
+        divisor.as_f64().unwrap_or(1.0),
+    );
+    let quotient = value / divisor;
+    // tolerates the rounding of decimal fractions such as 0.3 / 0.1
+    quotient.is_finite() && (quotient - quotient.round()).abs() < 1e-9 * quotient.abs().max(1.0)
+}
+
+/// A value in a message, cut when it is long.
+fn short(value: &Value) -> String {
+    let text = value.to_string();
+    match text.char_indices().nth(60) {
+        Some((i, _)) => format!("{}...", &text[..i]),
+        None => text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use super::{Draft, Validator};
+
+    macro_rules! suite {
+        ($name:literal) => {
+            ($name, include_str!(concat!("suite/", $name, ".json")))
+        };
+    }
+
+    /// A subset of the JSON-Schema-Test-Suite, github.com/json-schema-org/JSON-Schema-Test-Suite,
+    /// the groups of its files that need neither remote schemas nor the metaschemas.
+    const SUITE: &[(&str, &str)] = &[
+        suite!("draft2020-12/additionalProperties"),
+        suite!("draft2020-12/allOf"),
+        suite!("draft2020-12/anchor"),
+        suite!("draft2020-12/anyOf"),
+        suite!("draft2020-12/boolean_schema"),
+        suite!("draft2020-12/const"),
+        suite!("draft2020-12/contains"),
+        suite!("draft2020-12/dependentRequired"),
+        suite!("draft2020-12/dependentSchemas"),
+        suite!("draft2020-12/dynamicRef"),
+        suite!("draft2020-12/enum"),
+        suite!("draft2020-12/exclusiveMaximum"),
+        suite!("draft2020-12/exclusiveMinimum"),
+        suite!("draft2020-12/if-then-else"),
+        suite!("draft2020-12/items"),
+        suite!("draft2020-12/maxContains"),
+        suite!("draft2020-12/maxItems"),
+        suite!("draft2020-12/maxLength"),
+        suite!("draft2020-12/maxProperties"),
+        suite!("draft2020-12/maximum"),
+        suite!("draft2020-12/minContains"),
+        suite!("draft2020-12/minItems"),
+        suite!("draft2020-12/minLength"),
+        suite!("draft2020-12/minProperties"),
+        suite!("draft2020-12/minimum"),
+        suite!("draft2020-12/multipleOf"),
+        suite!("draft2020-12/not"),
+        suite!("draft2020-12/oneOf"),
+        suite!("draft2020-12/pattern"),
+        suite!("draft2020-12/patternProperties"),
+        suite!("draft2020-12/prefixItems"),
+        suite!("draft2020-12/properties"),
+        suite!("draft2020-12/propertyNames"),
+        suite!("draft2020-12/ref"),
+        suite!("draft2020-12/required"),
+        suite!("draft2020-12/type"),
+        suite!("draft2020-12/unevaluatedItems"),
+        suite!("draft2020-12/unevaluatedProperties"),
+        suite!("draft2020-12/uniqueItems"),
+        suite!("draft7/additionalItems"),
+        suite!("draft7/contains"),
+        suite!("draft7/dependencies"),
+        suite!("draft7/items"),
+        suite!("draft7/ref"),
+    ];
+
+    #[test]
+    fn json_schema_test_suite() {
+        let mut failures = Vec::new();
+        for (file, text) in SUITE {
+            let draft = match file.starts_with("draft7/") {
+                true => Draft::Draft7,
+                false => Draft::Draft2020,
+            };
+            let groups: Vec<Value> = serde_json::from_str(text).unwrap();
+            for group in &groups {
+                let schema = group["schema"].clone();
+                let uri = "http://localhost:1234/schema.json".to_string();
+                let validator = match Validator::new(schema, uri, draft, false) {
+                    Ok(validator) => validator,
+                    Err(_) => {
+                        failures.push(format!(
+                            "{}: {}: invalid schema",
+                            file, group["description"]
+                        ));
+                        continue;
+                    }
+                };
+                for test in group["tests"].as_array().unwrap() {
+                    let valid = validator.validate(&test["data"]).is_empty();
+                    if Some(valid) != test["valid"].as_bool() {
+                        failures.push(format!(
+                            "{}: {}: {}",
+                            file, group["description"], test["description"]
+                        ));
+                    }
+                }
+            }
+        }
+        assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+    }
+}
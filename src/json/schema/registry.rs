@@ -0,0 +1,487 @@
+//! The schema documents and what `$id`, anchors and `$ref` point to in them.
+//!
+//! Referenced `file:` schemas are loaded along with the root one, anything else has to
+//! be embedded in it under an `$id`. The schemas are checked on the way, so that the
+//! validation itself can assume they are well-formed.
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::Context;
+use serde_json::{Map, Value};
+
+use super::super::regex::Regex;
+use super::super::{parse, pointer};
+use super::Draft;
+
+pub struct Document {
+    pub uri: String,
+    /// How locations in the document are shown, empty for the root schema.
+    pub name: String,
+    pub value: Value,
+}
+
+/// A schema in one of the documents.
+#[derive(Clone, Debug)]
+pub struct Target {
+    pub doc: usize,
+    pub pointer: String,
+    /// The base URI in effect where the schema is, before its own `$id` applies.
+    pub base: String,
+}
+
+pub struct Registry {
+    pub documents: Vec<Document>,
+    /// Schema resources by URI and anchors by `uri#name`.
+    targets: HashMap<String, Target>,
+    dynamic_anchors: HashSet<String>,
+}
+
+/// Why the schemas can not be used.
+pub enum LoadError {
+    /// A referenced schema document is not JSON.
+    Parse(anyhow::Error),
+    /// The problems found in the schemas.
+    Invalid(Vec<String>),
+}
+
+impl Registry {
+    pub fn load(root: Value, uri: String, draft: Draft) -> Result<Registry, LoadError> {
+        let mut registry = Registry {
+            documents: Vec::new(),
+            targets: HashMap::new(),
+            dynamic_anchors: HashSet::new(),
+        };
+        let mut walker = Walker {
+            draft,
+            problems: Vec::new(),
+            refs: Vec::new(),
+        };
+
+        registry.add(root, uri, String::new(), &mut walker);
+        let mut checked = 0;
+        while checked < walker.refs.len() {
+            let (base, reference, location) = walker.refs[checked].clone();
+            checked += 1;
+            let (uri, _) = split_fragment(&resolve(&base, &reference));
+            if registry.targets.contains_key(&uri) {
+                continue;
+            }
+            match uri.strip_prefix("file://") {
+                Some(path) => {
+                    let text = match std::fs::read_to_string(path) {
+                        Ok(text) => text,
+                        Err(e) => {
+                            walker.problems.push(format!(
+                                "{}: can not read '{}', {}",
+                                location, path, e
+                            ));
+                            continue;
+                        }
+                    };
+                    let value = parse(&text)
+                        .with_context(|| format!("Parsing the schema '{}'", path))
+                        .map_err(LoadError::Parse)?;
+                    registry.add(value, uri.clone(), path.to_string(), &mut walker);
+                }
+                None => walker.problems.push(format!(
+                    "{}: can not load '{}', only local files are, other schemas must be embedded with an `$id`",
+                    location, uri
+                )),
+            }
+        }
+
+        for (base, reference, location) in &walker.refs {
+            if registry.resolve(base, reference).is_none() && registry.is_loaded(base, reference) {
+                walker
+                    .problems
+                    .push(format!("{}: '{}' does not resolve", location, reference));
+            }
+        }
+        match walker.problems.is_empty() {
+            true => Ok(registry),
+            false => Err(LoadError::Invalid(walker.problems)),
+        }
+    }
+
+    fn add(&mut self, value: Value, uri: String, name: String, walker: &mut Walker) {
+        let doc = self.documents.len();
+        let mut found = Found::default();
+        walker.walk(&value, doc, &name, &uri, String::new(), &mut found);
+        for (key, target) in found.targets {
+            self.targets.entry(key).or_insert(target);
+        }
+        self.dynamic_anchors.extend(found.dynamic_anchors);
+        // the document is also known by the URI it was loaded from, when its `$id` differs
+        self.targets.entry(uri.clone()).or_insert(Target {
+            doc,
+            pointer: String::new(),
+            base: uri.clone(),
+        });
+        self.documents.push(Document { uri, name, value });
+    }
+
+    /// Whether the resource a reference points into is known, so that an unresolved
+    /// reference is a wrong fragment rather than an unloadable resource.
+    fn is_loaded(&self, base: &str, reference: &str) -> bool {
+        let (uri, _) = split_fragment(&resolve(base, reference));
+        self.targets.contains_key(&uri)
+    }
+
+    pub fn resolve(&self, base: &str, reference: &str) -> Option<Target> {
+        let (uri, fragment) = split_fragment(&resolve(base, reference));
+        match fragment {
+            None => self.targets.get(&uri).cloned(),
+            Some(fragment) if fragment.is_empty() || fragment.starts_with('/') => {
+                let resource = self.targets.get(&uri)?;
+                let tokens = pointer::parse(&format!("#{}", fragment)).ok()?;
+                pointer::get(self.schema(resource), &tokens)?;
+                let suffix: String = tokens
+                    .iter()
+                    .map(|t| format!("/{}", pointer::escape(t)))
+                    .collect();
+                Some(Target {
+                    doc: resource.doc,
+                    pointer: format!("{}{}", resource.pointer, suffix),
+                    base: match suffix.is_empty() {
+                        true => resource.base.clone(),
+                        false => uri,
+                    },
+                })
+            }
+            Some(anchor) => self.targets.get(&format!("{}#{}", uri, anchor)).cloned(),
+        }
+    }
+
+    pub fn has_dynamic_anchor(&self, resource: &str, name: &str) -> bool {
+        self.dynamic_anchors
+            .contains(&format!("{}#{}", resource, name))
+    }
+
+    pub fn schema(&self, target: &Target) -> &Value {
+        let tokens = pointer::parse(&target.pointer).unwrap_or_default();
+        pointer::get(&self.documents[target.doc].value, &tokens).unwrap_or(&Value::Bool(true))
+    }
+}
+
+#[derive(Default)]
+struct Found {
+    targets: Vec<(String, Target)>,
+    dynamic_anchors: Vec<String>,
+}
+
+/// Goes through every subschema to find identifiers and references, and to check it.
+struct Walker {
+    draft: Draft,
+    problems: Vec<String>,
+    /// The base URI, `$ref` and location of every reference.
+    refs: Vec<(String, String, String)>,
+}
+
+impl Walker {
+    fn walk(
+        &mut self,
+        schema: &Value,
+        doc: usize,
+        name: &str,
+        base: &str,
+        pointer: String,
+        found: &mut Found,
+    ) {
+        let location = format!("{}#{}", name, pointer);
+        let map = match schema {
+            Value::Bool(_) => return,
+            Value::Object(map) => map,
+            _ => {
+                self.problems
+                    .push(format!("{}: a schema is an object or a boolean", location));
+                return;
+            }
+        };
+        let target = || Target {
+            doc,
+            pointer: pointer.clone(),
+            base: base.to_string(),
+        };
+
+        let mut base = base.to_string();
+        // a draft 7 `$ref` overrides its siblings, `$id` included
+        let id = match (self.draft, map.contains_key("$ref")) {
+            (Draft::Draft7, true) => None,
+            _ => map.get("$id"),
+        };
+        if let Some(Value::String(id)) = id {
+            match (self.draft, id.strip_prefix('#')) {
+                // a plain name fragment is how draft 7 declares anchors
+                (Draft::Draft7, Some(anchor)) => found
+                    .targets
+                    .push((format!("{}#{}", base, anchor), target())),
+                (Draft::Draft2020, _) if id.contains('#') && !id.ends_with('#') => {
+                    self.problems.push(format!(
+                        "{}/$id: must not have a fragment, use $anchor",
+                        location
+                    ))
+                }
+                _ => {
+                    let (uri, _) = split_fragment(&resolve(&base, id));
+                    found.targets.push((uri.clone(), target()));
+                    base = uri;
+                }
+            }
+        }
+        if self.draft == Draft::Draft2020 {
+            for keyword in ["$anchor", "$dynamicAnchor"] {
+                if let Some(Value::String(anchor)) = map.get(keyword) {
+                    found
+                        .targets
+                        .push((format!("{}#{}", base, anchor), target()));
+                    if keyword == "$dynamicAnchor" {
+                        found.dynamic_anchors.push(format!("{}#{}", base, anchor));
+                    }
+                }
+            }
+        }
+        for keyword in ["$ref", "$dynamicRef"] {
+            if let Some(Value::String(reference)) = map.get(keyword) {
+                let location = format!("{}/{}", location, keyword);
+                self.refs.push((base.clone(), reference.clone(), location));
+            }
+        }
+
+        self.check(map, &location);
+        for (suffix, subschema) in subschemas(map, self.draft) {
+            let pointer = format!("{}{}", pointer, suffix);
+            self.walk(subschema, doc, name, &base, pointer, found);
+        }
+    }
+
+    /// Checks the values of the keywords, the subschemas are checked on their own.
+    fn check(&mut self, map: &Map<String, Value>, location: &str) {
+        const TYPES: &[&str] = &[
+            "null", "boolean", "object", "array", "number", "integer", "string",
+        ];
+        let draft7 = self.draft == Draft::Draft7;
+
+        for (keyword, value) in map {
+            let problem = match keyword.as_str() {
+                "type" => {
+                    let names = match value {
+                        Value::Array(names) => names.iter().collect(),
+                        value => vec![value],
+                    };
+                    let valid = names
+                        .iter()
+                        .all(|n| n.as_str().is_some_and(|n| TYPES.contains(&n)));
+                    (!valid)
+                        .then(|| format!("must be one of {} or an array of them", TYPES.join(", ")))
+                }
+                "enum" => (!value.is_array()).then(|| "must be an array".to_string()),
+                "multipleOf" => (!value.as_f64().is_some_and(|n| n > 0.0))
+                    .then(|| "must be a number greater than 0".to_string()),
+                "maximum" | "minimum" | "exclusiveMaximum" | "exclusiveMinimum" => {
+                    (!value.is_number()).then(|| "must be a number".to_string())
+                }
+                "maxLength" | "minLength" | "maxItems" | "minItems" | "maxProperties"
+                | "minProperties" | "maxContains" | "minContains" => {
+                    (!is_count(value)).then(|| "must be a non-negative integer".to_string())
+                }
+                "pattern" => match value.as_str().map(Regex::new) {
+                    Some(Ok(_)) => None,
+                    Some(Err(e)) => Some(e.to_string()),
+                    None => Some("must be a string".to_string()),
+                },
+                "patternProperties" => match value.as_object() {
+                    Some(patterns) => patterns
+                        .keys()
+                        .find_map(|p| Regex::new(p).err())
+                        .map(|e| e.to_string()),
+                    None => Some("must be an object".to_string()),
+                },
+                "required" => (!is_string_set(value))
+                    .then(|| "must be an array of unique strings".to_string()),
+                "dependentRequired" if !draft7 => {
+                    let valid = value
+                        .as_object()
+                        .is_some_and(|deps| deps.values().all(is_string_set));
+                    (!valid).then(|| "must be an object of arrays of unique strings".to_string())
+                }
+                "dependencies" if draft7 => {
+                    let valid = value.as_object().is_some_and(|deps| {
+                        deps.values()
+                            .all(|d| is_string_set(d) || d.is_object() || d.is_boolean())
+                    });
+                    (!valid)
+                        .then(|| "must be an object of schemas or arrays of strings".to_string())
+                }
+                "uniqueItems" => (!value.is_boolean()).then(|| "must be a boolean".to_string()),
+                "$ref" | "$id" | "$schema" | "format" | "$anchor" | "$dynamicRef"
+                | "$dynamicAnchor" => (!value.is_string()).then(|| "must be a string".to_string()),
+                "properties" | "definitions" | "$defs" | "dependentSchemas" => {
+                    (!value.is_object()).then(|| "must be an object".to_string())
+                }
+                "allOf" | "anyOf" | "oneOf" | "prefixItems" => value
+                    .as_array()
+                    .is_none_or(|a| a.is_empty())
+                    .then(|| "must be a non-empty array of schemas".to_string()),
+                "items" if !draft7 && value.is_array() => {
+                    Some("must be a schema, use prefixItems for arrays of schemas".to_string())
+                }
+                _ => None,
+            };
+            if let Some(problem) = problem {
+                self.problems.push(format!(
+                    "{}/{}: {}",
+                    location,
+                    pointer::escape(keyword),
+                    problem
+                ));
+            }
+        }
+    }
+}
+
+fn is_count(value: &Value) -> bool {
+    value.as_u64().is_some() || value.as_f64().is_some_and(|n| n >= 0.0 && n.fract() == 0.0)
+}
+
+fn is_string_set(value: &Value) -> bool {
+    value.as_array().is_some_and(|items| {
+        items.iter().all(Value::is_string)
+            && items
+                .iter()
+                .enumerate()
+                .all(|(i, item)| !items[..i].contains(item))
+    })
+}
+
+/// The subschemas directly in a schema and their pointers relative to it.
+pub fn subschemas(map: &Map<String, Value>, draft: Draft) -> Vec<(String, &Value)> {
+    let draft7 = draft == Draft::Draft7;
+    let mut found = Vec::new();
+    for (keyword, value) in map {
+        let prefix = format!("/{}", pointer::escape(keyword));
+        let holds = match keyword.as_str() {
+            "not"
+            | "if"
+            | "then"
+            | "else"
+            | "contains"
+            | "propertyNames"
+            | "additionalProperties" => Holds::One,
+            "additionalItems" if draft7 => Holds::One,
+            "unevaluatedItems" | "unevaluatedProperties" if !draft7 => Holds::One,
+            "items" if value.is_array() => Holds::Many,
+            "items" => Holds::One,
+            "allOf" | "anyOf" | "oneOf" => Holds::Many,
+            "prefixItems" if !draft7 => Holds::Many,
+            "properties" | "patternProperties" | "definitions" | "$defs" => Holds::Map,
+            "dependentSchemas" if !draft7 => Holds::Map,
+            "dependencies" if draft7 => Holds::Map,
+            _ => continue,
+        };
+        match (holds, value) {
+            (Holds::One, value) => found.push((prefix, value)),
+            (Holds::Many, Value::Array(items)) => {
+                for (i, item) in items.iter().enumerate() {
+                    found.push((format!("{}/{}", prefix, i), item));
+                }
+            }
+            (Holds::Map, Value::Object(schemas)) => {
+                for (name, schema) in schemas {
+                    // the arrays of `dependencies` are property names
+                    if !schema.is_array() || keyword != "dependencies" {
+                        found.push((format!("{}/{}", prefix, pointer::escape(name)), schema));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    found
+}
+
+enum Holds {
+    One,
+    Many,
+    Map,
+}
+
+/// Splits a URI into the part before `#` and the fragment, an empty fragment is none.
+pub fn split_fragment(uri: &str) -> (String, Option<String>) {
+    match uri.split_once('#') {
+        Some((uri, "")) => (uri.to_string(), None),
+        Some((uri, fragment)) => (uri.to_string(), Some(fragment.to_string())),
+        None => (uri.to_string(), None),
+    }
+}
+
+/// Resolves a URI reference against a base URI, RFC 3986 section 5.2.
+pub fn resolve(base: &str, reference: &str) -> String {
+    let has_scheme = reference.split_once(':').is_some_and(|(scheme, _)| {
+        !scheme.is_empty()
+            && scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+    });
+    if has_scheme {
+        return remove_dot_segments(reference);
+    }
+    let (base, _) = split_fragment(base);
+    if reference.is_empty() {
+        return base;
+    }
+    if reference.starts_with('#') {
+        return format!("{}{}", base, reference);
+    }
+
+    let (scheme, rest) = base.split_once(':').unwrap_or(("", base.as_str()));
+    if let Some(reference) = reference.strip_prefix("//") {
+        return format!("{}://{}", scheme, reference);
+    }
+    // the authority, if any, stays with absolute paths
+    let authority_end = match rest.strip_prefix("//") {
+        Some(after) => 2 + after.find('/').unwrap_or(after.len()),
+        None => 0,
+    };
+    let (authority, path) = rest.split_at(authority_end);
+    let path = match reference.starts_with('/') {
+        true => reference.to_string(),
+        false => {
+            let path = path.split(['?', '#']).next().unwrap_or_default();
+            let directory = &path[..path.rfind('/').map_or(0, |i| i + 1)];
+            format!("{}{}", directory, reference)
+        }
+    };
+    remove_dot_segments(&format!("{}:{}{}", scheme, authority, path))
+}
+
+fn remove_dot_segments(uri: &str) -> String {
+    if !uri.contains("./") {
+        return uri.to_string();
+    }
+    let (before, fragment) = match uri.split_once('#') {
+        Some((before, fragment)) => (before, Some(fragment)),
+        None => (uri, None),
+    };
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in before.split('/') {
+        match segment {
+            "." => {}
+            ".." => {
+                if segments.len() > 1 {
+                    segments.pop();
+                }
+            }
+            segment => segments.push(segment),
+        }
+    }
+    let mut result = segments.join("/");
+    if before.ends_with("/.") || before.ends_with("/..") {
+        result.push('/');
+    }
+    if let Some(fragment) = fragment {
+        result.push('#');
+        result.push_str(fragment);
+    }
+    result
+}
@@ -0,0 +1,191 @@
+//! Where values are in the JSON text, to point at them by line and column.
+
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use super::{pointer, syntax};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Position {
+    /// 1-based.
+    pub line: usize,
+    /// 1-based, in characters.
+    pub column: usize,
+}
+
+/// The positions of every value of a document by JSON Pointer.
+pub struct SourceMap {
+    positions: HashMap<String, Position>,
+}
+
+impl SourceMap {
+    /// Maps a document that is known to be valid JSON, or JSON5.
+    pub fn new(text: &str) -> SourceMap {
+        let mut scanner = Scanner {
+            chars: text.chars().peekable(),
+            position: Position { line: 1, column: 1 },
+            positions: HashMap::new(),
+        };
+        scanner.value(String::new());
+        SourceMap {
+            positions: scanner.positions,
+        }
+    }
+
+    pub fn get(&self, pointer: &str) -> Option<Position> {
+        self.positions.get(pointer).copied()
+    }
+}
+
+struct Scanner<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    position: Position,
+    positions: HashMap<String, Position>,
+}
+
+impl Scanner<'_> {
+    fn bump(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        if c == '\n' {
+            self.position.line += 1;
+            self.position.column = 1;
+        } else {
+            self.position.column += 1;
+        }
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        loop {
+            match self.chars.peek() {
+                Some(c) if c.is_whitespace() || *c == '\u{feff}' => {
+                    self.bump();
+                }
+                // the comments of JSON5
+                Some('/') => {
+                    self.bump();
+                    match self.bump() {
+                        Some('/') => {
+                            while self.chars.peek().is_some_and(|&c| c != '\n') {
+                                self.bump();
+                            }
+                        }
+                        _ => {
+                            let mut previous = None;
+                            while let Some(c) = self.bump() {
+                                if previous == Some('*') && c == '/' {
+                                    break;
+                                }
+                                previous = Some(c);
+                            }
+                        }
+                    }
+                }
+                _ => return,
+            }
+        }
+    }
+
+    fn value(&mut self, pointer: String) {
+        self.skip_whitespace();
+        self.positions.insert(pointer.clone(), self.position);
+        match self.chars.peek() {
+            Some('{') => {
+                self.bump();
+                self.members(&pointer);
+            }
+            Some('[') => {
+                self.bump();
+                self.items(&pointer);
+            }
+            Some('"' | '\'') => {
+                self.string();
+            }
+            _ => {
+                while self
+                    .chars
+                    .peek()
+                    .is_some_and(|c| !matches!(c, ',' | '}' | ']' | '/') && !c.is_whitespace())
+                {
+                    self.bump();
+                }
+            }
+        }
+    }
+
+    fn members(&mut self, pointer: &str) {
+        loop {
+            self.skip_whitespace();
+            let key = match self.chars.peek() {
+                Some('"' | '\'') => self.string(),
+                // an identifier, in JSON5
+                Some(&c) if c.is_alphanumeric() || c == '_' || c == '$' => {
+                    let mut key = String::new();
+                    while let Some(&c) = self.chars.peek() {
+                        if !(c.is_alphanumeric() || c == '_' || c == '$') {
+                            break;
+                        }
+                        key.push(c);
+                        self.bump();
+                    }
+                    key
+                }
+                Some(_) => {
+                    // `}`, or a `,` between members
+                    if self.bump() == Some('}') {
+                        return;
+                    }
+                    continue;
+                }
+                None => return,
+            };
+            self.skip_whitespace();
+            self.bump(); // `:`
+            self.value(format!("{}/{}", pointer, pointer::escape(&key)));
+        }
+    }
+
+    fn items(&mut self, pointer: &str) {
+        let mut index = 0;
+        loop {
+            self.skip_whitespace();
+            match self.chars.peek() {
+                Some(']') | None => {
+                    self.bump();
+                    return;
+                }
+                Some(',') => {
+                    self.bump();
+                }
+                Some(_) => {
+                    self.value(format!("{}/{}", pointer, index));
+                    index += 1;
+                }
+            }
+        }
+    }
+
+    /// Reads a string and returns its value.
+    fn string(&mut self) -> String {
+        let mut raw = String::new();
+        let quote = self.bump();
+        raw.extend(quote);
+        while let Some(c) = self.bump() {
+            raw.push(c);
+            match c {
+                '\\' => raw.extend(self.bump()),
+                c if Some(c) == quote => break,
+                _ => {}
+            }
+        }
+        match serde_json::from_str(&raw) {
+            Ok(s) => s,
+            // single quotes or escapes of JSON5
+            Err(_) => match syntax::parse(&raw).value {
+                Some(Value::String(s)) => s,
+                _ => String::new(),
+            },
+        }
+    }
+}
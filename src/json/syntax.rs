@@ -0,0 +1,1067 @@
+//! A JSON parser that carries on after mistakes, to report all of them with hints.
+
+use serde_json::{Map, Value};
+
+use super::source::Position;
+
+/// As deep as serde_json nests before giving up.
+const MAX_DEPTH: usize = 128;
+
+const NON_FINITE_HINT: &str = "NaN and Infinity are not JSON numbers, use null or a string";
+
+pub struct Problem {
+    /// Where the problem is, in characters.
+    pub offset: usize,
+    pub message: String,
+    pub hint: Option<&'static str>,
+    /// Valid JSON that is likely a mistake, such as a repeated key.
+    pub warning: bool,
+    /// Not JSON but JSON5, such as comments and trailing commas.
+    pub json5: bool,
+    /// JSON5 that JSON has no value for, NaN and Infinity, repaired as null.
+    pub lossy: bool,
+}
+
+/// The value as far as it could be made out, and what is wrong with the text.
+pub struct Parsed {
+    pub value: Option<Value>,
+    /// In order of where they are found.
+    pub problems: Vec<Problem>,
+}
+
+pub fn parse(text: &str) -> Parsed {
+    let mut parser = Parser {
+        chars: text.chars().collect(),
+        pos: 0,
+        depth: 0,
+        too_deep: false,
+        problems: Vec::new(),
+    };
+    if parser.peek() == Some('\u{feff}') {
+        parser.json5(
+            0,
+            "byte order mark",
+            Some("save the file without a byte order mark"),
+        );
+        parser.pos += 1;
+    }
+    parser.skip_whitespace();
+    let value = match parser.peek() {
+        None => {
+            parser.problem(parser.pos, "the input is empty", None);
+            None
+        }
+        Some(_) => parser.value(),
+    };
+    parser.skip_whitespace();
+    if value.is_some() && parser.peek().is_some() {
+        parser.problem(
+            parser.pos,
+            "unexpected content after the document",
+            Some("a document has a single value at the top, put several in an array"),
+        );
+    }
+    Parsed {
+        value,
+        problems: parser.problems,
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    depth: usize,
+    /// Set when the nesting went over [`MAX_DEPTH`], the rest of the text is skipped.
+    too_deep: bool,
+    problems: Vec<Problem>,
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+fn starts_value(c: char) -> bool {
+    "{[\"'-+.".contains(c) || is_word_char(c)
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn peek_at(&self, ahead: usize) -> Option<char> {
+        self.chars.get(self.pos + ahead).copied()
+    }
+
+    fn problem(&mut self, offset: usize, message: impl Into<String>, hint: Option<&'static str>) {
+        self.problems.push(Problem {
+            offset,
+            message: message.into(),
+            hint,
+            warning: false,
+            json5: false,
+            lossy: false,
+        });
+    }
+
+    fn json5(&mut self, offset: usize, message: impl Into<String>, hint: Option<&'static str>) {
+        self.problems.push(Problem {
+            offset,
+            message: message.into(),
+            hint,
+            warning: false,
+            json5: true,
+            lossy: false,
+        });
+    }
+
+    /// NaN or Infinity, numbers in JSON5 that are null in JSON.
+    fn non_finite(&mut self, offset: usize, message: String) -> Value {
+        self.problems.push(Problem {
+            offset,
+            message,
+            hint: Some(NON_FINITE_HINT),
+            warning: false,
+            json5: true,
+            lossy: true,
+        });
+        Value::Null
+    }
+
+    fn take_word(&mut self) -> String {
+        let start = self.pos;
+        while self.peek().is_some_and(is_word_char) {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek() {
+            match c {
+                ' ' | '\t' | '\n' | '\r' => self.pos += 1,
+                '/' if self.peek_at(1) == Some('/') => {
+                    self.json5(self.pos, "comments are not allowed in JSON", None);
+                    while self.peek().is_some_and(|c| c != '\n') {
+                        self.pos += 1;
+                    }
+                }
+                '/' if self.peek_at(1) == Some('*') => {
+                    let start = self.pos;
+                    self.pos += 2;
+                    while self.pos < self.chars.len()
+                        && !(self.peek() == Some('*') && self.peek_at(1) == Some('/'))
+                    {
+                        self.pos += 1;
+                    }
+                    match self.pos < self.chars.len() {
+                        true => {
+                            self.pos += 2;
+                            self.json5(start, "comments are not allowed in JSON", None);
+                        }
+                        false => {
+                            self.problem(start, "unterminated comment", Some("close it with `*/`"))
+                        }
+                    }
+                }
+                c if c.is_whitespace() => {
+                    let message = format!(
+                        "the whitespace character U+{:04X} is not allowed in JSON",
+                        c as u32
+                    );
+                    self.json5(self.pos, message, Some("replace it with a space"));
+                    self.pos += 1;
+                }
+                _ => break,
+            }
+        }
+    }
+
+    /// Skips what could not be parsed, up to the next `,` or closing bracket.
+    fn recover(&mut self) {
+        while let Some(c) = self.peek() {
+            match c {
+                ',' => {
+                    self.pos += 1;
+                    return;
+                }
+                '}' | ']' => return,
+                _ => self.pos += 1,
+            }
+        }
+    }
+
+    fn value(&mut self) -> Option<Value> {
+        self.skip_whitespace();
+        let start = self.pos;
+        match self.peek() {
+            None => {
+                self.problem(start, "expected a value, found the end of the input", None);
+                None
+            }
+            Some('{' | '[') if self.depth == MAX_DEPTH => {
+                let message = format!("nested deeper than {} levels", MAX_DEPTH);
+                self.problem(start, message, None);
+                self.pos = self.chars.len();
+                self.too_deep = true;
+                None
+            }
+            Some('{') => Some(self.container('}')),
+            Some('[') => Some(self.container(']')),
+            Some('"' | '\'') => Some(Value::String(self.string())),
+            Some('-' | '+' | '.' | '0'..='9') => self.number(),
+            Some(c) if is_word_char(c) => Some(self.word()),
+            Some(c) => {
+                self.problem(start, format!("expected a value, found `{}`", c), None);
+                None
+            }
+        }
+    }
+
+    /// An object when `close` is `}`, an array when it is `]`.
+    fn container(&mut self, close: char) -> Value {
+        let open = self.pos;
+        let object = close == '}';
+        let (what, other) = match object {
+            true => ("members", ']'),
+            false => ("items", '}'),
+        };
+        self.pos += 1;
+        self.depth += 1;
+        let mut map = Map::new();
+        let mut items = Vec::new();
+
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                None if self.too_deep => break,
+                None => {
+                    let message = format!("`{}` is never closed", self.chars[open]);
+                    self.problem(open, message, Some("add the missing closing bracket"));
+                    break;
+                }
+                Some(c) if c == close => {
+                    self.pos += 1;
+                    break;
+                }
+                Some(c) if c == other => {
+                    let message = format!("`{}` does not close the `{}`", c, self.chars[open]);
+                    self.problem(self.pos, message, None);
+                    self.pos += 1;
+                    break;
+                }
+                _ => {}
+            }
+
+            if object {
+                let key_start = self.pos;
+                let key = match self.key() {
+                    Some(key) => key,
+                    None => {
+                        self.recover();
+                        continue;
+                    }
+                };
+                let value = self.value();
+                if map.contains_key(&key) {
+                    self.problems.push(Problem {
+                        offset: key_start,
+                        message: format!("the key \"{}\" is repeated, the last value wins", key),
+                        hint: None,
+                        warning: true,
+                        json5: false,
+                        lossy: false,
+                    });
+                }
+                match value {
+                    Some(value) => {
+                        map.insert(key, value);
+                    }
+                    None => {
+                        self.recover();
+                        continue;
+                    }
+                }
+            } else {
+                match self.value() {
+                    Some(value) => items.push(value),
+                    None => {
+                        self.recover();
+                        continue;
+                    }
+                }
+            }
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => {
+                    let comma = self.pos;
+                    self.pos += 1;
+                    self.skip_whitespace();
+                    if self.peek() == Some(close) {
+                        let hint = match object {
+                            true => "remove the comma after the last member",
+                            false => "remove the comma after the last item",
+                        };
+                        self.json5(comma, "trailing comma", Some(hint));
+                    }
+                }
+                Some(c) if c == close => {
+                    self.pos += 1;
+                    break;
+                }
+                // the loop reports the unclosed bracket
+                None => {}
+                Some(c) if starts_value(c) => {
+                    let message = format!("expected `,` or `{}`", close);
+                    let hint = match object {
+                        true => "add the missing comma between members",
+                        false => "add the missing comma between items",
+                    };
+                    self.problem(self.pos, message, Some(hint));
+                }
+                Some(c) => {
+                    let message = format!(
+                        "expected `,` or `{}` between {}, found `{}`",
+                        close, what, c
+                    );
+                    self.problem(self.pos, message, None);
+                    self.recover();
+                }
+            }
+        }
+
+        self.depth -= 1;
+        match object {
+            true => Value::Object(map),
+            false => Value::Array(items),
+        }
+    }
+
+    /// A key and the `:` after it.
+    fn key(&mut self) -> Option<String> {
+        let start = self.pos;
+        let key = match self.peek() {
+            Some('"' | '\'') => self.string(),
+            Some(c) if is_word_char(c) => {
+                let word = self.take_word();
+                let message = format!("the key `{}` is not quoted", word);
+                let hint = Some("keys are strings, in double quotes");
+                // JSON5 takes identifiers, which do not start with a digit
+                match c.is_ascii_digit() {
+                    true => self.problem(start, message, hint),
+                    false => self.json5(start, message, hint),
+                }
+                word
+            }
+            Some(c) => {
+                self.problem(start, format!("expected a key, found `{}`", c), None);
+                return None;
+            }
+            None => return None,
+        };
+        self.skip_whitespace();
+        match self.peek() {
+            Some(':') => self.pos += 1,
+            Some('=') => {
+                self.problem(
+                    self.pos,
+                    "expected `:`, found `=`",
+                    Some("members are written \"key\": value"),
+                );
+                self.pos += 1;
+            }
+            _ => {
+                let message = format!("expected `:` after the key \"{}\"", key);
+                self.problem(self.pos, message, None);
+            }
+        }
+        Some(key)
+    }
+
+    fn string(&mut self) -> String {
+        let start = self.pos;
+        let quote = self.chars[start];
+        self.pos += 1;
+        if quote == '\'' {
+            self.json5(
+                start,
+                "strings must be in double quotes",
+                Some("replace the single quotes with double quotes"),
+            );
+        }
+
+        let mut s = String::new();
+        loop {
+            let c = match self.peek() {
+                Some('\n') | None => {
+                    let hint = "add the closing quote, or escape line breaks in the string as \\n";
+                    self.problem(start, "unterminated string", Some(hint));
+                    break;
+                }
+                Some(c) => c,
+            };
+            self.pos += 1;
+            match c {
+                c if c == quote => break,
+                '\\' => self.escape(&mut s, quote),
+                c if (c as u32) < 0x20 => {
+                    let message =
+                        format!("the control character U+{:04X} must be escaped", c as u32);
+                    self.problem(self.pos - 1, message, Some("tabs are written \\t"));
+                    s.push(c);
+                }
+                c => s.push(c),
+            }
+        }
+        s
+    }
+
+    fn escape(&mut self, s: &mut String, quote: char) {
+        let at = self.pos - 1;
+        let c = match self.peek() {
+            Some(c) => c,
+            // the string reports being unterminated
+            None => return,
+        };
+        self.pos += 1;
+        match c {
+            '"' => s.push('"'),
+            '\\' => s.push('\\'),
+            '/' => s.push('/'),
+            'b' => s.push('\u{8}'),
+            'f' => s.push('\u{c}'),
+            'n' => s.push('\n'),
+            'r' => s.push('\r'),
+            't' => s.push('\t'),
+            'u' => {
+                let unit = self.hex_escape(at);
+                let c = match unit {
+                    Some(high @ 0xd800..=0xdbff)
+                        if self.peek() == Some('\\') && self.peek_at(1) == Some('u') =>
+                    {
+                        self.pos += 1;
+                        match self.hex_escape(at) {
+                            Some(low @ 0xdc00..=0xdfff) => {
+                                char::from_u32(0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00))
+                            }
+                            _ => None,
+                        }
+                    }
+                    Some(unit) => char::from_u32(unit),
+                    None => return,
+                };
+                match c {
+                    Some(c) => s.push(c),
+                    None => {
+                        self.problem(at, "unpaired surrogate in a `\\u` escape", None);
+                        s.push('\u{fffd}');
+                    }
+                }
+            }
+            '\'' => {
+                if quote == '"' {
+                    self.json5(
+                        at,
+                        "invalid escape `\\'`",
+                        Some("single quotes need no escape in JSON strings"),
+                    );
+                }
+                s.push('\'');
+            }
+            'v' => {
+                self.json5(at, "invalid escape `\\v`", Some("write it as \\u000b"));
+                s.push('\u{b}');
+            }
+            '0' if !self.peek().is_some_and(|c| c.is_ascii_digit()) => {
+                self.json5(at, "invalid escape `\\0`", Some("write it as \\u0000"));
+                s.push('\0');
+            }
+            'x' => {
+                let digits: String = self.chars[self.pos..].iter().take(2).collect();
+                match u8::from_str_radix(&digits, 16) {
+                    Ok(byte) if digits.chars().all(|c| c.is_ascii_hexdigit()) => {
+                        self.pos += 2;
+                        let hint = Some("write it as \\u00 and the two hex digits");
+                        self.json5(at, "`\\x` escapes are not JSON", hint);
+                        s.push(byte as char);
+                    }
+                    _ => {
+                        self.problem(at, "invalid `\\x` escape", Some("it takes two hex digits"));
+                    }
+                }
+            }
+            // JSON5 continues strings on the next line after a backslash
+            '\n' | '\r' => {
+                if c == '\r' && self.peek() == Some('\n') {
+                    self.pos += 1;
+                }
+                let hint = Some("join the lines, or escape the line break as \\n");
+                self.json5(at, "escaped line break in a string", hint);
+            }
+            c => {
+                let message = format!("invalid escape `\\{}`", c);
+                self.problem(at, message, Some("backslashes are written \\\\"));
+                s.push('\\');
+                s.push(c);
+            }
+        }
+    }
+
+    /// The four hex digits after `\u`, the `u` is already consumed.
+    fn hex_escape(&mut self, at: usize) -> Option<u32> {
+        if self.peek() == Some('u') {
+            self.pos += 1;
+        }
+        let digits: String = self.chars[self.pos..].iter().take(4).collect();
+        match (digits.len(), u32::from_str_radix(&digits, 16)) {
+            (4, Ok(unit)) if digits.chars().all(|c| c.is_ascii_hexdigit()) => {
+                self.pos += 4;
+                Some(unit)
+            }
+            _ => {
+                self.problem(
+                    at,
+                    "invalid `\\u` escape",
+                    Some("it takes four hex digits, `\\u00e9`"),
+                );
+                None
+            }
+        }
+    }
+
+    fn word(&mut self) -> Value {
+        let start = self.pos;
+        let word = self.take_word();
+        match word.as_str() {
+            "true" => return Value::Bool(true),
+            "false" => return Value::Bool(false),
+            "null" => return Value::Null,
+            "NaN" | "Infinity" => {
+                return self.non_finite(start, format!("`{}` is not JSON", word));
+            }
+            _ => {}
+        }
+        let truncated = ["true", "false", "null"]
+            .into_iter()
+            .find(|literal| self.pos == self.chars.len() && literal.starts_with(&word));
+        if let Some(literal) = truncated {
+            let message = format!("the input ends in the middle of `{}`", literal);
+            self.problem(start, message, None);
+            return serde_json::from_str(literal).unwrap_or_default();
+        }
+        let (message, hint, value) = match word.to_lowercase().as_str() {
+            "true" => (
+                None,
+                "true, false and null are lowercase",
+                Value::Bool(true),
+            ),
+            "false" => (
+                None,
+                "true, false and null are lowercase",
+                Value::Bool(false),
+            ),
+            "null" | "none" | "nil" | "undefined" => (None, "use null", Value::Null),
+            "nan" | "infinity" => (None, NON_FINITE_HINT, Value::Null),
+            _ => (
+                Some(format!("unexpected `{}`", word)),
+                "strings are in double quotes",
+                Value::String(word.clone()),
+            ),
+        };
+        let message = message.unwrap_or_else(|| format!("`{}` is not JSON", word));
+        self.problem(start, message, Some(hint));
+        value
+    }
+
+    fn number(&mut self) -> Option<Value> {
+        let start = self.pos;
+        if matches!(self.peek(), Some('-' | '+'))
+            && self.peek_at(1).is_some_and(char::is_alphabetic)
+        {
+            self.pos += 1;
+            let word = self.take_word();
+            let literal: String = self.chars[start..self.pos].iter().collect();
+            let message = format!("`{}` is not a JSON number", literal);
+            if matches!(word.as_str(), "Infinity" | "NaN") {
+                return Some(self.non_finite(start, message));
+            }
+            self.problem(start, message, Some("strings are in double quotes"));
+            return Some(Value::Null);
+        }
+
+        self.pos += 1;
+        while let Some(c) = self.peek() {
+            let exponent_sign =
+                matches!(c, '+' | '-') && matches!(self.chars[self.pos - 1], 'e' | 'E');
+            match c.is_ascii_alphanumeric() || c == '.' || exponent_sign {
+                true => self.pos += 1,
+                false => break,
+            }
+        }
+        let literal: String = self.chars[start..self.pos].iter().collect();
+        if is_number(&literal) {
+            return serde_json::from_str(&literal).ok();
+        }
+
+        let (normalized, hint, json5) = normalize_number(&literal);
+        let message = format!("`{}` is not a JSON number", literal);
+        match json5 {
+            true => self.json5(start, message, Some(hint)),
+            false => self.problem(start, message, Some(hint)),
+        }
+        normalized.and_then(|n| serde_json::from_str(&n).ok())
+    }
+}
+
+/// Whether a literal follows the number grammar of RFC 8259.
+fn is_number(literal: &str) -> bool {
+    let s = literal.strip_prefix('-').unwrap_or(literal);
+    let digits = |s: &str| s.len() - s.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+
+    let int = digits(s);
+    if int == 0 || (int > 1 && s.starts_with('0')) {
+        return false;
+    }
+    let mut rest = &s[int..];
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let n = digits(fraction);
+        if n == 0 {
+            return false;
+        }
+        rest = &fraction[n..];
+    }
+    if let Some(exponent) = rest.strip_prefix(['e', 'E']) {
+        let exponent = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+        let n = digits(exponent);
+        if n == 0 {
+            return false;
+        }
+        rest = &exponent[n..];
+    }
+    rest.is_empty()
+}
+
+/// The closest JSON number to a literal that is not one, what is wrong with it and
+/// whether JSON5 takes it.
+fn normalize_number(literal: &str) -> (Option<String>, &'static str, bool) {
+    let (sign, unsigned) = match literal.split_at(1) {
+        ("-", rest) => ("-", rest),
+        ("+", rest) => ("", rest),
+        _ => ("", literal),
+    };
+    let hex = unsigned
+        .strip_prefix("0x")
+        .or_else(|| unsigned.strip_prefix("0X"));
+    if let Some(hex) = hex {
+        let value = u64::from_str_radix(hex, 16)
+            .ok()
+            .map(|n| format!("{}{}", sign, n));
+        let json5 = value.is_some();
+        return (
+            value,
+            "hexadecimal numbers are not JSON, write it in decimal",
+            json5,
+        );
+    }
+
+    let leading_zeros =
+        unsigned.len() > 1 && unsigned.starts_with('0') && unsigned.as_bytes()[1].is_ascii_digit();
+    let hint = if literal.starts_with('+') {
+        "JSON numbers have no `+` sign"
+    } else if unsigned.starts_with('.') {
+        "write a 0 before the decimal point"
+    } else if unsigned.ends_with('.') || unsigned.contains(".e") || unsigned.contains(".E") {
+        "write a digit after the decimal point"
+    } else if leading_zeros {
+        "JSON numbers have no leading zeros"
+    } else {
+        return (
+            None,
+            "it is neither a number nor a string in double quotes",
+            false,
+        );
+    };
+
+    let trimmed = unsigned.trim_start_matches('0');
+    let mut normalized = match trimmed.starts_with(|c: char| c.is_ascii_digit()) {
+        true => trimmed.to_string(),
+        false => format!("0{}", trimmed),
+    };
+    normalized = normalized.replace(".e", ".0e").replace(".E", ".0E");
+    if normalized.ends_with('.') {
+        normalized.push('0');
+    }
+    let normalized = format!("{}{}", sign, normalized);
+    let normalized = Some(normalized).filter(|n| is_number(n));
+    let json5 = !leading_zeros && normalized.is_some();
+    (normalized, hint, json5)
+}
+
+/// Line and column of character offsets.
+pub struct Lines<'a> {
+    text: &'a str,
+    /// The character offset and the byte offset of every line.
+    starts: Vec<(usize, usize)>,
+}
+
+impl Lines<'_> {
+    pub fn new(text: &str) -> Lines<'_> {
+        let mut starts = vec![(0, 0)];
+        for (i, (byte, c)) in text.char_indices().enumerate() {
+            if c == '\n' {
+                starts.push((i + 1, byte + 1));
+            }
+        }
+        Lines { text, starts }
+    }
+
+    pub fn position(&self, offset: usize) -> Position {
+        let line = self.starts.partition_point(|&(start, _)| start <= offset) - 1;
+        Position {
+            line: line + 1,
+            column: offset - self.starts[line].0 + 1,
+        }
+    }
+
+    fn line(&self, line: usize) -> &str {
+        let start = self.starts[line - 1].1;
+        let end = self
+            .starts
+            .get(line)
+            .map_or(self.text.len(), |&(_, byte)| byte);
+        self.text[start..end].trim_end_matches(['\n', '\r'])
+    }
+
+    /// The line of a problem with a caret under it, and the hint.
+    pub fn snippet(&self, problem: &Problem) -> String {
+        // long lines, as in minified documents, are cut around the problem
+        const WINDOW: usize = 100;
+        let position = self.position(problem.offset);
+        let line: Vec<char> = self.line(position.line).chars().collect();
+        let column = position.column - 1;
+        let start = column
+            .saturating_sub(WINDOW / 2)
+            .min(line.len().saturating_sub(WINDOW));
+        let end = (start + WINDOW).min(line.len());
+
+        let mut shown: String = line[start..end].iter().collect();
+        let mut caret: String = line[start..column.min(line.len())]
+            .iter()
+            .map(|&c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        if start > 0 {
+            shown.insert(0, '…');
+            caret.insert(0, ' ');
+        }
+        if end < line.len() {
+            shown.push('…');
+        }
+
+        let number = position.line.to_string();
+        let gutter = " ".repeat(number.len());
+        let mut snippet = format!(
+            "{} |\n{} | {}\n{} | {}^",
+            gutter, number, shown, gutter, caret
+        );
+        if let Some(hint) = problem.hint {
+            snippet.push_str(&format!("\n{} = hint: {}", gutter, hint));
+        }
+        snippet
+    }
+}
+
+/// Reads the JSON5 and JSONC that serde_json rejects. The error is on the first mistake
+/// JSON5 does not allow either, with its line and a hint rather than only a position.
+/// NaN and Infinity are read as null, with a warning.
+pub fn relaxed(text: &str, error: serde_json::Error) -> anyhow::Result<Value> {
+    let parsed = parse(text);
+    let problem = parsed.problems.iter().find(|p| !p.warning && !p.json5);
+    match (problem, parsed.value) {
+        (None, Some(value)) => {
+            let lines = Lines::new(text);
+            for problem in parsed.problems.iter().filter(|p| p.lossy) {
+                let position = lines.position(problem.offset);
+                eprintln!(
+                    "Warning: line {}, column {}: {}, it is read as null",
+                    position.line, position.column, problem.message
+                );
+            }
+            Ok(value)
+        }
+        (None, None) => Err(error.into()),
+        (Some(problem), _) => {
+            let lines = Lines::new(text);
+            let position = lines.position(problem.offset);
+            Err(anyhow::anyhow!(
+                "line {}, column {}: {}\n{}",
+                position.line,
+                position.column,
+                problem.message,
+                lines.snippet(problem)
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, Lines};
+    use serde_json::{json, Value};
+
+    /// The line, column and message of every problem, in order of where they are.
+    fn problems(text: &str) -> Vec<(usize, usize, String)> {
+        let lines = Lines::new(text);
+        let mut problems = parse(text).problems;
+        problems.sort_by_key(|problem| problem.offset);
+        problems
+            .iter()
+            .map(|problem| {
+                let position = lines.position(problem.offset);
+                (position.line, position.column, problem.message.clone())
+            })
+            .collect()
+    }
+
+    /// The repaired value, and each problem with whether JSON5 allows what it is about.
+    fn repaired(text: &str) -> (Value, Vec<(String, bool)>) {
+        let parsed = parse(text);
+        let mut problems = parsed.problems;
+        problems.sort_by_key(|problem| problem.offset);
+        let problems = problems.into_iter().map(|p| (p.message, p.json5)).collect();
+        (parsed.value.unwrap(), problems)
+    }
+
+    /// Whether every problem of `text` is something JSON5 allows.
+    fn all_json5(text: &str) -> bool {
+        repaired(text).1.iter().all(|(_, json5)| *json5)
+    }
+
+    fn messages(text: &str) -> Vec<String> {
+        problems(text).into_iter().map(|(_, _, m)| m).collect()
+    }
+
+    #[test]
+    fn every_mistake() {
+        assert_eq!(
+            problems(r#"{"a": 1 "b": [1, 2,, 3], "c": tru"#),
+            [
+                (1, 1, "`{` is never closed".to_string()),
+                (1, 9, "expected `,` or `}`".to_string()),
+                (1, 20, "expected a value, found `,`".to_string()),
+                (1, 31, "the input ends in the middle of `true`".to_string()),
+            ]
+        );
+        assert_eq!(
+            problems("{\n\t\"é\": \"x\",\n\t\"b\": [1, 2}\n"),
+            [
+                (1, 1, "`{` is never closed".to_string()),
+                (
+                    3,
+                    12,
+                    "expected `,` or `]` between items, found `}`".to_string()
+                ),
+                (3, 12, "`}` does not close the `[`".to_string()),
+            ]
+        );
+        // columns count characters, not bytes
+        assert_eq!(problems(r#"["é😀", @]"#)[0].1, 8);
+        // a carriage return ends no line on its own
+        assert_eq!(problems("[1,\r\n2 3]")[0].0, 2);
+    }
+
+    #[test]
+    fn messages_of_mistakes() {
+        let cases = [
+            ("", "the input is empty"),
+            ("   ", "the input is empty"),
+            ("1 2", "unexpected content after the document"),
+            ("[1, 2", "`[` is never closed"),
+            (r#"{"a" 1}"#, "expected `:` after the key \"a\""),
+            (r#"{"a"= 1}"#, "expected `:`, found `=`"),
+            ("{1: 2}", "the key `1` is not quoted"),
+            ("{]", "`]` does not close the `{`"),
+            ("[}", "`}` does not close the `[`"),
+            ("[1 2]", "expected `,` or `]`"),
+            ("[1; 2]", "expected `,` or `]` between items, found `;`"),
+            ("{\"a\": \"x\n\"}", "unterminated string"),
+            ("[\"\\q\"]", "invalid escape `\\q`"),
+            ("[\"\\u12\"]", "invalid `\\u` escape"),
+            ("[\"\\ud800\"]", "unpaired surrogate in a `\\u` escape"),
+            ("[\"a\tb\"]", "the control character U+0009 must be escaped"),
+            ("[/* x", "unterminated comment"),
+            ("[01]", "`01` is not a JSON number"),
+            ("[1e]", "`1e` is not a JSON number"),
+            ("[-Infinity]", "`-Infinity` is not a JSON number"),
+            ("[NaN]", "`NaN` is not JSON"),
+            ("[hello]", "unexpected `hello`"),
+            ("[#]", "expected a value, found `#`"),
+            ("{\"a\": nul", "the input ends in the middle of `null`"),
+            ("[fals", "the input ends in the middle of `false`"),
+        ];
+        for (text, message) in cases {
+            assert!(
+                messages(text).iter().any(|m| m == message),
+                "{:?}: {:?}",
+                text,
+                messages(text)
+            );
+        }
+        for valid in [
+            "0",
+            "-0.5e+10",
+            r#"{"a": [true, false, null, "\u00e9\ud83d\ude00"]}"#,
+        ] {
+            assert!(problems(valid).is_empty(), "{}", valid);
+        }
+    }
+
+    #[test]
+    fn depth() {
+        let text = "[".repeat(200);
+        let parsed = parse(&text);
+        assert_eq!(parsed.problems.len(), 1);
+        assert_eq!(parsed.problems[0].message, "nested deeper than 128 levels");
+        assert_eq!(parsed.problems[0].offset, 128);
+    }
+
+    #[test]
+    fn repeated_keys() {
+        let parsed = parse(r#"{"a": 1, "b": 2, "a": 3}"#);
+        let value = parsed.value.unwrap();
+        assert_eq!(value.to_string(), r#"{"a":3,"b":2}"#);
+        assert_eq!(parsed.problems.len(), 1);
+        assert!(parsed.problems[0].warning);
+        assert_eq!(parsed.problems[0].offset, 17);
+    }
+
+    #[test]
+    fn snippets() {
+        let text = "{\n\t\"b\": [1, 2}\n";
+        let lines = Lines::new(text);
+        let problems = parse(text).problems;
+        assert_eq!(
+            lines.snippet(&problems[0]),
+            "  |\n2 | \t\"b\": [1, 2}\n  | \t          ^"
+        );
+        let never_closed = problems.iter().find(|p| p.offset == 0).unwrap();
+        assert_eq!(
+            lines.snippet(never_closed),
+            "  |\n1 | {\n  | ^\n  = hint: add the missing closing bracket"
+        );
+
+        // the gutter is as wide as the line number
+        let text = format!("[{}\n@]", "\n".repeat(10));
+        let lines = Lines::new(&text);
+        assert_eq!(
+            lines.snippet(&parse(&text).problems[0]),
+            "   |\n12 | @]\n   | ^"
+        );
+
+        // long lines are cut around the problem
+        let text = format!("[{}@{}]", "1,".repeat(100), ",2".repeat(100));
+        let lines = Lines::new(&text);
+        let snippet = lines.snippet(&parse(&text).problems[0]);
+        let shown = format!("…{}@{}…", &"1,".repeat(100)[150..], &",2".repeat(100)[..49]);
+        assert_eq!(
+            snippet,
+            format!("  |\n1 | {}\n  |  {}^", shown, " ".repeat(50))
+        );
+    }
+
+    #[test]
+    fn relaxed_errors() {
+        let error = crate::json::parse("{\n  \"a\": 1\n  \"b\": 2,\n}").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 3, column 3: expected `,` or `}`\n  |\n3 |   \"b\": 2,\n  |   ^\n  = hint: add the missing comma between members"
+        );
+    }
+
+    #[test]
+    fn json5() {
+        let (value, problems) = repaired("{// c\n\"a\": /* b */ 1}");
+        assert_eq!(value, json!({"a": 1}));
+        assert_eq!(problems.len(), 2);
+        assert!(problems
+            .iter()
+            .all(|(m, json5)| m == "comments are not allowed in JSON" && *json5));
+
+        let (value, problems) = repaired(r"{'a': 'it\'s', b: [1, 2,], $c_d: 3,}");
+        assert_eq!(value, json!({"a": "it's", "b": [1, 2], "$c_d": 3}));
+        let problems: Vec<_> = problems.into_iter().map(|(m, _)| m).collect();
+        assert_eq!(
+            problems,
+            [
+                "strings must be in double quotes",
+                "strings must be in double quotes",
+                "the key `b` is not quoted",
+                "trailing comma",
+                "the key `$c_d` is not quoted",
+                "trailing comma",
+            ]
+        );
+        assert!(all_json5(r"{'a': 'it\'s', b: [1, 2,], $c_d: 3,}"));
+
+        let (value, _) = repaired("[0x1F, +1, .5, 1., -0XAB, 1.e2]");
+        assert_eq!(value.to_string(), "[31,1,0.5,1.0,-171,1.0e2]");
+        assert!(all_json5("[0x1F, +1, .5, 1., -0XAB, 1.e2]"));
+
+        let (value, _) = repaired("[\"\\x41\", \"a\\\nb\", \"\\v\\0\", \"\\'\"]");
+        assert_eq!(value, json!(["A", "ab", "\u{b}\u{0}", "'"]));
+        assert!(all_json5("[\"\\x41\", \"a\\\nb\", \"\\v\\0\", \"\\'\"]"));
+
+        assert!(all_json5("\u{feff}[1,\u{a0}2]"));
+        assert_eq!(repaired("\u{feff}[1]").0, json!([1]));
+
+        // JSON5 numbers that JSON has no value for
+        let text = "[NaN, Infinity, -Infinity, +Infinity, -NaN]";
+        assert_eq!(repaired(text).0, json!([null, null, null, null, null]));
+        assert!(all_json5(text));
+        assert!(parse(text).problems.iter().all(|p| p.lossy));
+    }
+
+    #[test]
+    fn beyond_json5() {
+        // a key starting with a digit is not a JSON5 identifier
+        let (value, problems) = repaired("{1a: 2}");
+        assert_eq!(value, json!({"1a": 2}));
+        assert_eq!(
+            problems,
+            [("the key `1a` is not quoted".to_string(), false)]
+        );
+
+        // JSON5 spells NaN and Infinity like JavaScript
+        let text = "[True, None, nan, -infinity, nil, FALSE, undefined, 01]";
+        let (value, problems) = repaired(text);
+        assert_eq!(value, json!([true, null, null, null, null, false, null, 1]));
+        assert_eq!(problems.len(), 8);
+        assert!(problems.iter().all(|(_, json5)| !json5));
+
+        assert!(!all_json5("[\"\\x4\"]"));
+        assert!(!all_json5("[/* x"));
+    }
+
+    #[test]
+    fn relaxed_documents() {
+        let jsonc = "{\n  // the port\n  \"port\": 8080,\n  \"hosts\": [\"a\", \"b\",],\n}\n";
+        assert_eq!(
+            crate::json::parse(jsonc).unwrap(),
+            json!({"port": 8080, "hosts": ["a", "b"]})
+        );
+        let json5 =
+            "{unquoted: 'and you can quote me on that', hex: 0xDECAF, lead: .8675309, plus: +1,}";
+        assert_eq!(
+            crate::json::parse(json5).unwrap(),
+            json!({"unquoted": "and you can quote me on that", "hex": 912559, "lead": 0.8675309, "plus": 1})
+        );
+        assert_eq!(
+            crate::json::parse("{a: Infinity, b: -Infinity, c: NaN}").unwrap(),
+            json!({"a": null, "b": null, "c": null})
+        );
+        let error = crate::json::parse("{\"a\": True}").unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("line 1, column 7: `True` is not JSON\n"));
+    }
+}
@@ -0,0 +1,151 @@
+//! `json validate`, checking a document against a JSON Schema.
+
+use anyhow::Context;
+use serde_json::Value;
+
+use super::schema::{Draft, LoadError, Validator};
+use super::source::SourceMap;
+use super::{parse, pointer, source_name};
+use crate::exit::ExitStatus;
+use crate::input::{decode_utf8, read_all, InputSource};
+
+const INVALID_DOCUMENT: ExitStatus = ExitStatus(1, "The document does not match the schema");
+const INVALID_SCHEMA: ExitStatus = ExitStatus(3, "The schema is invalid");
+const PARSE_ERROR: ExitStatus = ExitStatus(4, "Could not read or parse the JSON");
+
+#[derive(clap::Args, Debug)]
+pub struct ValidateArgs {
+    /// The JSON Schema file
+    #[clap(long, short)]
+    schema: String,
+
+    /// The draft of the schema, when its `$schema` does not say; 2020-12 by default
+    #[clap(long, arg_enum)]
+    draft: Option<Draft>,
+
+    /// Also check the `format` of strings, such as `date-time`, `email` and `uuid`
+    #[clap(long)]
+    formats: bool,
+
+    #[clap(flatten)]
+    input: InputSource,
+}
+
+pub fn run(args: ValidateArgs) -> anyhow::Result<()> {
+    let validator = load_schema(&args)?;
+    let name = source_name(&args.input);
+    let text = read_all(args.input)
+        .and_then(decode_utf8)
+        .context(PARSE_ERROR)?;
+    let document: Value = parse(&text)
+        .context("Parse Valid JSON")
+        .context(PARSE_ERROR)?;
+
+    let violations = validator.validate(&document);
+    if violations.is_empty() {
+        println!("OK");
+        return Ok(());
+    }
+
+    let source = SourceMap::new(&text);
+    let mut located: Vec<_> = violations
+        .into_iter()
+        .map(|violation| {
+            let instance = pointer::format(&violation.instance);
+            let position = source.get(&instance);
+            (position, instance, violation)
+        })
+        .collect();
+    located.sort_by_key(|(position, _, _)| *position);
+    for (position, instance, violation) in &located {
+        let position = position.map_or_else(String::new, |p| format!("{}:{}", p.line, p.column));
+        println!(
+            "{}:{}: #{}: {} (schema: {})",
+            name, position, instance, violation.message, violation.keyword
+        );
+    }
+    let plural = if located.len() == 1 { "" } else { "s" };
+    Err(anyhow::anyhow!("{} violation{}", located.len(), plural)).context(INVALID_DOCUMENT)
+}
+
+fn load_schema(args: &ValidateArgs) -> anyhow::Result<Validator> {
+    let path = std::fs::canonicalize(&args.schema)
+        .with_context(|| format!("Reading the schema '{}'", args.schema))
+        .context(PARSE_ERROR)?;
+    let text = std::fs::read_to_string(&path)
+        .with_context(|| format!("Reading the schema '{}'", args.schema))
+        .context(PARSE_ERROR)?;
+    let schema: Value = parse(&text)
+        .with_context(|| format!("Parsing the schema '{}'", args.schema))
+        .context(PARSE_ERROR)?;
+
+    let declared = schema.get("$schema").and_then(Value::as_str);
+    let draft = match (args.draft, declared) {
+        (Some(draft), _) => draft,
+        (None, None) => Draft::Draft2020,
+        (None, Some(uri)) => match Draft::from_uri(uri) {
+            Some(draft) => draft,
+            None => {
+                return Err(anyhow::anyhow!(
+                    "Unsupported `$schema` '{}', choose the draft with --draft",
+                    uri
+                ))
+                .context(INVALID_SCHEMA)
+            }
+        },
+    };
+
+    let uri = format!("file://{}", path.display());
+    Validator::new(schema, uri, draft, args.formats).map_err(|e| match e {
+        LoadError::Parse(e) => e.context(PARSE_ERROR),
+        LoadError::Invalid(problems) => {
+            anyhow::anyhow!(problems.join("\n")).context(INVALID_SCHEMA)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{run, ValidateArgs};
+    use crate::exit;
+    use crate::input::InputSource;
+
+    /// The exit status of validating `document` against a schema file with `schema` in it.
+    fn status(schema: Option<&str>, document: &str) -> i32 {
+        let path = std::env::temp_dir().join(format!("devstuff-validate-{}", std::process::id()));
+        if let Some(schema) = schema {
+            std::fs::write(&path, schema).unwrap();
+        }
+        let args = ValidateArgs {
+            schema: path.display().to_string(),
+            draft: None,
+            formats: false,
+            input: InputSource {
+                input: Some(document.to_string()),
+                raw: true,
+            },
+        };
+        let status = run(args).map_or_else(|e| exit::code(&e), |_| 0);
+        let _ = std::fs::remove_file(&path);
+        status
+    }
+
+    #[test]
+    fn exit_status() {
+        let schema = r#"{"type": "object", "required": ["a"]}"#;
+        assert_eq!(status(Some(schema), r#"{"a": 1}"#), 0);
+        assert_eq!(status(Some(schema), r#"{"b": 1}"#), 1);
+        assert_eq!(status(Some(schema), "[1, 2"), 4);
+
+        assert_eq!(status(Some(r#"{"type": "nothing"}"#), "1"), 3);
+        assert_eq!(status(Some(r##"{"$ref": "#/$defs/missing"}"##), "1"), 3);
+        assert_eq!(status(Some(r#"{"pattern": "(a"}"#), "1"), 3);
+        assert_eq!(
+            status(Some(r#"{"$schema": "https://example.com/schema"}"#), "1"),
+            3
+        );
+
+        assert_eq!(status(Some("{\"type\": "), "1"), 4);
+        assert_eq!(status(None, "1"), 4);
+    }
+}
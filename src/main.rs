@@ -1,10 +1,24 @@
-use std::io::BufRead;
-
 use anyhow::Context;
 use clap::Parser;
-use crypto_hash::{hex_digest, Algorithm};
 use uuid::Uuid;
 
+use b64::Base64Arg;
+use codec::{Codec, CodecArg};
+use hash::sri::HtmlSriArgs;
+use hash::HashArg;
+use input::{for_text_input, InputSource};
+use json::JsonArg;
+use url::UrlArg;
+
+mod b64;
+mod codec;
+mod exit;
+mod hash;
+mod input;
+mod json;
+mod progress;
+mod url;
+
 /// Simple program to greet a person
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = None)]
@@ -19,166 +33,78 @@ enum ToolType {
     /// Minify or unminify html
     Html(HtmlArg),
 
-    /// Minify or unminify json
+    /// Minify, unminify, query, validate, lint, repair, diff, patch and merge json, or infer its schema
     Json(JsonArg),
 
     /// Base64 Encoding and Decoding
     B64(Base64Arg),
 
-    /// Popular hash functions (Blake3, SHA1, SHA256, SHA512)
+    /// Base32 and base32hex Encoding and Decoding
+    B32(CodecArg),
+
+    /// Base58 and Base58Check Encoding and Decoding
+    B58(CodecArg),
+
+    /// Ascii85 and Z85 Encoding and Decoding
+    B85(CodecArg),
+
+    /// Hex Encoding and Decoding
+    Hex(CodecArg),
+
+    /// Percent-encoding, and parsing and building URLs
+    Url(UrlArg),
+
+    /// Hash functions and checksums (MD5, SHA-1, SHA-2, SHA-3, BLAKE2, Blake3, CRC, xxHash, ...)
     Hash(HashArg),
 
     /// Generate an UUID
     Uuid,
 }
 
-#[derive(clap::Args, Debug)]
-struct HashArg {
-    #[clap(subcommand)]
-    action: HashAction,
-}
-
-#[derive(clap::Subcommand, Debug)]
-enum HashAction {
-    Md5(InputSource),
-    Sha1(InputSource),
-    Sha256(InputSource),
-    Sha512(InputSource),
-    Blake3(InputSource),
-}
-
 #[derive(clap::Args, Debug)]
 struct HtmlArg {
     #[clap(subcommand)]
     action: HtmlAction,
 }
 
-#[derive(clap::Args, Debug)]
-struct JsonArg {
-    #[clap(subcommand)]
-    action: JsonAction,
-}
-
-#[derive(clap::Args, Debug)]
-struct Base64Arg {
-    #[clap(subcommand)]
-    action: Base64Action,
-}
-
 #[derive(clap::Subcommand, Debug)]
 enum HtmlAction {
     Minify(InputSource),
-}
 
-#[derive(clap::Subcommand, Debug)]
-enum JsonAction {
-    Minify(InputSource),
-    Unminify(InputSource),
-}
-
-#[derive(clap::Subcommand, Debug)]
-enum Base64Action {
-    Encode(InputSource),
-    Decode(InputSource),
+    /// Add, update or verify the integrity attributes of <script> and <link> tags
+    Sri(HtmlSriArgs),
 }
 
-#[derive(clap::Args, Debug)]
-struct InputSource {
-    /// Filename to read from or raw input (must specify --raw)
-    input: Option<String>,
-
-    /// must be provided if raw input is provided
-    #[clap(long)]
-    raw: bool,
+fn main() {
+    if let Err(e) = run(Args::parse()) {
+        eprintln!("Error: {:?}", e);
+        std::process::exit(exit::code(&e));
+    }
 }
 
-fn main() -> anyhow::Result<()> {
-    let args = Args::parse();
-
+fn run(args: Args) -> anyhow::Result<()> {
     match args.tool_type {
         ToolType::Html(h) => match h.action {
-            HtmlAction::Minify(is) => for_input(is, |input| {
+            HtmlAction::Minify(is) => for_text_input(is, |input| {
                 println!("{}", minify::html::minify(&input));
                 Ok(())
             })
             .context("Minify HTML"),
+            HtmlAction::Sri(args) => {
+                hash::sri::run_html(args).context("HTML Subresource Integrity")
+            }
         },
-        ToolType::Json(j) => match j.action {
-            JsonAction::Minify(is) => for_input(is, |input| {
-                println!("{}", minify::json::minify(&input));
-                Ok(())
-            })
-            .context("Minify JSON"),
-            JsonAction::Unminify(is) => for_input(is, |input| {
-                let s: serde_json::Value =
-                    serde_json::from_str(&input).context("Parse Valid JSON")?;
-                println!("{}", serde_json::to_string_pretty(&s).unwrap());
-                Ok(())
-            })
-            .context("Minify JSON"),
-        },
-        ToolType::B64(b) => match b.action {
-            Base64Action::Encode(is) => for_input(is, |input| {
-                println!("{}", base64::encode(input));
-                Ok(())
-            })
-            .context("Base64 Encoding"),
-            Base64Action::Decode(is) => for_input(is, |input| {
-                println!("{:}", String::from_utf8(base64::decode(input)?)?);
-                Ok(())
-            })
-            .context("Base64 Decoding"),
-        },
-        ToolType::Hash(h) => match h.action {
-            HashAction::Md5(is) => hash(is, Algorithm::MD5, "MD5"),
-            HashAction::Sha1(is) => hash(is, Algorithm::SHA1, "SHA1"),
-            HashAction::Sha256(is) => hash(is, Algorithm::SHA256, "SHA256"),
-            HashAction::Sha512(is) => hash(is, Algorithm::SHA512, "SHA512"),
-            HashAction::Blake3(is) => for_input(is, |input| {
-                println!("{}", blake3::hash(input.as_bytes()).to_hex());
-                Ok(())
-            })
-            .context("Blake3 Hash"),
-        },
+        ToolType::Json(j) => json::run(j),
+        ToolType::B64(b) => b64::run(b),
+        ToolType::B32(c) => codec::run(Codec::Base32, c),
+        ToolType::B58(c) => codec::run(Codec::Base58, c),
+        ToolType::B85(c) => codec::run(Codec::Base85, c),
+        ToolType::Hex(c) => codec::run(Codec::Hex, c),
+        ToolType::Url(u) => url::run(u),
+        ToolType::Hash(h) => hash::run(h),
         ToolType::Uuid => {
             println!("{}", Uuid::new_v4());
             Ok(())
         }
     }
 }
-
-fn hash(is: InputSource, algo: Algorithm, name: &str) -> anyhow::Result<()> {
-    for_input(is, |input| {
-        println!("{}", hex_digest(algo, input.as_bytes()));
-        Ok(())
-    })
-    .context(format!("{} Hash", name))
-}
-
-fn for_input(is: InputSource, f: impl Fn(String) -> anyhow::Result<()>) -> anyhow::Result<()> {
-    if let Some(input) = is.input {
-        if is.raw {
-            return f(input);
-        }
-        let lines = std::fs::read_to_string(input.clone()).context(format!(
-            "Reading from file '{}', if this is raw input then specify --raw flag",
-            input
-        ))?;
-        return f(lines);
-    }
-
-    if !atty::is(atty::Stream::Stdin) {
-        let stdin = std::io::stdin();
-        let mut lines = Vec::new();
-        for line in stdin.lock().lines() {
-            let line = line.expect("Could not read line from standard in");
-            lines.push(line);
-        }
-
-        return f(lines.join("\n"));
-    }
-
-    return Err(anyhow::anyhow!(
-        "Not input source found. You can either pipe the input or specify a file or plaintext"
-    ));
-}
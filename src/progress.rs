@@ -0,0 +1,80 @@
+use std::io::{Read, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+const MIB: f64 = 1024.0 * 1024.0;
+
+/// Progress indicator drawn on stderr, it does nothing unless stderr is a terminal.
+pub struct Progress {
+    enabled: bool,
+    total: Option<u64>,
+    done: AtomicU64,
+    last_draw: Mutex<Option<Instant>>,
+}
+
+impl Progress {
+    pub fn new(requested: bool, total: Option<u64>) -> Self {
+        Self {
+            enabled: requested && atty::is(atty::Stream::Stderr),
+            total,
+            done: AtomicU64::new(0),
+            last_draw: Mutex::new(None),
+        }
+    }
+
+    /// Records `n` more bytes as processed, can be called from multiple threads.
+    pub fn advance(&self, n: u64) {
+        if !self.enabled {
+            return;
+        }
+        let done = self.done.fetch_add(n, Ordering::Relaxed) + n;
+
+        let mut last_draw = self.last_draw.lock().unwrap();
+        if matches!(*last_draw, Some(at) if at.elapsed() < REDRAW_INTERVAL) {
+            return;
+        }
+        *last_draw = Some(Instant::now());
+
+        let line = match self.total {
+            Some(total) if total > 0 => format!(
+                "{:>3}% ({:.1}/{:.1} MiB)",
+                done * 100 / total,
+                done as f64 / MIB,
+                total as f64 / MIB
+            ),
+            _ => format!("{:.1} MiB", done as f64 / MIB),
+        };
+        let mut stderr = std::io::stderr();
+        let _ = write!(stderr, "\r{}", line);
+        let _ = stderr.flush();
+    }
+
+    /// Clears the indicator so it does not mix with the output.
+    pub fn finish(&self) {
+        if self.enabled && self.last_draw.lock().unwrap().is_some() {
+            let _ = write!(std::io::stderr(), "\r\x1b[2K");
+        }
+    }
+}
+
+/// Reports every byte read through it to a [`Progress`].
+pub struct ProgressReader<'a, R> {
+    inner: R,
+    progress: &'a Progress,
+}
+
+impl<'a, R> ProgressReader<'a, R> {
+    pub fn new(inner: R, progress: &'a Progress) -> Self {
+        Self { inner, progress }
+    }
+}
+
+impl<R: Read> Read for ProgressReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.progress.advance(n as u64);
+        Ok(n)
+    }
+}
@@ -0,0 +1,620 @@
+//! Percent-encoding (RFC 3986) and the parts of URLs.
+
+use anyhow::Context;
+use serde_json::{Map, Value};
+
+use crate::codec::write_decoded;
+use crate::input::{for_input, for_text_input, InputSource};
+use crate::json;
+
+#[derive(clap::Args, Debug)]
+pub struct UrlArg {
+    #[clap(subcommand)]
+    action: UrlAction,
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum UrlAction {
+    /// Percent-encode text
+    Encode(CodecArgs),
+
+    /// Decode percent-encoded text
+    Decode(CodecArgs),
+
+    /// Split a URL into its parts and decode its query parameters
+    Parse(ParseArgs),
+
+    /// Assemble a URL from JSON, as printed by `parse --json`, or add JSON query
+    /// parameters to a base URL
+    Build(BuildArgs),
+}
+
+#[derive(clap::ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Mode {
+    /// A single component such as a query value, like encodeURIComponent()
+    Component,
+    /// A whole URL, its delimiters and existing %XX escapes are kept, like encodeURI()
+    Uri,
+    /// application/x-www-form-urlencoded, spaces are `+`
+    Form,
+}
+
+#[derive(clap::Args, Debug)]
+struct CodecArgs {
+    #[clap(long, arg_enum, default_value = "component")]
+    mode: Mode,
+
+    #[clap(flatten)]
+    input: InputSource,
+}
+
+#[derive(clap::Args, Debug)]
+struct ParseArgs {
+    /// Print the parts as JSON instead of a table
+    #[clap(long)]
+    json: bool,
+
+    #[clap(flatten)]
+    input: InputSource,
+}
+
+#[derive(clap::Args, Debug)]
+struct BuildArgs {
+    /// URL to add the query parameters of the JSON object to
+    #[clap(long, value_name = "URL")]
+    base: Option<String>,
+
+    #[clap(flatten)]
+    input: InputSource,
+}
+
+pub fn run(arg: UrlArg) -> anyhow::Result<()> {
+    match arg.action {
+        UrlAction::Encode(args) => for_input(args.input, |input| {
+            println!("{}", encode(&input, args.mode));
+            Ok(())
+        })
+        .context("URL Encoding"),
+        UrlAction::Decode(args) => for_text_input(args.input, |input| {
+            write_decoded(decode(input.trim(), args.mode), None)
+        })
+        .context("URL Decoding"),
+        UrlAction::Parse(args) => for_text_input(args.input, |input| {
+            let url = Url::parse(input.trim())?;
+            if args.json {
+                println!("{}", serde_json::to_string_pretty(&url.to_json())?);
+            } else {
+                url.print_table();
+            }
+            Ok(())
+        })
+        .context("URL Parse"),
+        UrlAction::Build(args) => for_text_input(args.input, |input| {
+            let json: Value = json::parse(&input).context("Parse Valid JSON")?;
+            let url = match &args.base {
+                Some(base) => {
+                    let mut url = Url::parse(base.trim())?;
+                    url.params.extend(params_from_json(&json)?);
+                    url
+                }
+                None => Url::from_json(&json)?,
+            };
+            println!("{}", url);
+            Ok(())
+        })
+        .context("URL Build"),
+    }
+}
+
+fn is_unreserved(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || matches!(b, b'-' | b'.' | b'_' | b'~')
+}
+
+fn is_reserved(b: u8) -> bool {
+    matches!(
+        b,
+        b':' | b'/'
+            | b'?'
+            | b'#'
+            | b'['
+            | b']'
+            | b'@'
+            | b'!'
+            | b'$'
+            | b'&'
+            | b'\''
+            | b'('
+            | b')'
+            | b'*'
+            | b'+'
+            | b','
+            | b';'
+            | b'='
+    )
+}
+
+fn encode(bytes: &[u8], mode: Mode) -> String {
+    let mut out = String::with_capacity(bytes.len());
+    for (i, &b) in bytes.iter().enumerate() {
+        let keep = match mode {
+            Mode::Component => is_unreserved(b),
+            Mode::Uri => is_unreserved(b) || is_reserved(b) || is_escape(&bytes[i..]),
+            Mode::Form => b.is_ascii_alphanumeric() || matches!(b, b'*' | b'-' | b'.' | b'_'),
+        };
+        match b {
+            _ if keep => out.push(b as char),
+            b' ' if mode == Mode::Form => out.push('+'),
+            _ => out.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    out
+}
+
+/// Whether `bytes` starts with a `%XX` escape.
+fn is_escape(bytes: &[u8]) -> bool {
+    matches!(bytes, [b'%', h, l, ..] if h.is_ascii_hexdigit() && l.is_ascii_hexdigit())
+}
+
+/// Decodes `%XX` escapes, malformed ones are kept as they are like browsers do.
+fn decode(text: &str, mode: Mode) -> Vec<u8> {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' if is_escape(&bytes[i..]) => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or_default();
+                let b = u8::from_str_radix(hex, 16).unwrap_or_default();
+                // decodeURI() leaves the escaped delimiters alone as they change the meaning
+                if mode == Mode::Uri && is_reserved(b) {
+                    out.extend_from_slice(&bytes[i..i + 3]);
+                } else {
+                    out.push(b);
+                }
+                i += 3;
+                continue;
+            }
+            b'+' if mode == Mode::Form => out.push(b' '),
+            b => out.push(b),
+        }
+        i += 1;
+    }
+    out
+}
+
+/// Decodes every `%XX` escape, as in a single URL component.
+pub fn percent_decode(text: &str) -> Vec<u8> {
+    decode(text, Mode::Component)
+}
+
+fn decode_component(text: &str, mode: Mode) -> String {
+    String::from_utf8_lossy(&decode(text, mode)).into_owned()
+}
+
+/// The parts of a URL or relative reference, as split by the regular expression in
+/// appendix B of RFC 3986. Only the query is decoded.
+#[derive(Default)]
+struct Url {
+    scheme: Option<String>,
+    username: Option<String>,
+    password: Option<String>,
+    /// Without the brackets of IPv6 addresses.
+    host: Option<String>,
+    port: Option<u16>,
+    path: String,
+    params: Vec<(String, String)>,
+    fragment: Option<String>,
+}
+
+impl Url {
+    fn parse(text: &str) -> anyhow::Result<Self> {
+        let mut url = Url::default();
+        let mut rest = text;
+
+        if let Some((before, fragment)) = rest.split_once('#') {
+            url.fragment = Some(fragment.to_string());
+            rest = before;
+        }
+        if let Some((before, query)) = rest.split_once('?') {
+            url.params = query
+                .split('&')
+                .filter(|p| !p.is_empty())
+                .map(|p| {
+                    let (name, value) = p.split_once('=').unwrap_or((p, ""));
+                    (
+                        decode_component(name, Mode::Form),
+                        decode_component(value, Mode::Form),
+                    )
+                })
+                .collect();
+            rest = before;
+        }
+        if let Some((scheme, after)) = rest.split_once(':') {
+            let valid = scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+            if valid {
+                url.scheme = Some(scheme.to_string());
+                rest = after;
+            }
+        }
+        if let Some(after) = rest.strip_prefix("//") {
+            let end = after.find('/').unwrap_or(after.len());
+            url.parse_authority(&after[..end])?;
+            rest = &after[end..];
+        }
+        url.path = rest.to_string();
+        Ok(url)
+    }
+
+    fn parse_authority(&mut self, authority: &str) -> anyhow::Result<()> {
+        let host_port = match authority.rsplit_once('@') {
+            Some((userinfo, host_port)) => {
+                let (username, password) = match userinfo.split_once(':') {
+                    Some((u, p)) => (u, Some(p)),
+                    None => (userinfo, None),
+                };
+                self.username = Some(decode_component(username, Mode::Component));
+                self.password = password.map(|p| decode_component(p, Mode::Component));
+                host_port
+            }
+            None => authority,
+        };
+
+        // the port follows the last `:` that is not part of an IPv6 address
+        let port_start = match host_port.rfind(']') {
+            Some(bracket) => host_port[bracket..].find(':').map(|n| bracket + n),
+            None => host_port.rfind(':'),
+        };
+        let host = match port_start {
+            Some(colon) => {
+                // RFC 3986 allows an empty port, which means the default one
+                let port = &host_port[colon + 1..];
+                if !port.is_empty() {
+                    let port = port
+                        .parse()
+                        .map_err(|_| anyhow::anyhow!("Invalid port '{}'", port))?;
+                    self.port = Some(port);
+                }
+                &host_port[..colon]
+            }
+            None => host_port,
+        };
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        self.host = Some(host.to_string());
+        Ok(())
+    }
+
+    fn default_port(&self) -> Option<u16> {
+        match self.scheme.as_deref()?.to_ascii_lowercase().as_str() {
+            "http" | "ws" => Some(80),
+            "https" | "wss" => Some(443),
+            "ftp" => Some(21),
+            "ssh" | "sftp" => Some(22),
+            "postgres" | "postgresql" => Some(5432),
+            "mysql" => Some(3306),
+            "redis" => Some(6379),
+            "mongodb" => Some(27017),
+            "amqp" => Some(5672),
+            _ => None,
+        }
+    }
+
+    fn query(&self) -> String {
+        self.params
+            .iter()
+            .map(|(name, value)| format!("{}={}", encode_param(name), encode_param(value)))
+            .collect::<Vec<_>>()
+            .join("&")
+    }
+
+    fn print_table(&self) {
+        let port = match (self.port, self.default_port()) {
+            (Some(port), _) => Some(port.to_string()),
+            (None, Some(port)) => Some(format!("{} (default)", port)),
+            (None, None) => None,
+        };
+        let query = Some(self.query()).filter(|_| !self.params.is_empty());
+        let rows = [
+            ("scheme", self.scheme.clone()),
+            ("username", self.username.clone()),
+            ("password", self.password.clone()),
+            ("host", self.host.clone()),
+            ("port", port),
+            ("path", Some(self.path.clone())),
+            ("query", query),
+            ("fragment", self.fragment.clone()),
+        ];
+        for (name, value) in rows {
+            if let Some(value) = value {
+                println!("{:<10}{}", name, value);
+            }
+        }
+
+        if !self.params.is_empty() {
+            let width = self.params.iter().map(|(n, _)| n.len()).max().unwrap_or(0);
+            println!();
+            println!("Query parameters:");
+            for (name, value) in &self.params {
+                println!("  {:<width$}  {}", name, value, width = width);
+            }
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        let string = |s: &Option<String>| match s {
+            Some(s) => Value::String(s.clone()),
+            None => Value::Null,
+        };
+
+        // repeated parameters become arrays
+        let mut params = Map::new();
+        for (name, value) in &self.params {
+            let value = Value::String(value.clone());
+            match params.get_mut(name) {
+                Some(Value::Array(values)) => values.push(value),
+                Some(first) => *first = Value::Array(vec![first.clone(), value]),
+                None => {
+                    params.insert(name.clone(), value);
+                }
+            }
+        }
+
+        let mut json = Map::new();
+        json.insert("scheme".to_string(), string(&self.scheme));
+        json.insert("username".to_string(), string(&self.username));
+        json.insert("password".to_string(), string(&self.password));
+        json.insert("host".to_string(), string(&self.host));
+        json.insert(
+            "port".to_string(),
+            self.port.map(Value::from).unwrap_or(Value::Null),
+        );
+        json.insert("path".to_string(), Value::String(self.path.clone()));
+        json.insert("params".to_string(), Value::Object(params));
+        json.insert("fragment".to_string(), string(&self.fragment));
+        Value::Object(json)
+    }
+
+    fn from_json(json: &Value) -> anyhow::Result<Self> {
+        let object = json
+            .as_object()
+            .context("Expected an object with scheme, host, path, params, ...")?;
+        let string = |key: &str| -> anyhow::Result<Option<String>> {
+            match object.get(key) {
+                None | Some(Value::Null) => Ok(None),
+                Some(Value::String(s)) => Ok(Some(s.clone())),
+                Some(v) => Ok(Some(scalar(key, v)?)),
+            }
+        };
+
+        let port = match object.get("port") {
+            None | Some(Value::Null) => None,
+            Some(v) => Some(
+                scalar("port", v)?
+                    .parse()
+                    .context("The port must be a number from 0 to 65535")?,
+            ),
+        };
+        let params = match object.get("params").or_else(|| object.get("query")) {
+            None | Some(Value::Null) => Vec::new(),
+            Some(Value::String(query)) => Url::parse(&format!("?{}", query))?.params,
+            Some(params) => params_from_json(params)?,
+        };
+
+        for key in object.keys() {
+            let known = [
+                "scheme", "username", "password", "host", "port", "path", "params", "query",
+                "fragment",
+            ];
+            if !known.contains(&key.as_str()) {
+                anyhow::bail!(
+                    "Unknown key '{}', use --base to add query parameters to a URL",
+                    key
+                );
+            }
+        }
+
+        Ok(Url {
+            scheme: string("scheme")?,
+            username: string("username")?,
+            password: string("password")?,
+            host: string("host")?,
+            port,
+            path: string("path")?.unwrap_or_default(),
+            params,
+            fragment: string("fragment")?,
+        })
+    }
+}
+
+impl std::fmt::Display for Url {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(scheme) = &self.scheme {
+            write!(f, "{}:", scheme)?;
+        }
+        if let Some(host) = &self.host {
+            f.write_str("//")?;
+            if let Some(username) = &self.username {
+                f.write_str(&encode(username.as_bytes(), Mode::Component))?;
+                if let Some(password) = &self.password {
+                    write!(f, ":{}", encode(password.as_bytes(), Mode::Component))?;
+                }
+                f.write_str("@")?;
+            }
+            if host.contains(':') {
+                write!(f, "[{}]", host)?;
+            } else {
+                f.write_str(&encode(host.as_bytes(), Mode::Uri))?;
+            }
+            if let Some(port) = self.port {
+                write!(f, ":{}", port)?;
+            }
+            if !self.path.is_empty() && !self.path.starts_with('/') {
+                f.write_str("/")?;
+            }
+        }
+        f.write_str(&encode(self.path.as_bytes(), Mode::Uri))?;
+        if !self.params.is_empty() {
+            write!(f, "?{}", self.query())?;
+        }
+        if let Some(fragment) = &self.fragment {
+            write!(f, "#{}", encode(fragment.as_bytes(), Mode::Uri))?;
+        }
+        Ok(())
+    }
+}
+
+/// Query parameters are form encoded, which is how they are decoded.
+fn encode_param(text: &str) -> String {
+    encode(text.as_bytes(), Mode::Form)
+}
+
+/// Parameters from an object, arrays are repeated parameters and null values are skipped.
+fn params_from_json(json: &Value) -> anyhow::Result<Vec<(String, String)>> {
+    let object = json
+        .as_object()
+        .context("Query parameters must be a JSON object")?;
+    let mut params = Vec::new();
+    for (name, value) in object {
+        match value {
+            Value::Null => {}
+            Value::Array(values) => {
+                for value in values {
+                    params.push((name.clone(), scalar(name, value)?));
+                }
+            }
+            value => params.push((name.clone(), scalar(name, value)?)),
+        }
+    }
+    Ok(params)
+}
+
+fn scalar(name: &str, value: &Value) -> anyhow::Result<String> {
+    match value {
+        Value::String(s) => Ok(s.clone()),
+        Value::Number(n) => Ok(n.to_string()),
+        Value::Bool(b) => Ok(b.to_string()),
+        _ => anyhow::bail!("'{}' must be a string, number or boolean", name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, encode, Mode, Url};
+    use serde_json::json;
+
+    #[test]
+    fn encoding() {
+        let text = "a b&c=d/é~*%41";
+        assert_eq!(
+            encode(text.as_bytes(), Mode::Component),
+            "a%20b%26c%3Dd%2F%C3%A9~%2A%2541"
+        );
+        // the delimiters and the escapes already there are kept
+        assert_eq!(encode(text.as_bytes(), Mode::Uri), "a%20b&c=d/%C3%A9~*%41");
+        assert_eq!(
+            encode(text.as_bytes(), Mode::Form),
+            "a+b%26c%3Dd%2F%C3%A9%7E*%2541"
+        );
+        assert_eq!(encode(b"100%", Mode::Uri), "100%25");
+    }
+
+    #[test]
+    fn decoding() {
+        assert_eq!(
+            decode("a%20b+c%C3%A9", Mode::Component),
+            "a b+cé".as_bytes()
+        );
+        assert_eq!(decode("a%20b+c", Mode::Form), b"a b c");
+        // decodeURI() keeps the escaped delimiters, which would change the meaning
+        assert_eq!(decode("%2Fa%20b%3F%26%41%7E", Mode::Uri), b"%2Fa b%3F%26A~");
+        assert_eq!(decode("%2Fa%3F", Mode::Component), b"/a?");
+        // malformed escapes are kept as they are
+        assert_eq!(decode("%zz%4%", Mode::Component), b"%zz%4%");
+    }
+
+    #[test]
+    fn parts() {
+        let url =
+            Url::parse("https://us%40er:p%3Ass@[2001:db8::1]:8443/a/b?x=1&y=a+b%26#top").unwrap();
+        assert_eq!(url.scheme.as_deref(), Some("https"));
+        assert_eq!(url.username.as_deref(), Some("us@er"));
+        assert_eq!(url.password.as_deref(), Some("p:ss"));
+        assert_eq!(url.host.as_deref(), Some("2001:db8::1"));
+        assert_eq!(url.port, Some(8443));
+        assert_eq!(url.path, "/a/b");
+        assert_eq!(
+            url.params,
+            [
+                ("x".to_string(), "1".to_string()),
+                ("y".to_string(), "a b&".to_string())
+            ]
+        );
+        assert_eq!(url.fragment.as_deref(), Some("top"));
+
+        let url = Url::parse("ssh://git@[::1]/repo").unwrap();
+        assert_eq!(url.username.as_deref(), Some("git"));
+        assert_eq!(url.password, None);
+        assert_eq!(url.host.as_deref(), Some("::1"));
+        assert_eq!((url.port, url.default_port()), (None, Some(22)));
+
+        // only the last `@` ends the userinfo
+        let url = Url::parse("http://a@b:c@host:/").unwrap();
+        assert_eq!(url.username.as_deref(), Some("a@b"));
+        assert_eq!(url.password.as_deref(), Some("c"));
+        assert_eq!((url.host.as_deref(), url.port), (Some("host"), None));
+
+        let url = Url::parse("mailto:someone@example.com").unwrap();
+        assert_eq!(url.host, None);
+        assert_eq!(url.path, "someone@example.com");
+
+        let url = Url::parse("../a:b/c?q").unwrap();
+        assert_eq!(url.scheme, None);
+        assert_eq!(url.path, "../a:b/c");
+        assert_eq!(url.params, [("q".to_string(), String::new())]);
+    }
+
+    #[test]
+    fn ports() {
+        for port in ["99999", "8o", "-1", "0x50"] {
+            let error = Url::parse(&format!("http://host:{}/", port)).err().unwrap();
+            assert_eq!(error.to_string(), format!("Invalid port '{}'", port));
+        }
+        assert!(Url::parse("http://[::1]:65536").is_err());
+        assert_eq!(Url::parse("http://host:65535").unwrap().port, Some(65535));
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let urls = [
+            "https://us%40er:p%3Ass@[2001:db8::1]:8443/a/b?x=1&x=2&y=a+b%26#top",
+            "postgres://localhost/db?sslmode=require",
+            "mailto:someone@example.com",
+            "//cdn.example.com/lib.js",
+            "/search?q=caf%C3%A9&empty=",
+        ];
+        for text in urls {
+            let json = Url::parse(text).unwrap().to_json();
+            let url = Url::from_json(&json).unwrap();
+            assert_eq!(url.to_string(), text);
+        }
+
+        let json = Url::parse("http://h/p?x=1&x=2&y=3").unwrap().to_json();
+        assert_eq!(
+            json,
+            json!({
+                "scheme": "http",
+                "username": null,
+                "password": null,
+                "host": "h",
+                "port": null,
+                "path": "/p",
+                "params": {"x": ["1", "2"], "y": "3"},
+                "fragment": null
+            })
+        );
+        assert!(Url::from_json(&json!({"host": "h", "port": 70000})).is_err());
+        assert!(Url::from_json(&json!({"host": "h", "other": 1})).is_err());
+    }
+}